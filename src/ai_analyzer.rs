@@ -1,9 +1,17 @@
 use crate::cache::ApiCache;
-use crate::templates::{get_template_path, Templates};
+use crate::chunker;
+use crate::config::ProviderKind;
+use crate::error::ApiError;
+use crate::i18n::Catalog;
+use crate::logger;
+use crate::provider::{ChatMessage, CompletionRequest, LlmProvider, ResponseSchema};
+use crate::templates::Templates;
 use crate::token_tracker::{TokenTracker, TokenUsage};
+use futures::stream::{self, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AiAnalysisRequest {
@@ -17,6 +25,12 @@ pub struct ConversationData {
     pub user_messages: Vec<String>,
     pub assistant_actions: Vec<String>,
     pub timestamps: Vec<String>,
+    /// Conversation summaries written by Claude Code, sent with every chunk
+    #[serde(default)]
+    pub summaries: Vec<String>,
+    /// Commits made in the project's repository during the period (`abc1234 Subject`)
+    #[serde(default)]
+    pub commits: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,29 +59,104 @@ pub struct AiActivity {
     pub technical_details: Option<String>,
 }
 
+/// How often a reply that is not valid JSON is sent back to the model for repair
+const MAX_REPAIR_ATTEMPTS: u32 = 1;
+
+/// Read-only state shared by every project analysis
+struct AnalysisContext<'a> {
+    provider: Arc<dyn LlmProvider>,
+    cache: Option<ApiCache>,
+    templates: &'a Templates,
+    lang: String,
+}
+
+/// Successful analysis of a single project
+struct ProjectAnalysis {
+    response: AiAnalysisResponse,
+    usage: Option<TokenUsage>,
+    /// Number of API requests made (0 when served from cache)
+    requests: u32,
+    retries: u32,
+}
+
+/// Failed analysis of a single project, with the requests made before it failed
+struct AnalysisFailure {
+    error: ApiError,
+    tally: RequestTally,
+}
+
+/// Request statistics for one AI analysis run
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ApiStats {
+    pub requests: u32,
+    pub retries: u32,
+    pub failures: Vec<ProjectFailure>,
+}
+
+/// A project whose analysis failed after all retries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectFailure {
+    pub project: String,
+    pub error: String,
+}
+
+impl ApiStats {
+    /// Whether anything worth reporting happened (retries or failures)
+    pub fn has_issues(&self) -> bool {
+        self.retries > 0 || !self.failures.is_empty()
+    }
+
+    pub fn get_summary_string(&self, lang: &str) -> String {
+        let mut output = String::new();
+
+        let catalog = Catalog::new(lang);
+        output.push_str(&format!("\n## {}\n\n", catalog.get("api.heading")));
+        output.push_str(&format!(
+            "- {}: {}\n",
+            catalog.get("api.requests"),
+            self.requests
+        ));
+        output.push_str(&format!(
+            "- {}: {}\n",
+            catalog.get("api.retries"),
+            self.retries
+        ));
+        if !self.failures.is_empty() {
+            output.push_str(&format!(
+                "- {}: {}\n",
+                catalog.get("api.failed"),
+                self.failures.len()
+            ));
+        }
+
+        for failure in &self.failures {
+            output.push_str(&format!("  - {}: {}\n", failure.project, failure.error));
+        }
+
+        output
+    }
+}
+
+/// Analyze every project with the AI provider.
+///
+/// Projects are pulled from a single work queue with at most `parallel` requests
+/// in flight, so a slow project never holds back the others. `parallel = 1`
+/// analyzes the projects one after another.
+///
+/// Transient API failures are retried by the provider; projects that still fail
+/// are recorded in the returned [`ApiStats`] instead of aborting the run.
 pub async fn analyze_with_ai(
-    api_key: &str,
+    provider: Arc<dyn LlmProvider>,
+    templates: &Templates,
     conversations: Vec<ConversationData>,
     lang: &str,
-    model: &str,
     quiet: bool,
     parallel: usize,
-) -> Result<(HashMap<String, AiAnalysisResponse>, TokenTracker), Box<dyn std::error::Error>> {
-    // Use parallel implementation if requested
-    if parallel > 1 {
-        return crate::ai_analyzer_parallel::analyze_with_ai_parallel(
-            api_key,
-            conversations,
-            lang,
-            model,
-            quiet,
-            parallel,
-        )
-        .await;
-    }
-    let client = reqwest::Client::new();
+) -> Result<(HashMap<String, AiAnalysisResponse>, TokenTracker, ApiStats), Box<dyn std::error::Error>>
+{
     let mut results = HashMap::new();
     let mut token_tracker = TokenTracker::new();
+    let mut api_stats = ApiStats::default();
 
     // Initialize cache
     let cache = match ApiCache::new() {
@@ -78,17 +167,11 @@ pub async fn analyze_with_ai(
         }
     };
 
-    // Load templates
-    let templates = if let Some(template_path) = get_template_path() {
-        match Templates::load_from_file(&template_path) {
-            Ok(t) => Templates::merge_with_defaults(Some(t)),
-            Err(e) => {
-                eprintln!("Template loading error: {e}. Using defaults.");
-                Templates::default()
-            }
-        }
-    } else {
-        Templates::default()
+    let ctx = AnalysisContext {
+        provider: Arc::clone(&provider),
+        cache,
+        templates,
+        lang: lang.to_string(),
     };
 
     // Create progress bar
@@ -108,194 +191,393 @@ pub async fn analyze_with_ai(
         pb
     };
 
-    for conv in conversations {
-        pb.set_message(format!("Analyzing: {}", conv.project_name));
-
-        // Check cache first
-        if let Some(ref cache) = cache {
-            let cache_key_messages = vec![
-                conv.user_messages.join("\n"),
-                conv.assistant_actions.join("\n"),
-            ];
-
-            if let Some(cached_response) =
-                cache.get(&conv.project_name, lang, model, &cache_key_messages)
-            {
-                results.insert(conv.project_name.clone(), cached_response);
+    let mut analyses = stream::iter(conversations)
+        .map(|conv| {
+            let ctx = &ctx;
+            let pb = &pb;
+            async move {
+                pb.set_message(format!("Analyzing: {}", conv.project_name));
+                let analysis = analyze_conversation(ctx, &conv).await;
                 pb.inc(1);
-                continue;
+                (conv.project_name, analysis)
+            }
+        })
+        .buffer_unordered(parallel.max(1));
+
+    while let Some((project_name, analysis)) = analyses.next().await {
+        match analysis {
+            Ok(analysis) => {
+                api_stats.requests += analysis.requests;
+                api_stats.retries += analysis.retries;
+                if let Some(usage) = analysis.usage {
+                    token_tracker.add_usage(&project_name, usage);
+                }
+                results.insert(project_name, analysis.response);
+            }
+            Err(AnalysisFailure { error, tally }) => {
+                // Chunks analyzed before the failure were still sent and billed
+                api_stats.requests += tally.requests;
+                api_stats.retries += tally.retries;
+                if let Some(usage) = tally.usage {
+                    token_tracker.add_usage(&project_name, usage);
+                }
+                pb.suspend(|| {
+                    eprintln!(
+                        "{} API error for project '{}': {}",
+                        provider.kind().display_name(),
+                        project_name,
+                        error
+                    )
+                });
+                api_stats.failures.push(ProjectFailure {
+                    project: project_name,
+                    error: error.to_string(),
+                });
             }
         }
+    }
 
-        let prompt = create_analysis_prompt(&conv, lang, &templates);
-
-        let default_instruction = get_language_instruction(lang).to_string();
-        let language_instruction = templates
-            .prompts
-            .language_instructions
-            .get(lang)
-            .unwrap_or(&default_instruction);
-
-        let system_message = templates
-            .prompts
-            .system_message
-            .replace("{language_instruction}", language_instruction);
-
-        let model_name = match model {
-            "gpt-4o" => "chatgpt-4o-latest",
-            "gpt-4o-mini" => "gpt-4o-mini",
-            "gpt-4-turbo" => "gpt-4-turbo-preview",
-            "gpt-3.5-turbo" => "gpt-3.5-turbo",
-            _ => model, // fallback to provided name
-        };
-
-        let request_body = serde_json::json!({
-            "model": model_name,
-            "messages": [
-                {
-                    "role": "system",
-                    "content": system_message
-                },
-                {
-                    "role": "user",
-                    "content": prompt
-                }
-            ],
-            "temperature": 0.3
-        });
-
-        match client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await
+    pb.finish_with_message("AI analysis complete!");
+
+    // Calculate cost estimate (local models are free)
+    if provider.kind() != ProviderKind::Ollama {
+        token_tracker.calculate_cost(provider.model());
+    }
+
+    Ok((results, token_tracker, api_stats))
+}
+
+async fn analyze_conversation(
+    ctx: &AnalysisContext<'_>,
+    conv: &ConversationData,
+) -> Result<ProjectAnalysis, AnalysisFailure> {
+    let model = ctx.provider.model();
+    let cache_key_messages = cache_key_messages(conv);
+
+    // Check cache first
+    if let Some(ref cache) = ctx.cache {
+        if let Some(cached_response) =
+            cache.get(&conv.project_name, &ctx.lang, model, &cache_key_messages)
         {
-            Ok(response) => {
-                if response.status().is_success() {
-                    match response.json::<serde_json::Value>().await {
-                        Ok(api_response) => {
-                            if let Some(content) = api_response
-                                .get("choices")
-                                .and_then(|choices| choices.as_array())
-                                .and_then(|arr| arr.first())
-                                .and_then(|choice| choice.get("message"))
-                                .and_then(|message| message.get("content"))
-                                .and_then(|content| content.as_str())
-                            {
-                                // Remove markdown code blocks if present
-                                let cleaned_content = if content.starts_with("```json")
-                                    && content.ends_with("```")
-                                {
-                                    content
-                                        .trim_start_matches("```json")
-                                        .trim_end_matches("```")
-                                        .trim()
-                                } else if content.starts_with("```") && content.ends_with("```") {
-                                    content
-                                        .trim_start_matches("```")
-                                        .trim_end_matches("```")
-                                        .trim()
-                                } else {
-                                    content
-                                };
-
-                                match serde_json::from_str::<AiAnalysisResponse>(cleaned_content) {
-                                    Ok(analysis) => {
-                                        // Track token usage
-                                        if let Some(usage) = api_response["usage"].as_object() {
-                                            let token_usage = TokenUsage {
-                                                prompt_tokens: usage["prompt_tokens"]
-                                                    .as_u64()
-                                                    .unwrap_or(0)
-                                                    as u32,
-                                                completion_tokens: usage["completion_tokens"]
-                                                    .as_u64()
-                                                    .unwrap_or(0)
-                                                    as u32,
-                                                total_tokens: usage["total_tokens"]
-                                                    .as_u64()
-                                                    .unwrap_or(0)
-                                                    as u32,
-                                            };
-                                            token_tracker
-                                                .add_usage(&conv.project_name, token_usage);
-                                        }
-
-                                        // Store in cache
-                                        if let Some(ref cache) = cache {
-                                            let cache_key_messages = vec![
-                                                conv.user_messages.join("\n"),
-                                                conv.assistant_actions.join("\n"),
-                                            ];
-                                            if let Err(e) = cache.set(
-                                                &conv.project_name,
-                                                lang,
-                                                model,
-                                                &cache_key_messages,
-                                                &analysis,
-                                            ) {
-                                                eprintln!("Cache save error: {e}");
-                                            }
-                                        }
-
-                                        results.insert(conv.project_name.clone(), analysis);
-                                    }
-                                    Err(e) => {
-                                        eprintln!(
-                                            "JSON parsing error for project '{}': {}",
-                                            conv.project_name, e
-                                        );
-                                        eprintln!("Invalid JSON: {cleaned_content}");
-                                    }
-                                }
-                            } else {
-                                eprintln!(
-                                    "No content in API response for project '{}'",
-                                    conv.project_name
-                                );
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "Response parsing error for project '{}': {}",
-                                conv.project_name, e
-                            );
-                        }
-                    }
-                } else {
-                    let status = response.status();
-                    match response.text().await {
-                        Ok(error_text) => {
-                            eprintln!(
-                                "OpenAI API error for project '{}': {} - {}",
-                                conv.project_name, status, error_text
-                            );
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "Failed to get error text for project '{}': {}",
-                                conv.project_name, e
-                            );
-                        }
-                    }
+            return Ok(ProjectAnalysis {
+                response: cached_response,
+                usage: None,
+                requests: 0,
+                retries: 0,
+            });
+        }
+    }
+
+    let mut tally = RequestTally::default();
+    let analysis = match map_reduce(ctx, conv, &mut tally).await {
+        Ok(analysis) => analysis,
+        Err(error) => return Err(AnalysisFailure { error, tally }),
+    };
+
+    // Store in cache
+    if let Some(ref cache) = ctx.cache {
+        if let Err(e) = cache.set(
+            &conv.project_name,
+            &ctx.lang,
+            model,
+            &cache_key_messages,
+            &analysis,
+        ) {
+            eprintln!("Cache save error: {e}");
+        }
+    }
+
+    Ok(ProjectAnalysis {
+        response: analysis,
+        usage: tally.usage,
+        requests: tally.requests,
+        retries: tally.retries,
+    })
+}
+
+/// Analyze every context-sized piece of the conversation, then merge the results
+async fn map_reduce(
+    ctx: &AnalysisContext<'_>,
+    conv: &ConversationData,
+    tally: &mut RequestTally,
+) -> Result<AiAnalysisResponse, ApiError> {
+    let system = create_system_message(&ctx.lang, ctx.templates);
+
+    // Map: analyze every context-sized piece of the conversation on its own
+    let chunks = chunker::split_conversation(conv, chunk_budget(ctx, &system, conv));
+    let chunk_count = chunks.len();
+    let mut partials = Vec::with_capacity(chunk_count);
+    for (index, chunk) in chunks.iter().enumerate() {
+        let mut prompt = create_analysis_prompt(chunk, &ctx.lang, ctx.templates);
+        if chunk_count > 1 {
+            prompt.push_str(&format!(
+                "\n\n(This is part {} of {} of the conversation.)",
+                index + 1,
+                chunk_count
+            ));
+        }
+        partials.push(request_analysis(ctx, conv, &system, prompt, tally).await?);
+    }
+
+    // Reduce: merge the partial analyses into one
+    reduce_analyses(ctx, conv, &system, partials, tally).await
+}
+
+/// Tokens of transcript that fit into one analysis request for this project
+fn chunk_budget(ctx: &AnalysisContext<'_>, system: &str, conv: &ConversationData) -> usize {
+    let scaffold = ConversationData {
+        project_name: conv.project_name.clone(),
+        user_messages: Vec::new(),
+        assistant_actions: Vec::new(),
+        timestamps: Vec::new(),
+        summaries: conv.summaries.clone(),
+        commits: conv.commits.clone(),
+    };
+    let overhead = chunker::estimate_tokens(system)
+        + chunker::estimate_tokens(&create_analysis_prompt(&scaffold, &ctx.lang, ctx.templates))
+        + chunker::estimate_tokens(&analysis_response_schema().schema.to_string());
+
+    chunker::chunk_budget(ctx.provider.context_limit(), overhead)
+}
+
+/// Merge partial analyses until one is left, in as few requests as the context allows
+async fn reduce_analyses(
+    ctx: &AnalysisContext<'_>,
+    conv: &ConversationData,
+    system: &str,
+    mut partials: Vec<AiAnalysisResponse>,
+    tally: &mut RequestTally,
+) -> Result<AiAnalysisResponse, ApiError> {
+    let overhead = chunker::estimate_tokens(system)
+        + chunker::estimate_tokens(&create_reduce_prompt(&conv.project_name, &[], &ctx.lang))
+        + chunker::estimate_tokens(&analysis_response_schema().schema.to_string());
+    let budget = chunker::chunk_budget(ctx.provider.context_limit(), overhead);
+
+    while partials.len() > 1 {
+        // Every group holds at least two partials so each round shrinks the list
+        let mut groups: Vec<Vec<AiAnalysisResponse>> = Vec::new();
+        let mut group_tokens = 0;
+        for partial in partials {
+            let tokens =
+                chunker::estimate_tokens(&serde_json::to_string(&partial).unwrap_or_default());
+            match groups.last_mut() {
+                Some(group) if group.len() < 2 || group_tokens + tokens <= budget => {
+                    group.push(partial);
+                    group_tokens += tokens;
+                }
+                _ => {
+                    groups.push(vec![partial]);
+                    group_tokens = tokens;
                 }
             }
+        }
+
+        partials = Vec::with_capacity(groups.len());
+        for mut group in groups {
+            if group.len() == 1 {
+                partials.extend(group.pop());
+                continue;
+            }
+            let prompt = create_reduce_prompt(&conv.project_name, &group, &ctx.lang);
+            partials.push(request_analysis(ctx, conv, system, prompt, tally).await?);
+        }
+    }
+
+    partials
+        .pop()
+        .ok_or_else(|| ApiError::InvalidResponse("no analysis produced".to_string()))
+}
+
+/// Token usage and request counts accumulated over the requests for one project
+#[derive(Default)]
+struct RequestTally {
+    usage: Option<TokenUsage>,
+    requests: u32,
+    retries: u32,
+}
+
+/// Ask the model for an analysis, sending unparsable replies back for repair
+async fn request_analysis(
+    ctx: &AnalysisContext<'_>,
+    conv: &ConversationData,
+    system: &str,
+    prompt: String,
+    tally: &mut RequestTally,
+) -> Result<AiAnalysisResponse, ApiError> {
+    let mut request = CompletionRequest {
+        system: system.to_string(),
+        messages: vec![ChatMessage::user(prompt)],
+        response_schema: Some(analysis_response_schema()),
+    };
+    let mut repairs = 0;
+
+    loop {
+        tally.requests += 1;
+        let completion = ctx.provider.complete(&request).await.map_err(|e| {
+            tally.retries += e.retries();
+            e
+        })?;
+        tally.retries += completion.retries;
+        tally.usage = merge_usage(tally.usage.take(), completion.usage);
+
+        match parse_analysis_response(&completion.text) {
+            Ok(analysis) => return Ok(analysis),
+            Err(e) if repairs < MAX_REPAIR_ATTEMPTS => {
+                // Show the model its own reply and the parse error, then ask again
+                logger::warn(&format!(
+                    "Invalid JSON for project '{}' ({e}), asking the model to repair it",
+                    conv.project_name
+                ));
+                request
+                    .messages
+                    .push(ChatMessage::assistant(completion.text));
+                request.messages.push(ChatMessage::user(repair_prompt(&e)));
+                repairs += 1;
+            }
             Err(e) => {
-                eprintln!("API call error for project '{}': {}", conv.project_name, e);
+                return Err(ApiError::InvalidResponse(format!(
+                    "JSON parsing error: {e}. Invalid JSON: {}",
+                    strip_code_fences(&completion.text)
+                )))
             }
         }
+    }
+}
 
-        pb.inc(1);
+/// Prompt asking the model to merge partial analyses of one project
+fn create_reduce_prompt(project_name: &str, partials: &[AiAnalysisResponse], lang: &str) -> String {
+    let (_, structure) = get_prompt_templates(lang);
+    let partials = partials
+        .iter()
+        .map(|partial| serde_json::to_string_pretty(partial).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n---\n");
+
+    format!(
+        "The conversation of project \"{project_name}\" was too long for one request, so it was analyzed in parts. \
+         Merge the partial analyses below into a single analysis of the whole project: \
+         combine activities of the same category, keep the most significant achievements and challenges, \
+         and write one title, purpose and insight for the project.\n\n\
+         Respond in this JSON format:\n{structure}\n\n\
+         Partial analyses:\n{partials}"
+    )
+}
+
+/// Parse the model's reply, tolerating markdown code fences around the JSON
+pub fn parse_analysis_response(content: &str) -> Result<AiAnalysisResponse, serde_json::Error> {
+    serde_json::from_str(strip_code_fences(content))
+}
+
+/// Follow-up message asking the model to fix a reply that did not parse
+fn repair_prompt(error: &serde_json::Error) -> String {
+    format!(
+        "Your previous reply could not be parsed as the requested JSON: {error}. \
+         Reply again with only the corrected JSON object, following the schema exactly."
+    )
+}
+
+fn merge_usage(total: Option<TokenUsage>, usage: Option<TokenUsage>) -> Option<TokenUsage> {
+    match (total, usage) {
+        (Some(total), Some(usage)) => Some(TokenUsage {
+            prompt_tokens: total.prompt_tokens + usage.prompt_tokens,
+            completion_tokens: total.completion_tokens + usage.completion_tokens,
+            total_tokens: total.total_tokens + usage.total_tokens,
+        }),
+        (total, usage) => total.or(usage),
     }
+}
 
-    pb.finish_with_message("AI analysis complete!");
+/// JSON schema of [`AiAnalysisResponse`], used for the provider's structured output
+///
+/// Written for OpenAI's strict mode: every property is required and optional
+/// fields are nullable instead of omitted.
+pub fn analysis_response_schema() -> ResponseSchema {
+    let string = || serde_json::json!({ "type": "string" });
+    let string_list = || serde_json::json!({ "type": "array", "items": { "type": "string" } });
+
+    ResponseSchema {
+        name: "work_analysis".to_string(),
+        description: "Structured summary of the work done in one project".to_string(),
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "project_title": string(),
+                "project_purpose": string(),
+                "main_activities": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "category": string(),
+                            "description": string(),
+                            "impact": string(),
+                            "technical_details": { "type": ["string", "null"] }
+                        },
+                        "required": ["category", "description", "impact", "technical_details"],
+                        "additionalProperties": false
+                    }
+                },
+                "achievements": string_list(),
+                "challenges": string_list(),
+                "insights": string()
+            },
+            "required": [
+                "project_title",
+                "project_purpose",
+                "main_activities",
+                "achievements",
+                "challenges",
+                "insights"
+            ],
+            "additionalProperties": false
+        }),
+    }
+}
 
-    // Calculate cost estimate
-    token_tracker.calculate_cost(model);
+fn cache_key_messages(conv: &ConversationData) -> Vec<String> {
+    let mut messages = vec![
+        conv.user_messages.join("\n"),
+        conv.assistant_actions.join("\n"),
+    ];
+    if !conv.summaries.is_empty() {
+        messages.push(conv.summaries.join("\n"));
+    }
+    if !conv.commits.is_empty() {
+        messages.push(conv.commits.join("\n"));
+    }
+    messages
+}
 
-    Ok((results, token_tracker))
+/// Remove markdown code blocks the model may wrap its JSON in
+fn strip_code_fences(content: &str) -> &str {
+    let content = content.trim();
+    if content.starts_with("```json") && content.ends_with("```") {
+        content
+            .trim_start_matches("```json")
+            .trim_end_matches("```")
+            .trim()
+    } else if content.starts_with("```") && content.ends_with("```") {
+        content
+            .trim_start_matches("```")
+            .trim_end_matches("```")
+            .trim()
+    } else {
+        content
+    }
+}
+
+fn create_system_message(lang: &str, templates: &Templates) -> String {
+    let default_instruction = get_language_instruction(lang).to_string();
+    let language_instruction = templates
+        .prompts
+        .language_instructions
+        .get(lang)
+        .unwrap_or(&default_instruction);
+
+    templates
+        .prompts
+        .system_message
+        .replace("{language_instruction}", language_instruction)
 }
 
 fn get_language_instruction(lang: &str) -> &'static str {
@@ -438,15 +720,64 @@ fn get_prompt_templates(lang: &str) -> (&'static str, &'static str) {
 fn create_analysis_prompt(conv: &ConversationData, lang: &str, templates: &Templates) -> String {
     let (instructions, structure) = get_prompt_templates(lang);
 
+    let summaries = format_session_summaries(&conv.summaries);
+    let commits = format_commits(&conv.commits);
+
     // Use template if available, otherwise use default format
-    templates
+    let mut prompt = templates
         .prompts
         .user_prompt
         .replace("{instructions}", instructions)
         .replace("{structure}", structure)
         .replace("{project_name}", &conv.project_name)
         .replace("{user_messages}", &conv.user_messages.join("\n---\n"))
-        .replace("{assistant_actions}", &conv.assistant_actions.join("\n"))
+        .replace("{assistant_actions}", &conv.assistant_actions.join("\n"));
+
+    // Custom templates written before summaries existed still get them
+    if prompt.contains("{session_summaries}") {
+        prompt = prompt.replace("{session_summaries}", &summaries);
+    } else if !summaries.is_empty() {
+        prompt.push_str("\n\n");
+        prompt.push_str(summaries.trim_end());
+    }
+    if prompt.contains("{commits}") {
+        prompt = prompt.replace("{commits}", &commits);
+    } else if !commits.is_empty() {
+        prompt.push_str("\n\n");
+        prompt.push_str(commits.trim_end());
+    }
+    prompt
+}
+
+/// Commit subjects from the project's repository: what was actually delivered
+fn format_commits(commits: &[String]) -> String {
+    if commits.is_empty() {
+        return String::new();
+    }
+
+    let mut section =
+        "Commits (made in this period; treat as ground truth for what was delivered):\n"
+            .to_string();
+    for commit in commits {
+        section.push_str(&format!("- {commit}\n"));
+    }
+    section.push('\n');
+    section
+}
+
+/// Claude Code's own conversation summaries, the most reliable description of what happened
+fn format_session_summaries(summaries: &[String]) -> String {
+    if summaries.is_empty() {
+        return String::new();
+    }
+
+    let mut section =
+        "Session Summaries (written by Claude Code; treat as reliable context):\n".to_string();
+    for summary in summaries {
+        section.push_str(&format!("- {summary}\n"));
+    }
+    section.push('\n');
+    section
 }
 
 // Alternative approach using local LLM or embedding-based clustering
@@ -0,0 +1,179 @@
+//! Token budgeting for AI prompts
+//!
+//! A busy week or month of conversations does not fit into one request. The
+//! chunker estimates the token size of a project's [`ConversationData`] and
+//! splits it into pieces that each fit the model's context window; the
+//! analyzer summarizes every piece and merges the partial results.
+
+use crate::ai_analyzer::ConversationData;
+
+/// Context window assumed for models we know nothing about (typical local model)
+pub const DEFAULT_CONTEXT_LIMIT: usize = 8_192;
+
+/// Tokens kept free for the model's reply
+pub const RESERVED_OUTPUT_TOKENS: usize = 4_096;
+
+/// Smallest transcript budget a chunk gets, however small the context window
+pub const MIN_CHUNK_TOKENS: usize = 512;
+
+/// Rough token count of `text` without a model specific tokenizer
+///
+/// English averages about four characters per token, while CJK text is close
+/// to one token per character; counting the two separately keeps Japanese and
+/// Chinese transcripts from being underestimated.
+pub fn estimate_tokens(text: &str) -> usize {
+    let (ascii, other) = text.chars().fold((0usize, 0usize), |(ascii, other), c| {
+        if c.is_ascii() {
+            (ascii + 1, other)
+        } else {
+            (ascii, other + 1)
+        }
+    });
+    ascii.div_ceil(4) + other
+}
+
+/// Context window of a model in tokens
+pub fn context_limit_for_model(model: &str) -> usize {
+    let model = model.to_lowercase();
+    if model.starts_with("gpt-4.1") {
+        1_047_576
+    } else if model.starts_with("gpt-4o")
+        || model.starts_with("chatgpt-4o")
+        || model.starts_with("gpt-4-turbo")
+        || model.starts_with("o1")
+        || model.starts_with("o3")
+        || model.starts_with("o4")
+    {
+        128_000
+    } else if model.starts_with("gpt-3.5-turbo") {
+        16_385
+    } else if model.starts_with("gpt-4") {
+        8_192
+    } else if model.starts_with("claude") {
+        200_000
+    } else {
+        DEFAULT_CONTEXT_LIMIT
+    }
+}
+
+/// Tokens left for the transcript once the prompt scaffolding and the reply are accounted for
+pub fn chunk_budget(context_limit: usize, overhead_tokens: usize) -> usize {
+    context_limit
+        .saturating_sub(overhead_tokens + RESERVED_OUTPUT_TOKENS)
+        .max(MIN_CHUNK_TOKENS)
+}
+
+/// Tokens the transcript of `conv` adds to the analysis prompt
+pub fn conversation_tokens(conv: &ConversationData) -> usize {
+    list_tokens(&conv.user_messages) + list_tokens(&conv.assistant_actions)
+}
+
+/// Split a conversation into pieces whose transcript fits into `budget` tokens
+///
+/// User messages and assistant actions are split into the same number of
+/// contiguous, similarly sized groups so each piece covers roughly the same
+/// stretch of the conversation. A single message larger than the budget is
+/// truncated. Returns the conversation unchanged when it already fits.
+pub fn split_conversation(conv: &ConversationData, budget: usize) -> Vec<ConversationData> {
+    let budget = budget.max(1);
+    if conversation_tokens(conv) <= budget {
+        return vec![conv.clone()];
+    }
+
+    // Messages are cut to half the budget so a user message and an action can share a chunk
+    let item_budget = (budget / 2).max(1);
+    let user_messages: Vec<String> = conv
+        .user_messages
+        .iter()
+        .map(|m| truncate_to_tokens(m, item_budget))
+        .collect();
+    let assistant_actions: Vec<String> = conv
+        .assistant_actions
+        .iter()
+        .map(|a| truncate_to_tokens(a, item_budget))
+        .collect();
+
+    let total = list_tokens(&user_messages) + list_tokens(&assistant_actions);
+    let max_chunks = user_messages.len().max(assistant_actions.len()).max(1);
+    let mut chunk_count = total.div_ceil(budget).clamp(1, max_chunks);
+
+    loop {
+        let user_groups = split_balanced(&user_messages, chunk_count);
+        let action_groups = split_balanced(&assistant_actions, chunk_count);
+        let fits = user_groups
+            .iter()
+            .zip(&action_groups)
+            .all(|(users, actions)| list_tokens(users) + list_tokens(actions) <= budget);
+
+        if fits || chunk_count >= max_chunks {
+            let timestamp_groups = split_evenly(&conv.timestamps, chunk_count);
+            return user_groups
+                .into_iter()
+                .zip(action_groups)
+                .zip(timestamp_groups)
+                .filter(|((users, actions), _)| !users.is_empty() || !actions.is_empty())
+                .map(
+                    |((user_messages, assistant_actions), timestamps)| ConversationData {
+                        project_name: conv.project_name.clone(),
+                        user_messages,
+                        assistant_actions,
+                        timestamps,
+                        summaries: conv.summaries.clone(),
+                        commits: conv.commits.clone(),
+                    },
+                )
+                .collect();
+        }
+
+        chunk_count += 1;
+    }
+}
+
+/// Cut `text` so that it stays within `max_tokens`
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> String {
+    if estimate_tokens(text) <= max_tokens {
+        return text.to_string();
+    }
+
+    let mut tokens = 0.0;
+    let mut end = 0;
+    for (index, c) in text.char_indices() {
+        tokens += if c.is_ascii() { 0.25 } else { 1.0 };
+        if tokens > max_tokens as f64 {
+            break;
+        }
+        end = index + c.len_utf8();
+    }
+    format!("{}…", &text[..end])
+}
+
+fn list_tokens(items: &[String]) -> usize {
+    // Every item is joined with a separator line in the prompt
+    items.iter().map(|item| estimate_tokens(item) + 2).sum()
+}
+
+/// Split `items` into `count` contiguous groups of roughly equal token weight
+fn split_balanced(items: &[String], count: usize) -> Vec<Vec<String>> {
+    let total = list_tokens(items);
+    let mut groups: Vec<Vec<String>> = vec![Vec::new(); count];
+    let mut consumed = 0;
+
+    for item in items {
+        // Place each item by the position of its midpoint within the whole list
+        let weight = estimate_tokens(item) + 2;
+        let midpoint = consumed + weight / 2;
+        let index = (midpoint * count / total.max(1)).min(count - 1);
+        groups[index].push(item.clone());
+        consumed += weight;
+    }
+
+    groups
+}
+
+fn split_evenly(items: &[String], count: usize) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = vec![Vec::new(); count];
+    for (i, item) in items.iter().enumerate() {
+        groups[i * count / items.len()].push(item.clone());
+    }
+    groups
+}
@@ -1,4 +1,5 @@
-use clap::{Arg, Command};
+use crate::date_filter::ReportTimeZone;
+use clap::{Arg, ArgGroup, Command};
 
 /// Build the command line interface
 pub fn build_cli() -> Command {
@@ -11,12 +12,8 @@ pub fn build_cli() -> Command {
                 .short('d')
                 .long("date")
                 .value_name("DATE")
-                .help("Filter logs by date (YYYY-MM-DD)")
-                .conflicts_with("from")
-                .conflicts_with("to")
-                .conflicts_with("weekly")
-                .conflicts_with("monthly")
-                .conflicts_with("all")
+                .help("Filter logs by date (YYYY-MM-DD, ISO week YYYY-Www, today, yesterday)")
+                .group("period")
                 .required(false),
         )
         .arg(
@@ -25,11 +22,15 @@ pub fn build_cli() -> Command {
                 .long("all")
                 .help("Generate report for all conversations (no date filter)")
                 .action(clap::ArgAction::SetTrue)
-                .conflicts_with("date")
-                .conflicts_with("from")
-                .conflicts_with("to")
-                .conflicts_with("weekly")
-                .conflicts_with("monthly")
+                .group("period")
+                .required(false),
+        )
+        .arg(
+            Arg::new("yesterday")
+                .long("yesterday")
+                .help("Generate report for yesterday")
+                .action(clap::ArgAction::SetTrue)
+                .group("period")
                 .required(false),
         )
         .arg(
@@ -38,11 +39,15 @@ pub fn build_cli() -> Command {
                 .long("weekly")
                 .help("Generate report for the current week")
                 .action(clap::ArgAction::SetTrue)
-                .conflicts_with("date")
-                .conflicts_with("from")
-                .conflicts_with("to")
-                .conflicts_with("monthly")
-                .conflicts_with("all")
+                .group("period")
+                .required(false),
+        )
+        .arg(
+            Arg::new("last-week")
+                .long("last-week")
+                .help("Generate report for the previous week (Monday to Sunday)")
+                .action(clap::ArgAction::SetTrue)
+                .group("period")
                 .required(false),
         )
         .arg(
@@ -51,11 +56,31 @@ pub fn build_cli() -> Command {
                 .long("monthly")
                 .help("Generate report for the current month")
                 .action(clap::ArgAction::SetTrue)
-                .conflicts_with("date")
-                .conflicts_with("from")
-                .conflicts_with("to")
-                .conflicts_with("weekly")
-                .conflicts_with("all")
+                .group("period")
+                .required(false),
+        )
+        .arg(
+            Arg::new("last-month")
+                .long("last-month")
+                .help("Generate report for the previous month")
+                .action(clap::ArgAction::SetTrue)
+                .group("period")
+                .required(false),
+        )
+        .arg(
+            Arg::new("quarter")
+                .long("quarter")
+                .help("Generate report for the current calendar quarter")
+                .action(clap::ArgAction::SetTrue)
+                .group("period")
+                .required(false),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .value_name("WHEN")
+                .help("Generate report from a point until now: 3d, 2w, 1m (days, weeks, months ago) or a date")
+                .group("period")
                 .required(false),
         )
         .arg(
@@ -63,8 +88,8 @@ pub fn build_cli() -> Command {
                 .short('f')
                 .long("from")
                 .value_name("DATE")
-                .help("Start date for range (YYYY-MM-DD)")
-                .requires("to")
+                .help("Start date for range (YYYY-MM-DD or YYYY-Www); without --to the range is open-ended")
+                .conflicts_with("period")
                 .required(false),
         )
         .arg(
@@ -72,8 +97,25 @@ pub fn build_cli() -> Command {
                 .short('t')
                 .long("to")
                 .value_name("DATE")
-                .help("End date for range (YYYY-MM-DD)")
-                .requires("from")
+                .help("End date for range (YYYY-MM-DD or YYYY-Www); without --from the range is open-ended")
+                .conflicts_with("period")
+                .required(false),
+        )
+        .group(ArgGroup::new("period").multiple(false))
+        .arg(
+            Arg::new("tz")
+                .long("tz")
+                .value_name("ZONE")
+                .help("Time zone that defines report days, as an IANA name such as Asia/Tokyo (default: system local time)")
+                .value_parser(|name: &str| name.parse::<ReportTimeZone>())
+                .required(false),
+        )
+        .arg(
+            Arg::new("group-by")
+                .long("group-by")
+                .value_name("BUCKET")
+                .help("Add a timeline by day or week, or group by project only (default: days for up to a week, weeks for longer periods)")
+                .value_parser(["day", "week", "project"])
                 .required(false),
         )
         .arg(
@@ -92,20 +134,96 @@ pub fn build_cli() -> Command {
                 .help("Claude projects directory (default: ~/.claude/projects)")
                 .required(false),
         )
+        .arg(
+            Arg::new("project")
+                .long("project")
+                .value_name("GLOB")
+                .help("Only report projects whose name or working directory matches (can be repeated)")
+                .action(clap::ArgAction::Append)
+                .required(false),
+        )
+        .arg(
+            Arg::new("exclude-project")
+                .long("exclude-project")
+                .value_name("GLOB")
+                .help("Leave out projects whose name or working directory matches (can be repeated)")
+                .action(clap::ArgAction::Append)
+                .required(false),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .help("Settings file (default: ~/.config/cc2report/config.toml if it exists)")
+                .required(false),
+        )
         .arg(
             Arg::new("format")
                 .long("format")
                 .value_name("FORMAT")
-                .help("Output format: markdown or json (default: markdown)")
-                .value_parser(["markdown", "json"])
+                .help("Output format: markdown, html, json, or a csv/ics timesheet (default: markdown)")
+                .value_parser(["markdown", "html", "json", "csv", "ics"])
                 .default_value("markdown")
                 .required(false),
         )
+        .arg(
+            Arg::new("offline")
+                .long("offline")
+                .help("Build the report with the rule-based summarizer only (no API calls, no key needed)")
+                .action(clap::ArgAction::SetTrue)
+                .required(false),
+        )
+        .arg(
+            Arg::new("per-session")
+                .long("per-session")
+                .help("Also summarize every Claude Code session with AI (one extra request per session)")
+                .action(clap::ArgAction::SetTrue)
+                .required(false),
+        )
+        .arg(
+            Arg::new("all-authors")
+                .long("all-authors")
+                .help("List git commits by every author, not only the repository's user.email")
+                .action(clap::ArgAction::SetTrue)
+                .required(false),
+        )
+        .arg(
+            Arg::new("idle-gap")
+                .long("idle-gap")
+                .value_name("MINUTES")
+                .help("Pause that ends a block of active working time (default: 15)")
+                .value_parser(clap::value_parser!(i64).range(1..))
+                .required(false),
+        )
+        .arg(
+            Arg::new("provider")
+                .long("provider")
+                .value_name("PROVIDER")
+                .help("LLM provider: openai, anthropic or ollama (default: openai)")
+                .value_parser(["openai", "anthropic", "ollama"])
+                .default_value("openai")
+                .required(false),
+        )
         .arg(
             Arg::new("api-key")
                 .long("api-key")
                 .value_name("KEY")
-                .help("OpenAI API key (or set OPENAI_API_KEY env var)")
+                .help("API key for the provider (or set OPENAI_API_KEY / ANTHROPIC_API_KEY env var)")
+                .required(false),
+        )
+        .arg(
+            Arg::new("base-url")
+                .long("base-url")
+                .value_name("URL")
+                .help("API base URL for OpenAI-compatible servers, Azure OpenAI or proxies (or set OPENAI_BASE_URL env var)")
+                .required(false),
+        )
+        .arg(
+            Arg::new("header")
+                .long("header")
+                .value_name("NAME=VALUE")
+                .help("Extra HTTP header sent with API requests (can be repeated)")
+                .action(clap::ArgAction::Append)
                 .required(false),
         )
         .arg(
@@ -123,9 +241,7 @@ pub fn build_cli() -> Command {
             Arg::new("model")
                 .long("model")
                 .value_name("MODEL")
-                .help("OpenAI model to use (default: gpt-4o for best quality)")
-                .value_parser(["gpt-4o", "gpt-4o-mini", "gpt-4-turbo", "gpt-3.5-turbo"])
-                .default_value("gpt-4o")
+                .help("Model to use (default: gpt-4o for openai, claude-sonnet-4-5 for anthropic, llama3.1 for ollama)")
                 .required(false),
         )
         .arg(
@@ -136,6 +252,13 @@ pub fn build_cli() -> Command {
                 .action(clap::ArgAction::SetTrue)
                 .required(false),
         )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .help("Render the Markdown report with a MiniJinja template over the report's JSON fields")
+                .value_name("FILE")
+                .required(false),
+        )
         .arg(
             Arg::new("generate-template")
                 .long("generate-template")
@@ -157,6 +280,20 @@ pub fn build_cli() -> Command {
                 .action(clap::ArgAction::SetTrue)
                 .required(false),
         )
+        .arg(
+            Arg::new("reindex")
+                .long("reindex")
+                .help("Discard the log index and parse all log files again")
+                .action(clap::ArgAction::SetTrue)
+                .required(false),
+        )
+        .arg(
+            Arg::new("index-dir")
+                .long("index-dir")
+                .value_name("DIR")
+                .help("Directory of the log index (default: ~/.cache/cc2report/index)")
+                .required(false),
+        )
         .arg(
             Arg::new("show-token-usage")
                 .long("show-token-usage")
@@ -164,6 +301,22 @@ pub fn build_cli() -> Command {
                 .action(clap::ArgAction::SetTrue)
                 .required(false),
         )
+        .arg(
+            Arg::new("context-limit")
+                .long("context-limit")
+                .value_name("TOKENS")
+                .help("Context window of the model; longer projects are analyzed in chunks (default: per model)")
+                .value_parser(clap::value_parser!(usize))
+                .required(false),
+        )
+        .arg(
+            Arg::new("max-retries")
+                .long("max-retries")
+                .value_name("N")
+                .help("Retries for rate-limited or failed API requests (default: 3)")
+                .value_parser(clap::value_parser!(u32))
+                .required(false),
+        )
         .arg(
             Arg::new("parallel")
                 .short('p')
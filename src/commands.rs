@@ -0,0 +1,353 @@
+//! Build, test, lint, format and deploy commands run through the Bash tool
+//!
+//! Commands are classified by their program and subcommand (cargo, npm, pnpm,
+//! yarn, pytest, go, make, docker). A run failed when its `tool_result` is
+//! flagged `is_error` (non-zero exit); test runs additionally report the
+//! pass/fail counts printed by the runner, read from the entry's
+//! `toolUseResult`.
+
+use crate::parser::LogEntry;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandKind {
+    Format,
+    Lint,
+    Build,
+    Test,
+    Deploy,
+}
+
+impl CommandKind {
+    pub const ALL: [CommandKind; 5] = [
+        CommandKind::Build,
+        CommandKind::Test,
+        CommandKind::Lint,
+        CommandKind::Format,
+        CommandKind::Deploy,
+    ];
+
+    /// Name of the kind, as serialized and in the message catalog
+    pub fn key(self) -> &'static str {
+        match self {
+            CommandKind::Format => "format",
+            CommandKind::Lint => "lint",
+            CommandKind::Build => "build",
+            CommandKind::Test => "test",
+            CommandKind::Deploy => "deploy",
+        }
+    }
+}
+
+/// Kind of a shell command line; for chains such as `cargo fmt && cargo test`
+/// the most significant step (deploy, then test, build, lint, format) wins
+pub fn classify(command: &str) -> Option<CommandKind> {
+    command
+        .split(['\n', ';', '|', '&'])
+        .filter_map(classify_simple)
+        .max()
+}
+
+fn classify_simple(command: &str) -> Option<CommandKind> {
+    let mut words = command
+        .split_whitespace()
+        .skip_while(|word| word.contains('=') && !word.starts_with('-'))
+        .skip_while(|word| matches!(*word, "sudo" | "time" | "npx" | "exec"))
+        .map(|word| word.rsplit('/').next().unwrap_or(word));
+    let program = words.next()?;
+    let args: Vec<&str> = words.filter(|word| !word.starts_with('-')).collect();
+    let arg = |index: usize| args.get(index).copied().unwrap_or_default();
+
+    use CommandKind::*;
+    match program {
+        "cargo" => match arg(0) {
+            "build" | "b" | "check" | "c" => Some(Build),
+            "test" | "t" | "nextest" | "bench" => Some(Test),
+            "clippy" => Some(Lint),
+            "fmt" => Some(Format),
+            "publish" => Some(Deploy),
+            _ => None,
+        },
+        "npm" | "pnpm" | "yarn" | "bun" => {
+            let script = if arg(0) == "run" { arg(1) } else { arg(0) };
+            script_kind(script)
+        }
+        "pytest" | "py.test" | "tox" | "nox" => Some(Test),
+        "python" | "python3" if arg(0) == "pytest" || arg(0) == "unittest" => Some(Test),
+        "python" | "python3" if arg(0) == "build" => Some(Build),
+        "go" => match arg(0) {
+            "build" | "install" => Some(Build),
+            "test" => Some(Test),
+            "vet" => Some(Lint),
+            "fmt" => Some(Format),
+            _ => None,
+        },
+        // A bare `make` builds the default target
+        "make" | "just" if args.is_empty() => Some(Build),
+        "make" | "just" => script_kind(arg(0)),
+        "docker" | "podman" => {
+            let sub = if arg(0) == "compose" || arg(0) == "buildx" {
+                arg(1)
+            } else {
+                arg(0)
+            };
+            match sub {
+                "build" | "bake" => Some(Build),
+                "push" | "up" | "deploy" => Some(Deploy),
+                _ => None,
+            }
+        }
+        "docker-compose" => match arg(0) {
+            "build" => Some(Build),
+            "up" | "push" => Some(Deploy),
+            _ => None,
+        },
+        "jest" | "vitest" | "mocha" | "playwright" => Some(Test),
+        "tsc" | "webpack" => Some(Build),
+        "vite" | "next" if arg(0) == "build" => Some(Build),
+        "eslint" | "ruff" | "flake8" | "pylint" | "mypy" | "golangci-lint" | "shellcheck" => {
+            Some(Lint)
+        }
+        "prettier" | "black" | "isort" | "gofmt" | "rustfmt" => Some(Format),
+        "twine" if arg(0) == "upload" => Some(Deploy),
+        _ => None,
+    }
+}
+
+/// Kind of a package.json script or make target by its conventional name
+fn script_kind(name: &str) -> Option<CommandKind> {
+    let base = name.split([':', '-']).next().unwrap_or(name);
+    match base {
+        "build" | "compile" | "bundle" | "all" => Some(CommandKind::Build),
+        "test" | "t" | "check" | "e2e" | "coverage" => Some(CommandKind::Test),
+        "lint" | "typecheck" | "vet" => Some(CommandKind::Lint),
+        "fmt" | "format" | "prettier" => Some(CommandKind::Format),
+        "deploy" | "publish" | "release" => Some(CommandKind::Deploy),
+        _ => None,
+    }
+}
+
+/// Tests counted in a runner's summary lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TestCounts {
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+/// Pass/fail counts from the output of cargo test, pytest, jest, vitest,
+/// mocha or go test; None when no summary was printed
+///
+/// `go test` without `-v` prints one line per package, so packages are
+/// counted instead of tests.
+pub fn parse_test_counts(output: &str) -> Option<TestCounts> {
+    let mut counts = TestCounts::default();
+    let mut go_packages = TestCounts::default();
+    let mut found = false;
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(status) = line.strip_prefix("--- ") {
+            let target = if status.starts_with("PASS:") {
+                &mut counts.passed
+            } else if status.starts_with("FAIL:") {
+                &mut counts.failed
+            } else if status.starts_with("SKIP:") {
+                &mut counts.skipped
+            } else {
+                continue;
+            };
+            *target += 1;
+            found = true;
+        } else if line.starts_with("ok  \t") || line.starts_with("ok \t") {
+            go_packages.passed += 1;
+        } else if line.starts_with("FAIL\t") {
+            go_packages.failed += 1;
+        } else if is_summary_line(line) {
+            found |= add_counts(line, &mut counts);
+        }
+    }
+
+    if !found && go_packages != TestCounts::default() {
+        return Some(go_packages);
+    }
+    found.then_some(counts)
+}
+
+/// `test result: ...` (cargo), `=== 3 passed in 0.1s ===` (pytest),
+/// `Tests: ...` (jest, vitest) and `3 passing` (mocha)
+fn is_summary_line(line: &str) -> bool {
+    line.starts_with("test result:")
+        || (line.starts_with('=') && line.ends_with('='))
+        || line.starts_with("Tests:")
+        || line.starts_with("Tests ")
+        || line.split_once(' ').is_some_and(|(number, word)| {
+            number.parse::<usize>().is_ok()
+                && ["passing", "failing", "pending"]
+                    .iter()
+                    .any(|w| word.starts_with(w))
+        })
+}
+
+/// Add every `<number> <passed|failed|...>` pair of a summary line
+fn add_counts(line: &str, counts: &mut TestCounts) -> bool {
+    let words: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '|' | '(' | ')' | '='))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut found = false;
+    for pair in words.windows(2) {
+        let Ok(number) = pair[0].parse::<usize>() else {
+            continue;
+        };
+        let target = match pair[1].trim_end_matches('.') {
+            "passed" | "passing" => &mut counts.passed,
+            "failed" | "failing" | "error" | "errors" => &mut counts.failed,
+            "ignored" | "skipped" | "pending" | "todo" => &mut counts.skipped,
+            _ => continue,
+        };
+        *target += number;
+        found = true;
+    }
+    found
+}
+
+/// Runs of one kind of command and how many of them failed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RunCount {
+    pub runs: usize,
+    pub failed: usize,
+}
+
+/// Test runs of a project over the period
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TestHealth {
+    pub runs: usize,
+    pub failed_runs: usize,
+    /// Whether the most recent test run succeeded
+    pub passing: bool,
+    /// Counts of the most recent run that printed a summary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_counts: Option<TestCounts>,
+}
+
+/// Classified commands of a project and its test health
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CommandStats {
+    pub runs: BTreeMap<CommandKind, RunCount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tests: Option<TestHealth>,
+}
+
+impl CommandStats {
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+}
+
+/// Pairs each classified Bash call with its result as the entries are walked
+#[derive(Debug, Default)]
+pub struct CommandTracker {
+    pending: HashMap<String, CommandKind>,
+    stats: CommandStats,
+}
+
+impl CommandTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember Bash calls of an assistant entry, or record the outcome of
+    /// those answered by a user entry's `tool_result` blocks
+    pub fn record_entry(&mut self, entry: &LogEntry) {
+        let Some(blocks) = entry
+            .message
+            .as_ref()
+            .and_then(|message| message.content.as_ref())
+            .and_then(|content| content.as_array())
+        else {
+            return;
+        };
+
+        for block in blocks {
+            let id = block
+                .get("id")
+                .or_else(|| block.get("tool_use_id"))
+                .and_then(|v| v.as_str());
+            let Some(id) = id else {
+                continue;
+            };
+            match block.get("type").and_then(|v| v.as_str()) {
+                Some("tool_use") if block.get("name").and_then(|v| v.as_str()) == Some("Bash") => {
+                    let command = block
+                        .get("input")
+                        .and_then(|input| input.get("command"))
+                        .and_then(|v| v.as_str());
+                    if let Some(kind) = command.and_then(classify) {
+                        self.pending.insert(id.to_string(), kind);
+                    }
+                }
+                Some("tool_result") => {
+                    if let Some(kind) = self.pending.remove(id) {
+                        let failed = block.get("is_error").and_then(|v| v.as_bool()) == Some(true);
+                        let counts = entry.tool_use_result.as_ref().and_then(result_counts);
+                        self.record_run(kind, failed, counts);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn record_run(&mut self, kind: CommandKind, failed: bool, counts: Option<TestCounts>) {
+        // A runner may exit successfully and still report failures
+        let failed = failed || counts.is_some_and(|c| c.failed > 0);
+        let run = self.stats.runs.entry(kind).or_default();
+        run.runs += 1;
+        run.failed += usize::from(failed);
+
+        if kind == CommandKind::Test {
+            let tests = self.stats.tests.get_or_insert_with(TestHealth::default);
+            tests.runs += 1;
+            tests.failed_runs += usize::from(failed);
+            tests.passing = !failed;
+            if counts.is_some() {
+                tests.last_counts = counts;
+            }
+        }
+    }
+
+    pub fn into_stats(self) -> CommandStats {
+        self.stats
+    }
+}
+
+/// Test counts from a Bash `toolUseResult` (`stdout` and `stderr`)
+fn result_counts(result: &serde_json::Value) -> Option<TestCounts> {
+    let output: Vec<&str> = ["stdout", "stderr"]
+        .iter()
+        .filter_map(|key| result.get(key).and_then(|v| v.as_str()))
+        .collect();
+    parse_test_counts(&output.join("\n"))
+}
+
+/// The part of a Bash `toolUseResult` the test counts are read from: only
+/// the runners' summary lines are kept, so the log index stays small
+pub fn compact_result(result: &serde_json::Value) -> Option<serde_json::Value> {
+    let mut compact = serde_json::Map::new();
+    for key in ["stdout", "stderr"] {
+        let Some(output) = result.get(key).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let lines: Vec<&str> = output
+            .lines()
+            .filter(|line| parse_test_counts(line).is_some())
+            .collect();
+        if !lines.is_empty() {
+            compact.insert(key.to_string(), lines.join("\n").into());
+        }
+    }
+    (!compact.is_empty()).then_some(serde_json::Value::Object(compact))
+}
@@ -1,5 +1,9 @@
+use crate::log_index::LogIndex;
+use crate::timeline::GroupBy;
+use crate::work_time::DEFAULT_IDLE_GAP_MINUTES;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,13 +16,80 @@ pub struct Config {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiConfig {
+    pub provider: ProviderKind,
     pub openai_api_key: Option<String>,
+    pub anthropic_api_key: Option<String>,
+    /// Override for the provider's API base URL (Azure OpenAI, vLLM, LM Studio, mock servers)
+    pub base_url: Option<String>,
+    /// Additional HTTP headers sent with every request (e.g. `OpenAI-Organization`, Azure `api-key`)
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
     pub model: String,
+    /// Context window of the model in tokens; looked up from the model name when unset
+    #[serde(default)]
+    pub context_limit: Option<usize>,
     pub temperature: f32,
     pub max_retries: u32,
     pub retry_delay_ms: u64,
 }
 
+/// LLM backend used for the AI analysis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    OpenAi,
+    Anthropic,
+    Ollama,
+}
+
+impl ProviderKind {
+    /// Parse the value given to `--provider`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "openai" => Some(Self::OpenAi),
+            "anthropic" => Some(Self::Anthropic),
+            "ollama" => Some(Self::Ollama),
+            _ => None,
+        }
+    }
+
+    /// Human readable provider name used in messages
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::OpenAi => "OpenAI",
+            Self::Anthropic => "Anthropic",
+            Self::Ollama => "Ollama",
+        }
+    }
+
+    /// Model used when `--model` is not given
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            Self::OpenAi => "gpt-4o",
+            Self::Anthropic => "claude-sonnet-4-5",
+            Self::Ollama => "llama3.1",
+        }
+    }
+
+    /// Base URL of the provider's hosted API
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            Self::OpenAi => "https://api.openai.com/v1",
+            Self::Anthropic => "https://api.anthropic.com",
+            Self::Ollama => "http://localhost:11434/v1",
+        }
+    }
+
+    /// Environment variable holding the API key, if the provider needs one
+    pub fn api_key_env(&self) -> Option<&'static str> {
+        match self {
+            Self::OpenAi => Some("OPENAI_API_KEY"),
+            Self::Anthropic => Some("ANTHROPIC_API_KEY"),
+            Self::Ollama => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheConfig {
     pub enabled: bool,
@@ -32,6 +103,10 @@ pub struct OutputConfig {
     pub language: String,
     pub show_token_usage: bool,
     pub quiet: bool,
+    /// Timeline buckets; `None` picks them from the length of the period
+    pub group_by: Option<GroupBy>,
+    /// MiniJinja layout that replaces the built-in Markdown report
+    pub template: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,20 +114,84 @@ pub struct OutputConfig {
 pub enum OutputFormat {
     Markdown,
     Json,
+    /// Timesheet, one row per session
+    Csv,
+    /// iCalendar timesheet, one event per session
+    Ics,
+    /// Standalone HTML page with charts
+    Html,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessingConfig {
+    /// Skip the AI API and build the report from the rule-based summarizer only
+    pub offline: bool,
+    /// Also analyze every session on its own (one extra AI request per session)
+    pub per_session: bool,
     pub parallel_requests: usize,
+    /// Pause in minutes that ends a block of active working time
+    pub idle_gap_minutes: i64,
+    /// List commits by every author, not only the repository's `user.email`
+    pub all_authors: bool,
     pub log_dir: PathBuf,
+    /// Directory of the persistent log index
+    pub index_dir: PathBuf,
+    /// Globs selecting the projects to report; empty means all
+    pub include_projects: Vec<String>,
+    /// Globs of projects to leave out, from `--exclude-project` and the settings file
+    pub exclude_projects: Vec<String>,
+}
+
+/// Settings file (`~/.config/cc2report/config.toml`)
+///
+/// ```toml
+/// [projects]
+/// ignore = ["~/scratch/*", "playground-*"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsFile {
+    pub projects: ProjectSettings,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectSettings {
+    /// Projects never included in a report
+    pub ignore: Vec<String>,
+}
+
+impl SettingsFile {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read settings file {}: {e}", path.display()))?;
+        toml::from_str(&content)
+            .map_err(|e| format!("Invalid settings file {}: {e}", path.display()).into())
+    }
+
+    /// Settings from `--config`, else the default file if it exists
+    pub fn from_args(matches: &clap::ArgMatches) -> Result<Self, Box<dyn std::error::Error>> {
+        match matches.get_one::<String>("config") {
+            Some(path) => Self::load(Path::new(path)),
+            None => match default_settings_path() {
+                Some(path) if path.exists() => Self::load(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             api: ApiConfig {
+                provider: ProviderKind::OpenAi,
                 openai_api_key: None,
-                model: "gpt-4o".to_string(),
+                anthropic_api_key: None,
+                base_url: None,
+                extra_headers: HashMap::new(),
+                model: ProviderKind::OpenAi.default_model().to_string(),
+                context_limit: None,
                 temperature: 0.3,
                 max_retries: 3,
                 retry_delay_ms: 1000,
@@ -67,10 +206,19 @@ impl Default for Config {
                 language: detect_system_language(),
                 show_token_usage: false,
                 quiet: false,
+                group_by: None,
+                template: None,
             },
             processing: ProcessingConfig {
+                offline: false,
+                per_session: false,
                 parallel_requests: 1,
+                idle_gap_minutes: DEFAULT_IDLE_GAP_MINUTES,
+                all_authors: false,
                 log_dir: default_log_dir(),
+                index_dir: LogIndex::default_dir(),
+                include_projects: Vec::new(),
+                exclude_projects: Vec::new(),
             },
         }
     }
@@ -82,14 +230,54 @@ impl Config {
         let mut config = Self::default();
 
         // API configuration
+        if let Some(provider) = matches.get_one::<String>("provider") {
+            config.api.provider = ProviderKind::parse(provider)
+                .ok_or_else(|| format!("Unknown provider: {provider}"))?;
+        }
+
+        config.api.openai_api_key = std::env::var("OPENAI_API_KEY").ok();
+        config.api.anthropic_api_key = std::env::var("ANTHROPIC_API_KEY").ok();
+
         if let Some(key) = matches.get_one::<String>("api-key") {
-            config.api.openai_api_key = Some(key.clone());
-        } else if let Ok(key) = std::env::var("OPENAI_API_KEY") {
-            config.api.openai_api_key = Some(key);
+            match config.api.provider {
+                ProviderKind::Anthropic => config.api.anthropic_api_key = Some(key.clone()),
+                _ => config.api.openai_api_key = Some(key.clone()),
+            }
+        }
+
+        if let Some(base_url) = matches.get_one::<String>("base-url") {
+            config.api.base_url = Some(base_url.clone());
+        } else if config.api.provider == ProviderKind::OpenAi {
+            config.api.base_url = std::env::var("OPENAI_BASE_URL").ok();
+        }
+
+        if let Some(headers) = matches.get_many::<String>("header") {
+            for header in headers {
+                // The first separator ends the name; base64 values may contain `=` and `:`
+                let (name, value) = header
+                    .find(['=', ':'])
+                    .map(|at| (&header[..at], &header[at + 1..]))
+                    .ok_or_else(|| {
+                        format!("Invalid header (expected NAME=VALUE or NAME: VALUE): {header}")
+                    })?;
+                config
+                    .api
+                    .extra_headers
+                    .insert(name.trim().to_string(), value.trim().to_string());
+            }
         }
 
-        if let Some(model) = matches.get_one::<String>("model") {
-            config.api.model = model.clone();
+        config.api.model = match matches.get_one::<String>("model") {
+            Some(model) => model.clone(),
+            None => config.api.provider.default_model().to_string(),
+        };
+
+        if let Some(limit) = matches.get_one::<usize>("context-limit") {
+            config.api.context_limit = Some(*limit);
+        }
+
+        if let Some(max_retries) = matches.get_one::<u32>("max-retries") {
+            config.api.max_retries = *max_retries;
         }
 
         // Cache configuration is always enabled by default
@@ -99,6 +287,9 @@ impl Config {
         if let Some(format) = matches.get_one::<String>("format") {
             config.output.format = match format.as_str() {
                 "json" => OutputFormat::Json,
+                "csv" => OutputFormat::Csv,
+                "ics" => OutputFormat::Ics,
+                "html" => OutputFormat::Html,
                 _ => OutputFormat::Markdown,
             };
         }
@@ -110,22 +301,61 @@ impl Config {
         config.output.show_token_usage = matches.get_flag("show-token-usage");
         config.output.quiet = matches.get_flag("quiet");
 
+        if let Some(group_by) = matches.get_one::<String>("group-by") {
+            config.output.group_by = Some(group_by.parse()?);
+        }
+
+        if let Some(template) = matches.get_one::<String>("template") {
+            config.output.template = Some(PathBuf::from(template));
+        }
+
         // Processing configuration
+        config.processing.offline = matches.get_flag("offline");
+        config.processing.per_session = matches.get_flag("per-session");
+        config.processing.all_authors = matches.get_flag("all-authors");
+
         if let Some(parallel) = matches.get_one::<usize>("parallel") {
             config.processing.parallel_requests = (*parallel).min(10);
         }
 
+        if let Some(idle_gap) = matches.get_one::<i64>("idle-gap") {
+            config.processing.idle_gap_minutes = *idle_gap;
+        }
+
         if let Some(log_dir) = matches.get_one::<String>("log-dir") {
             config.processing.log_dir = PathBuf::from(log_dir);
         }
 
+        if let Some(index_dir) = matches.get_one::<String>("index-dir") {
+            config.processing.index_dir = PathBuf::from(index_dir);
+        }
+
+        let settings = SettingsFile::from_args(matches)?;
+        config.processing.exclude_projects = settings.projects.ignore;
+        if let Some(patterns) = matches.get_many::<String>("project") {
+            config.processing.include_projects = patterns.cloned().collect();
+        }
+        if let Some(patterns) = matches.get_many::<String>("exclude-project") {
+            config.processing.exclude_projects.extend(patterns.cloned());
+        }
+
         Ok(config)
     }
 
     /// Validate configuration
     pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
-        if self.api.openai_api_key.is_none() {
-            return Err("OpenAI API key is required. Set OPENAI_API_KEY environment variable or use --api-key option.".into());
+        // Custom endpoints (local servers, Azure with an `api-key` header) may not need a key
+        if let Some(env_var) = self.api.provider.api_key_env() {
+            if !self.processing.offline
+                && self.api.api_key().is_none()
+                && self.api.base_url.is_none()
+            {
+                return Err(format!(
+                    "{} API key is required. Set {env_var} environment variable or use --api-key option.",
+                    self.api.provider.display_name()
+                )
+                .into());
+            }
         }
 
         if !self.processing.log_dir.exists() {
@@ -140,6 +370,24 @@ impl Config {
     }
 }
 
+impl ApiConfig {
+    /// API key for the selected provider
+    pub fn api_key(&self) -> Option<&str> {
+        match self.provider {
+            ProviderKind::OpenAi => self.openai_api_key.as_deref(),
+            ProviderKind::Anthropic => self.anthropic_api_key.as_deref(),
+            ProviderKind::Ollama => None,
+        }
+    }
+
+    /// Base URL requests are sent to
+    pub fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .unwrap_or_else(|| self.provider.default_base_url())
+    }
+}
+
 fn default_cache_dir() -> PathBuf {
     if let Ok(home) = std::env::var("HOME") {
         PathBuf::from(home).join(".cache").join("cc2report")
@@ -148,6 +396,15 @@ fn default_cache_dir() -> PathBuf {
     }
 }
 
+fn default_settings_path() -> Option<PathBuf> {
+    std::env::var("HOME").ok().map(|home| {
+        PathBuf::from(home)
+            .join(".config")
+            .join("cc2report")
+            .join("config.toml")
+    })
+}
+
 fn default_log_dir() -> PathBuf {
     if let Ok(home) = std::env::var("HOME") {
         PathBuf::from(home).join(".claude").join("projects")
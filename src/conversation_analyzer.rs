@@ -1,11 +1,20 @@
-use crate::parser::Message;
+use crate::commands;
+use crate::i18n::Catalog;
+use crate::parser::{LogEntry, Message};
 use serde::Serialize;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct ConversationFlow {
     pub current_topic: Option<Topic>,
     pub topics: Vec<Topic>,
     pub context_stack: Vec<String>,
+    /// Session the following messages belong to
+    pub current_session: Option<String>,
+    /// Index into `topics` of the topic each message uuid belongs to
+    pub message_topics: HashMap<String, usize>,
+    /// Language of the step descriptions and outcomes
+    pub catalog: Catalog,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -17,15 +26,34 @@ pub struct Topic {
     pub started_at: String,
     pub completed_at: Option<String>,
     pub timestamp: String,
+    /// Claude Code session the topic was started in
+    pub session_id: Option<String>,
+    /// Summaries Claude Code wrote for conversations ending in this topic
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub summaries: Vec<String>,
+    /// Commits made while the topic was worked on (`abc1234 Subject`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub commits: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct WorkStep {
     pub description: String,
     pub details: Vec<String>,
+    /// Tool calls behind `details`, independent of the report language
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolCall>,
     pub result: StepResult,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCall {
+    pub name: String,
+    /// File the tool read or wrote
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub enum StepResult {
     Success(String),
@@ -49,20 +77,70 @@ impl Default for ConversationFlow {
 
 impl ConversationFlow {
     pub fn new() -> Self {
+        Self::with_language("en")
+    }
+
+    /// Flow whose step descriptions and outcomes are written in `lang`
+    pub fn with_language(lang: &str) -> Self {
         Self {
             current_topic: None,
             topics: Vec::new(),
             context_stack: Vec::new(),
+            current_session: None,
+            message_topics: HashMap::new(),
+            catalog: Catalog::new(lang),
+        }
+    }
+
+    /// Associate a message uuid with the current topic
+    pub fn track_message(&mut self, uuid: &str) {
+        if self.current_topic.is_some() {
+            self.message_topics
+                .insert(uuid.to_string(), self.topics.len());
         }
     }
 
+    /// Attach a `summary` record to the topic holding its leaf message.
+    ///
+    /// Call after [`finalize`](Self::finalize). Returns false when the leaf
+    /// message was not seen (e.g. it lies outside the date range).
+    pub fn attach_summary(&mut self, leaf_uuid: &str, summary: &str) -> bool {
+        let Some(topic) = self
+            .message_topics
+            .get(leaf_uuid)
+            .and_then(|&index| self.topics.get_mut(index))
+        else {
+            return false;
+        };
+
+        if !topic.summaries.iter().any(|existing| existing == summary) {
+            topic.summaries.push(summary.to_string());
+        }
+        true
+    }
+
+    /// Switch to another session; a topic never continues across sessions
+    pub fn set_session(&mut self, session_id: Option<&str>) {
+        if self.current_session.as_deref() == session_id {
+            return;
+        }
+
+        self.finalize();
+        self.current_session = session_id.map(str::to_string);
+    }
+
     pub fn analyze_user_message(&mut self, content: &str, timestamp: &str) {
-        // ユーザーの意図を理解する
-        if let Some(intent) = extract_user_intent(content) {
+        // ユーザーの意図を理解する（トピックがまだ無ければ最初の発言から開始）
+        let intent = extract_user_intent(content).or_else(|| {
+            (self.current_topic.is_none() && !content.trim().is_empty())
+                .then(|| summarize_intent(content))
+        });
+
+        if let Some(intent) = intent {
             // 現在のトピックを完了させる
             if let Some(mut topic) = self.current_topic.take() {
                 topic.completed_at = Some(timestamp.to_string());
-                topic.outcome = determine_topic_outcome(&topic);
+                topic.outcome = determine_topic_outcome(&topic, &self.catalog);
                 self.topics.push(topic);
             }
 
@@ -75,14 +153,43 @@ impl ConversationFlow {
                 started_at: timestamp.to_string(),
                 completed_at: None,
                 timestamp: timestamp.to_string(),
+                session_id: self.current_session.clone(),
+                summaries: Vec::new(),
+                commits: Vec::new(),
             });
         }
     }
 
+    /// Feed one log entry of the active conversation.
+    ///
+    /// `sidechain` marks subagent entries: their prompts come from the main
+    /// agent rather than the user, so only their work is counted.
+    pub fn process_entry(&mut self, entry: &LogEntry, sidechain: bool) {
+        match entry.entry_type.as_str() {
+            "user" if !sidechain => {
+                if let (Some(message), Some(timestamp)) = (&entry.message, &entry.timestamp) {
+                    if let Some(content) = extract_message_content(message) {
+                        self.analyze_user_message(&content, timestamp);
+                    }
+                }
+            }
+            "assistant" => {
+                if let Some(message) = &entry.message {
+                    self.analyze_assistant_response(message);
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(uuid) = &entry.uuid {
+            self.track_message(uuid);
+        }
+    }
+
     pub fn analyze_assistant_response(&mut self, message: &Message) {
         if let Some(topic) = &mut self.current_topic {
             // アシスタントの応答から実際の作業内容を抽出
-            if let Some(work_steps) = extract_work_steps(message) {
+            if let Some(work_steps) = extract_work_steps(message, &self.catalog) {
                 topic.steps.extend(work_steps);
             }
         }
@@ -90,12 +197,32 @@ impl ConversationFlow {
 
     pub fn finalize(&mut self) {
         if let Some(mut topic) = self.current_topic.take() {
-            topic.outcome = determine_topic_outcome(&topic);
+            topic.outcome = determine_topic_outcome(&topic, &self.catalog);
             self.topics.push(topic);
         }
     }
 }
 
+/// Text the user typed, from a plain string or the first text block of the content
+fn extract_message_content(message: &Message) -> Option<String> {
+    if let Some(content) = &message.content {
+        if let Some(text) = content.as_str() {
+            return Some(text.to_string());
+        } else if let Some(array) = content.as_array() {
+            for item in array {
+                if let Some(obj) = item.as_object() {
+                    if obj.get("type").and_then(|v| v.as_str()) == Some("text") {
+                        if let Some(text) = obj.get("content").and_then(|v| v.as_str()) {
+                            return Some(text.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
 fn extract_user_intent(content: &str) -> Option<String> {
     // 日本語と英語の両方に対応
     let _content_lower = content.to_lowercase();
@@ -222,7 +349,7 @@ fn generate_topic_title(intent: &str) -> String {
     }
 }
 
-fn extract_work_steps(message: &Message) -> Option<Vec<WorkStep>> {
+fn extract_work_steps(message: &Message, catalog: &Catalog) -> Option<Vec<WorkStep>> {
     let mut steps = Vec::new();
 
     if let Some(content) = &message.content {
@@ -242,6 +369,7 @@ fn extract_work_steps(message: &Message) -> Option<Vec<WorkStep>> {
                                     current_step = Some(WorkStep {
                                         description: step_desc,
                                         details: Vec::new(),
+                                        tools: Vec::new(),
                                         result: StepResult::InProgress,
                                     });
                                 }
@@ -252,17 +380,23 @@ fn extract_work_steps(message: &Message) -> Option<Vec<WorkStep>> {
                                 if let Some(input) = obj.get("input") {
                                     // ツール使用を人間が理解できる形に変換
                                     let human_readable =
-                                        convert_tool_use_to_human_readable(name, input);
-
-                                    if let Some(step) = &mut current_step {
-                                        step.details.push(human_readable);
-                                    } else {
-                                        current_step = Some(WorkStep {
-                                            description: "作業を実行中".to_string(),
-                                            details: vec![human_readable],
-                                            result: StepResult::InProgress,
-                                        });
-                                    }
+                                        convert_tool_use_to_human_readable(name, input, catalog);
+                                    let tool = ToolCall {
+                                        name: name.to_string(),
+                                        file: input
+                                            .get("file_path")
+                                            .and_then(|v| v.as_str())
+                                            .map(str::to_string),
+                                    };
+
+                                    let step = current_step.get_or_insert_with(|| WorkStep {
+                                        description: catalog.get("steps.working").to_string(),
+                                        details: Vec::new(),
+                                        tools: Vec::new(),
+                                        result: StepResult::InProgress,
+                                    });
+                                    step.details.push(human_readable);
+                                    step.tools.push(tool);
                                 }
                             }
                         }
@@ -270,7 +404,7 @@ fn extract_work_steps(message: &Message) -> Option<Vec<WorkStep>> {
                             if let Some(step) = &mut current_step {
                                 // 結果を解析
                                 if let Some(output) = obj.get("output") {
-                                    step.result = analyze_tool_result(output);
+                                    step.result = analyze_tool_result(output, catalog);
                                 }
                             }
                         }
@@ -431,67 +565,43 @@ fn extract_meaningful_action(text: &str) -> Option<String> {
     None
 }
 
-fn convert_tool_use_to_human_readable(tool_name: &str, input: &serde_json::Value) -> String {
-    match tool_name {
-        "Read" => {
-            if let Some(path) = input.get("file_path").and_then(|v| v.as_str()) {
-                let filename = path.split('/').next_back().unwrap_or(path);
-                format!("{filename} を読み込み")
-            } else {
-                "ファイルを読み込み".to_string()
-            }
-        }
-        "Write" => {
-            if let Some(path) = input.get("file_path").and_then(|v| v.as_str()) {
-                let filename = path.split('/').next_back().unwrap_or(path);
-                format!("{filename} を新規作成")
-            } else {
-                "新規ファイルを作成".to_string()
-            }
-        }
-        "Edit" | "MultiEdit" => {
-            if let Some(path) = input.get("file_path").and_then(|v| v.as_str()) {
-                let filename = path.split('/').next_back().unwrap_or(path);
-                format!("{filename} を編集")
-            } else {
-                "ファイルを編集".to_string()
-            }
+fn convert_tool_use_to_human_readable(
+    tool_name: &str,
+    input: &serde_json::Value,
+    catalog: &Catalog,
+) -> String {
+    let file_step = |key: &str, any: &str| match input.get("file_path").and_then(|v| v.as_str()) {
+        Some(path) => {
+            let filename = path.split('/').next_back().unwrap_or(path);
+            catalog.format(key, &[("file", &filename)])
         }
+        None => catalog.get(any).to_string(),
+    };
+
+    match tool_name {
+        "Read" => file_step("steps.read", "steps.read_any"),
+        "Write" => file_step("steps.write", "steps.write_any"),
+        "Edit" | "MultiEdit" => file_step("steps.edit", "steps.edit_any"),
         "Bash" => {
-            if let Some(cmd) = input.get("command").and_then(|v| v.as_str()) {
-                let cmd_parts: Vec<&str> = cmd.split_whitespace().collect();
-                match cmd_parts.first() {
-                    Some(&"cargo") if cmd_parts.get(1) == Some(&"build") => {
-                        "Rustプロジェクトをビルド".to_string()
-                    }
-                    Some(&"cargo") if cmd_parts.get(1) == Some(&"test") => {
-                        "Rustのテストを実行".to_string()
-                    }
-                    Some(&"cargo") if cmd_parts.get(1) == Some(&"run") => {
-                        "プログラムを実行".to_string()
-                    }
-                    Some(&"git") if cmd_parts.get(1) == Some(&"commit") => {
-                        "変更をGitにコミット".to_string()
-                    }
-                    Some(&"git") if cmd_parts.get(1) == Some(&"status") => {
-                        "Gitステータスを確認".to_string()
-                    }
-                    Some(&"npm") if cmd_parts.get(1) == Some(&"install") => {
-                        "npm依存関係をインストール".to_string()
-                    }
-                    Some(&"echo") => "メッセージを出力".to_string(),
-                    Some(&"mkdir") => "ディレクトリを作成".to_string(),
-                    _ => {
-                        if cmd.len() > 30 {
-                            format!("{} コマンドを実行", cmd_parts.first().unwrap_or(&""))
-                        } else {
-                            format!("「{cmd}」を実行")
-                        }
-                    }
+            let Some(cmd) = input.get("command").and_then(|v| v.as_str()) else {
+                return catalog.get("steps.command_any").to_string();
+            };
+            let cmd_parts: Vec<&str> = cmd.split_whitespace().collect();
+            let key = match (cmd_parts.first(), commands::classify(cmd)) {
+                (_, Some(kind)) => kind.key(),
+                (Some(&"cargo"), _) if cmd_parts.get(1) == Some(&"run") => "run",
+                (Some(&"git"), _) if cmd_parts.get(1) == Some(&"commit") => "git_commit",
+                (Some(&"git"), _) if cmd_parts.get(1) == Some(&"status") => "git_status",
+                (Some(&"npm"), _) if cmd_parts.get(1) == Some(&"install") => "npm_install",
+                (Some(&"echo"), _) => "echo",
+                (Some(&"mkdir"), _) => "mkdir",
+                _ if cmd.len() > 30 => {
+                    let program = cmd_parts.first().unwrap_or(&"");
+                    return catalog.format("steps.program", &[("program", program)]);
                 }
-            } else {
-                "コマンドを実行".to_string()
-            }
+                _ => return catalog.format("steps.command", &[("command", &cmd)]),
+            };
+            catalog.get_in("steps", key).to_string()
         }
         "Grep" => {
             if let Some(pattern) = input.get("pattern").and_then(|v| v.as_str()) {
@@ -501,40 +611,41 @@ fn convert_tool_use_to_human_readable(tool_name: &str, input: &serde_json::Value
                 } else {
                     pattern.to_string()
                 };
-                format!("「{pattern_display}」を検索")
+                catalog.format("steps.search", &[("pattern", &pattern_display)])
             } else {
-                "コード内を検索".to_string()
+                catalog.get("steps.search_any").to_string()
             }
         }
-        "TodoWrite" => "TODOリストを更新".to_string(),
-        "TodoRead" => "TODOリストを確認".to_string(),
-        _ => format!("{tool_name} ツールを使用"),
+        "TodoWrite" => catalog.get("steps.todo_write").to_string(),
+        "TodoRead" => catalog.get("steps.todo_read").to_string(),
+        _ => catalog.format("steps.tool", &[("tool", &tool_name)]),
     }
 }
 
-fn analyze_tool_result(output: &serde_json::Value) -> StepResult {
+fn analyze_tool_result(output: &serde_json::Value, catalog: &Catalog) -> StepResult {
+    let done = || StepResult::Success(catalog.get("outcome.done").to_string());
     if let Some(output_str) = output.as_str() {
         if output_str.contains("error")
             || output_str.contains("Error")
             || output_str.contains("failed")
             || output_str.contains("Failed")
         {
-            StepResult::Failed(extract_error_summary(output_str))
+            StepResult::Failed(extract_error_summary(output_str, catalog))
         } else {
-            StepResult::Success("完了".to_string())
+            done()
         }
     } else if let Some(obj) = output.as_object() {
         if obj.contains_key("error") {
-            StepResult::Failed("エラーが発生".to_string())
+            StepResult::Failed(catalog.get("outcome.error").to_string())
         } else {
-            StepResult::Success("完了".to_string())
+            done()
         }
     } else {
-        StepResult::Success("完了".to_string())
+        done()
     }
 }
 
-fn extract_error_summary(error_text: &str) -> String {
+fn extract_error_summary(error_text: &str, catalog: &Catalog) -> String {
     let lines: Vec<&str> = error_text.lines().collect();
     if let Some(first_error_line) = lines
         .iter()
@@ -542,11 +653,11 @@ fn extract_error_summary(error_text: &str) -> String {
     {
         first_error_line.trim().to_string()
     } else {
-        "エラーが発生しました".to_string()
+        catalog.get("outcome.error").to_string()
     }
 }
 
-fn determine_topic_outcome(topic: &Topic) -> TopicOutcome {
+fn determine_topic_outcome(topic: &Topic, catalog: &Catalog) -> TopicOutcome {
     let total_steps = topic.steps.len();
     if total_steps == 0 {
         return TopicOutcome::InProgress;
@@ -563,11 +674,14 @@ fn determine_topic_outcome(topic: &Topic) -> TopicOutcome {
             });
 
     if failed_count > 0 && success_count == 0 {
-        TopicOutcome::Failed("すべての作業が失敗しました".to_string())
+        TopicOutcome::Failed(catalog.get("outcome.all_failed").to_string())
     } else if failed_count > 0 {
-        TopicOutcome::PartiallyCompleted(format!("{success_count}/{total_steps}の作業が完了"))
+        TopicOutcome::PartiallyCompleted(catalog.format(
+            "outcome.partial",
+            &[("done", &success_count), ("total", &total_steps)],
+        ))
     } else if success_count == total_steps {
-        TopicOutcome::Completed("すべての作業が正常に完了しました".to_string())
+        TopicOutcome::Completed(catalog.get("outcome.all_done").to_string())
     } else {
         TopicOutcome::InProgress
     }
@@ -0,0 +1,195 @@
+//! Conversation tree reconstruction from `uuid` / `parentUuid`
+//!
+//! Claude Code logs are append-only: editing a prompt or retrying a reply adds
+//! a sibling branch, and subagent runs (`isSidechain`) are written between the
+//! main thread's messages. Reading the file top to bottom therefore counts
+//! abandoned branches and mixes subagent prompts into the user's intent. The
+//! tree follows each thread to its latest leaf and keeps sidechains apart.
+//!
+//! Compaction starts a new root (`parentUuid: null`) whose `logicalParentUuid`
+//! names the last message before it; the tree continues from that message.
+
+use crate::parser::LogEntry;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Position of a node: its timestamp, then its line order as a tie-breaker
+type NodeOrder = (Option<DateTime<Utc>>, usize);
+
+/// The messages of one session arranged as a tree
+#[derive(Debug)]
+pub struct ConversationTree {
+    entries: Vec<LogEntry>,
+    /// Active branch of the main thread, concatenated in time order where the
+    /// date filter cut it into pieces
+    main: Vec<usize>,
+    /// Active branch of every subagent run
+    sidechains: Vec<Vec<usize>>,
+    /// Entries on branches that were edited or retried away
+    pub abandoned: usize,
+}
+
+impl ConversationTree {
+    /// Build the tree from the entries of one session, given in log order
+    pub fn build(entries: Vec<LogEntry>) -> Self {
+        let index_by_uuid: HashMap<&str, usize> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| entry.uuid.as_deref().map(|uuid| (uuid, index)))
+            .collect();
+
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); entries.len()];
+        let mut parents: Vec<Option<usize>> = vec![None; entries.len()];
+        let mut roots = Vec::new();
+
+        for (index, entry) in entries.iter().enumerate() {
+            // A subagent thread hanging off the main thread starts a tree of its own
+            let parent = entry
+                .parent_uuid
+                .as_deref()
+                .or(entry.logical_parent_uuid.as_deref())
+                .and_then(|uuid| index_by_uuid.get(uuid).copied())
+                .filter(|&parent| {
+                    parent != index && is_sidechain(&entries[parent]) == is_sidechain(entry)
+                });
+
+            match parent {
+                Some(parent) => {
+                    children[parent].push(index);
+                    parents[index] = Some(parent);
+                }
+                None => roots.push(index),
+            }
+        }
+
+        let order: Vec<NodeOrder> = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry.timestamp.as_deref().and_then(parse_timestamp), index))
+            .collect();
+
+        // Roots whose parent was cut off by the date filter compete like siblings
+        // when they share that parent (an edited prompt); roots without a parent
+        // (a new start, or a compaction outside the file) are all kept
+        let mut main_groups: Vec<(Option<&str>, Vec<usize>)> = Vec::new();
+        let mut sidechains = Vec::new();
+        for root in roots {
+            let branch = active_branch(root, &children, &parents, &order);
+            if is_sidechain(&entries[root]) {
+                sidechains.push(branch);
+                continue;
+            }
+
+            let parent = entries[root].parent_uuid.as_deref();
+            match main_groups
+                .iter_mut()
+                .find(|(key, _)| key.is_some() && *key == parent)
+            {
+                Some((_, current)) => {
+                    if order[*branch.last().unwrap()] > order[*current.last().unwrap()] {
+                        *current = branch;
+                    }
+                }
+                None => main_groups.push((parent, branch)),
+            }
+        }
+
+        main_groups.sort_by_key(|(_, branch)| order[branch[0]]);
+        let main: Vec<usize> = main_groups
+            .into_iter()
+            .flat_map(|(_, branch)| branch)
+            .collect();
+        let on_branch = main.len() + sidechains.iter().map(Vec::len).sum::<usize>();
+
+        Self {
+            abandoned: entries.len() - on_branch,
+            entries,
+            main,
+            sidechains,
+        }
+    }
+
+    /// Main-thread entries on the active branches, in conversation order
+    pub fn main_thread(&self) -> impl Iterator<Item = &LogEntry> {
+        self.main.iter().map(|&index| &self.entries[index])
+    }
+
+    /// Subagent threads, each in conversation order
+    pub fn sidechains(&self) -> impl Iterator<Item = Vec<&LogEntry>> {
+        self.sidechains
+            .iter()
+            .map(|thread| thread.iter().map(|&index| &self.entries[index]).collect())
+    }
+
+    /// All active entries in processing order, flagged `true` for sidechain entries
+    ///
+    /// Each subagent thread is placed as a block before the first main-thread
+    /// message that is newer than the thread's start, so its work lands in the
+    /// topic that was active when the subagent ran.
+    pub fn walk(&self) -> Vec<(&LogEntry, bool)> {
+        let start_of = |thread: &Vec<usize>| {
+            thread
+                .first()
+                .and_then(|&index| self.entries[index].timestamp.as_deref())
+                .and_then(parse_timestamp)
+        };
+
+        let mut pending: Vec<&Vec<usize>> = self.sidechains.iter().collect();
+        pending.sort_by_key(|thread| start_of(thread));
+        let mut pending = pending.into_iter().peekable();
+
+        let mut walk = Vec::with_capacity(self.main.len());
+        for &index in &self.main {
+            let entry = &self.entries[index];
+            let timestamp = entry.timestamp.as_deref().and_then(parse_timestamp);
+            while let Some(thread) = pending.next_if(|thread| start_of(thread) < timestamp) {
+                walk.extend(thread.iter().map(|&i| (&self.entries[i], true)));
+            }
+            walk.push((entry, false));
+        }
+        for thread in pending {
+            walk.extend(thread.iter().map(|&i| (&self.entries[i], true)));
+        }
+
+        walk
+    }
+}
+
+fn is_sidechain(entry: &LogEntry) -> bool {
+    entry.is_sidechain == Some(true)
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Path from `root` to the most recently written leaf below it
+fn active_branch(
+    root: usize,
+    children: &[Vec<usize>],
+    parents: &[Option<usize>],
+    order: &[NodeOrder],
+) -> Vec<usize> {
+    // Iterative search: long sessions are far deeper than the call stack allows
+    let mut latest_leaf: Option<usize> = None;
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if !children[node].is_empty() {
+            stack.extend(&children[node]);
+        } else if latest_leaf.map_or(true, |leaf| order[node] > order[leaf]) {
+            latest_leaf = Some(node);
+        }
+    }
+    let latest_leaf = latest_leaf.unwrap_or(root);
+
+    let mut branch = vec![latest_leaf];
+    let mut node = latest_leaf;
+    while let Some(parent) = parents[node] {
+        branch.push(parent);
+        node = parent;
+    }
+    branch.reverse();
+    branch
+}
@@ -0,0 +1,270 @@
+//! Report period and the time zone that defines its days
+//!
+//! Log timestamps are UTC, but "today" or "June 29" means the user's calendar
+//! day. Every timestamp is therefore converted to the report time zone before
+//! its date is compared with the period, so late-evening work is counted on
+//! the day it happened.
+
+use crate::i18n::Catalog;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
+use clap::ArgMatches;
+use std::fmt;
+use std::str::FromStr;
+
+/// Time zone used for calendar days: the system's, or an IANA zone from `--tz`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportTimeZone {
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl ReportTimeZone {
+    /// The instant as wall-clock time in this zone
+    pub fn to_local(&self, timestamp: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Local => timestamp.with_timezone(&Local).fixed_offset(),
+            Self::Named(tz) => timestamp.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// Calendar day of the instant in this zone
+    pub fn date_of(&self, timestamp: DateTime<Utc>) -> NaiveDate {
+        self.to_local(timestamp).date_naive()
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.date_of(Utc::now())
+    }
+}
+
+impl FromStr for ReportTimeZone {
+    type Err = String;
+
+    /// `local` or an IANA name such as `Asia/Tokyo`
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        name.parse::<Tz>().map(Self::Named).map_err(|_| {
+            format!("Unknown time zone '{name}' (expected an IANA name such as Asia/Tokyo)")
+        })
+    }
+}
+
+impl fmt::Display for ReportTimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// Inclusive range of calendar days in a time zone; open ends are unbounded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub tz: ReportTimeZone,
+}
+
+impl DateFilter {
+    /// The report period selected on the command line, today by default
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let tz = matches
+            .get_one::<ReportTimeZone>("tz")
+            .copied()
+            .unwrap_or_default();
+        Self::from_matches_at(matches, tz.today())
+    }
+
+    /// [`DateFilter::from_matches`] with relative periods counted from `today`
+    pub fn from_matches_at(matches: &ArgMatches, today: NaiveDate) -> Result<Self, String> {
+        let tz = matches
+            .get_one::<ReportTimeZone>("tz")
+            .copied()
+            .unwrap_or_default();
+
+        let (from, to) = if let Some(expr) = matches.get_one::<String>("date") {
+            let (from, to) = parse_period(expr, today)?;
+            (Some(from), Some(to))
+        } else if let Some(expr) = matches.get_one::<String>("since") {
+            (Some(parse_since(expr, today)?), None)
+        } else if matches.get_flag("yesterday") {
+            let yesterday = today - Duration::days(1);
+            (Some(yesterday), Some(yesterday))
+        } else if matches.get_flag("weekly") {
+            week_of(today)
+        } else if matches.get_flag("last-week") {
+            week_of(today - Duration::days(7))
+        } else if matches.get_flag("monthly") {
+            month_of(today)
+        } else if matches.get_flag("last-month") {
+            month_of(today.with_day(1).unwrap_or(today) - Duration::days(1))
+        } else if matches.get_flag("quarter") {
+            quarter_of(today)
+        } else if matches.get_flag("all") {
+            (None, None)
+        } else if matches.contains_id("from") || matches.contains_id("to") {
+            let from = matches
+                .get_one::<String>("from")
+                .map(|expr| parse_period(expr, today).map(|(start, _)| start))
+                .transpose()
+                .map_err(|e| format!("Invalid from date: {e}"))?;
+            let to = matches
+                .get_one::<String>("to")
+                .map(|expr| parse_period(expr, today).map(|(_, end)| end))
+                .transpose()
+                .map_err(|e| format!("Invalid to date: {e}"))?;
+            if let (Some(from), Some(to)) = (from, to) {
+                if from > to {
+                    return Err(format!("--from {from} is after --to {to}"));
+                }
+            }
+            (from, to)
+        } else {
+            // Default behavior: when no date options are specified, use today
+            (Some(today), Some(today))
+        };
+
+        Ok(Self { from, to, tz })
+    }
+
+    /// Every date
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn day(date: NaiveDate) -> Self {
+        Self::range(Some(date), Some(date))
+    }
+
+    pub fn range(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        Self {
+            from,
+            to,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_tz(self, tz: ReportTimeZone) -> Self {
+        Self { tz, ..self }
+    }
+
+    pub fn contains_date(&self, date: NaiveDate) -> bool {
+        self.from.map_or(true, |from| date >= from) && self.to.map_or(true, |to| date <= to)
+    }
+
+    /// True if the instant falls on a day of the range in the report time zone
+    pub fn contains(&self, timestamp: DateTime<Utc>) -> bool {
+        self.contains_date(self.tz.date_of(timestamp))
+    }
+
+    /// True if the instant falls on a day before the range
+    pub fn is_before(&self, timestamp: DateTime<Utc>) -> bool {
+        self.from
+            .is_some_and(|from| self.tz.date_of(timestamp) < from)
+    }
+
+    /// True if the instant falls on a day after the range
+    pub fn is_after(&self, timestamp: DateTime<Utc>) -> bool {
+        self.to.is_some_and(|to| self.tz.date_of(timestamp) > to)
+    }
+
+    /// True if the range has no bounds
+    pub fn is_all(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+
+    /// The range as shown in report titles, with the time zone unless it is the system's
+    pub fn label(&self, catalog: &Catalog) -> String {
+        let day = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+        let range = match (self.from, self.to) {
+            (Some(from), Some(to)) if from == to => day(from),
+            (Some(from), Some(to)) => {
+                catalog.format("date.range", &[("from", &day(from)), ("to", &day(to))])
+            }
+            (Some(from), None) => catalog.format("date.since", &[("from", &day(from))]),
+            (None, Some(to)) => catalog.format("date.until", &[("to", &day(to))]),
+            (None, None) => catalog.get("date.all").to_string(),
+        };
+        match self.tz {
+            ReportTimeZone::Local => range,
+            tz => format!("{range} ({tz})"),
+        }
+    }
+}
+
+/// First and last day of a date expression
+///
+/// Accepts `YYYY-MM-DD`, an ISO week `YYYY-Www` (Monday to Sunday), `today`
+/// and `yesterday`.
+pub fn parse_period(expr: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    let expr = expr.trim();
+    match expr.to_ascii_lowercase().as_str() {
+        "today" => return Ok((today, today)),
+        "yesterday" => {
+            let yesterday = today - Duration::days(1);
+            return Ok((yesterday, yesterday));
+        }
+        _ => {}
+    }
+
+    if let Some((year, week)) = expr.split_once(['W', 'w']) {
+        let year = year.trim_end_matches('-');
+        let monday = year
+            .parse::<i32>()
+            .ok()
+            .zip(week.parse::<u32>().ok())
+            .and_then(|(year, week)| NaiveDate::from_isoywd_opt(year, week, Weekday::Mon))
+            .ok_or_else(|| format!("'{expr}' is not an ISO week (expected YYYY-Www)"))?;
+        return Ok((monday, monday + Duration::days(6)));
+    }
+
+    let date = NaiveDate::parse_from_str(expr, "%Y-%m-%d")
+        .map_err(|e| format!("'{expr}' is not a date (expected YYYY-MM-DD): {e}"))?;
+    Ok((date, date))
+}
+
+/// Start of `--since`: `Nd`, `Nw` or `Nm` ago, or a date expression
+pub fn parse_since(expr: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let expr = expr.trim();
+    let split = expr.char_indices().last().map_or(0, |(index, _)| index);
+    let (count, unit) = expr.split_at(split);
+    if let Ok(count) = count.parse::<u32>() {
+        let start = match unit.to_ascii_lowercase().as_str() {
+            "d" => today.checked_sub_signed(Duration::days(count.into())),
+            "w" => today.checked_sub_signed(Duration::weeks(count.into())),
+            "m" => today.checked_sub_months(Months::new(count)),
+            _ => return Err(format!("Unknown unit in '{expr}' (expected d, w or m)")),
+        };
+        return start.ok_or_else(|| format!("'{expr}' is too far in the past"));
+    }
+    parse_period(expr, today).map(|(start, _)| start)
+}
+
+/// Monday to Sunday of the week containing `date`
+fn week_of(date: NaiveDate) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    let monday = date - Duration::days(date.weekday().num_days_from_monday().into());
+    (Some(monday), Some(monday + Duration::days(6)))
+}
+
+/// First and last day of the month containing `date`
+fn month_of(date: NaiveDate) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    months_from(date.year(), date.month(), 1)
+}
+
+/// First and last day of the calendar quarter containing `date`
+fn quarter_of(date: NaiveDate) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    months_from(date.year(), (date.month0() / 3) * 3 + 1, 3)
+}
+
+fn months_from(year: i32, month: u32, count: u32) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    let first = NaiveDate::from_ymd_opt(year, month, 1);
+    let last = first
+        .and_then(|first| first.checked_add_months(Months::new(count)))
+        .map(|next| next - Duration::days(1));
+    (first, last)
+}
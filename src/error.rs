@@ -41,6 +41,25 @@ pub enum ApiError {
 
     /// Network error
     Network(String),
+
+    /// Extra header that is not a valid HTTP header
+    InvalidHeader(String),
+
+    /// The request still failed after retrying
+    RetriesExhausted {
+        retries: u32,
+        last_error: Box<ApiError>,
+    },
+}
+
+impl ApiError {
+    /// Number of retries made before giving up
+    pub fn retries(&self) -> u32 {
+        match self {
+            ApiError::RetriesExhausted { retries, .. } => *retries,
+            _ => 0,
+        }
+    }
 }
 
 impl fmt::Display for AppError {
@@ -61,7 +80,7 @@ impl fmt::Display for ApiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ApiError::MissingApiKey => {
-                write!(f, "OpenAI API key is required. Set OPENAI_API_KEY environment variable or use --api-key option.")
+                write!(f, "API key is required. Set OPENAI_API_KEY (or ANTHROPIC_API_KEY) environment variable or use --api-key option.")
             }
             ApiError::RequestFailed { status, message } => {
                 write!(f, "API request failed with status {status}: {message}")
@@ -75,6 +94,15 @@ impl fmt::Display for ApiError {
             ApiError::Network(msg) => {
                 write!(f, "Network error: {msg}")
             }
+            ApiError::InvalidHeader(msg) => {
+                write!(f, "Invalid HTTP header: {msg}")
+            }
+            ApiError::RetriesExhausted {
+                retries,
+                last_error,
+            } => {
+                write!(f, "{last_error} (gave up after {retries} retries)")
+            }
         }
     }
 }
@@ -0,0 +1,161 @@
+//! Files a project's sessions created, edited or read
+//!
+//! Built from the `Write`, `Edit`, `MultiEdit` and `Read` tool calls in the
+//! transcript, so a summary can be checked against the files it claims were
+//! changed. Line counts come from the tool input: the lines of a written file,
+//! and the lines that differ between `old_string` and `new_string` of an edit.
+
+use crate::parser::LogEntry;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FileChange {
+    /// Path relative to the project root when inside it
+    pub path: String,
+    /// Written before it was ever read or edited, i.e. a new file
+    pub created: bool,
+    /// `Write`, `Edit` and `MultiEdit` calls
+    pub edits: usize,
+    pub reads: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+/// Files touched in a project, keyed by path
+#[derive(Debug, Clone, Default)]
+pub struct FileLedger {
+    root: PathBuf,
+    files: HashMap<String, FileChange>,
+}
+
+impl FileLedger {
+    /// Ledger showing paths below `root` relative to it
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Record the file tool calls of an assistant entry
+    pub fn record_entry(&mut self, entry: &LogEntry) {
+        if entry.entry_type != "assistant" {
+            return;
+        }
+        let Some(blocks) = entry
+            .message
+            .as_ref()
+            .and_then(|message| message.content.as_ref())
+            .and_then(|content| content.as_array())
+        else {
+            return;
+        };
+
+        for block in blocks {
+            if block.get("type").and_then(|v| v.as_str()) != Some("tool_use") {
+                continue;
+            }
+            if let (Some(name), Some(input)) = (
+                block.get("name").and_then(|v| v.as_str()),
+                block.get("input"),
+            ) {
+                self.record_tool_use(name, input);
+            }
+        }
+    }
+
+    /// Record one tool call; other tools than Write/Edit/MultiEdit/Read are ignored
+    pub fn record_tool_use(&mut self, name: &str, input: &serde_json::Value) {
+        if !matches!(name, "Read" | "Write" | "Edit" | "MultiEdit") {
+            return;
+        }
+        let Some(path) = input.get("file_path").and_then(|v| v.as_str()) else {
+            return;
+        };
+        let text = |value: &serde_json::Value, key: &str| {
+            value
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let path = self.display_path(path);
+        let seen = self.files.contains_key(&path);
+        let file = self
+            .files
+            .entry(path.clone())
+            .or_insert_with(|| FileChange {
+                path,
+                ..FileChange::default()
+            });
+        match name {
+            "Read" => file.reads += 1,
+            "Write" => {
+                // Claude Code only overwrites files it has read, so an unseen file is new
+                file.created |= !seen;
+                file.edits += 1;
+                file.lines_added += text(input, "content").lines().count();
+            }
+            "Edit" => {
+                file.edits += 1;
+                let (added, removed) =
+                    changed_lines(&text(input, "old_string"), &text(input, "new_string"));
+                file.lines_added += added;
+                file.lines_removed += removed;
+            }
+            "MultiEdit" => {
+                file.edits += 1;
+                let edits = input.get("edits").and_then(|v| v.as_array());
+                for edit in edits.into_iter().flatten() {
+                    let (added, removed) =
+                        changed_lines(&text(edit, "old_string"), &text(edit, "new_string"));
+                    file.lines_added += added;
+                    file.lines_removed += removed;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Changed files by lines changed, then files that were only read
+    pub fn into_files(self) -> Vec<FileChange> {
+        let mut files: Vec<FileChange> = self.files.into_values().collect();
+        files.sort_by(|a, b| {
+            (b.edits > 0)
+                .cmp(&(a.edits > 0))
+                .then((b.lines_added + b.lines_removed).cmp(&(a.lines_added + a.lines_removed)))
+                .then(b.reads.cmp(&a.reads))
+                .then(a.path.cmp(&b.path))
+        });
+        files
+    }
+
+    fn display_path(&self, path: &str) -> String {
+        Path::new(path)
+            .strip_prefix(&self.root)
+            .ok()
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map_or_else(
+                || path.to_string(),
+                |relative| relative.display().to_string(),
+            )
+    }
+}
+
+/// Lines added and removed by replacing `old` with `new`, ignoring the
+/// unchanged lines at the start and end
+pub fn changed_lines(old: &str, new: &str) -> (usize, usize) {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (new.len() - prefix - suffix, old.len() - prefix - suffix)
+}
@@ -0,0 +1,214 @@
+//! Commits made in a project's repository during the report period
+//!
+//! The transcript says what was attempted; the commits say what was actually
+//! delivered. They are read with the `git` command line tool from the
+//! repository the sessions ran in, limited to the configured author unless
+//! `--all-authors` is given, and attached to the topic that was being worked
+//! on when they were made.
+
+use crate::conversation_analyzer::Topic;
+use crate::date_filter::DateFilter;
+use crate::session::SessionSummary;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+/// How long after a topic ends a commit still counts towards it
+const COMMIT_GRACE_MINUTES: i64 = 30;
+
+const RECORD_SEPARATOR: char = '\x1e';
+const FIELD_SEPARATOR: char = '\x1f';
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Commit {
+    pub hash: String,
+    /// Author date, RFC 3339 in the report time zone
+    pub timestamp: String,
+    pub author: String,
+    pub subject: String,
+    /// Paths changed by the commit
+    pub files: Vec<String>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl Commit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+
+    /// `abc1234 Subject`, as in `git log --oneline`
+    pub fn oneline(&self) -> String {
+        format!("{} {}", self.short_hash(), self.subject)
+    }
+}
+
+/// Lines changed by a set of commits; files are counted once
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl DiffStat {
+    pub fn of(commits: &[Commit]) -> Self {
+        let files: HashSet<&str> = commits
+            .iter()
+            .flat_map(|commit| commit.files.iter().map(String::as_str))
+            .collect();
+        Self {
+            files_changed: files.len(),
+            insertions: commits.iter().map(|c| c.insertions).sum(),
+            deletions: commits.iter().map(|c| c.deletions).sum(),
+        }
+    }
+}
+
+/// Non-merge commits on any branch of `repo` authored in the period, oldest first
+///
+/// Unless `all_authors` is set, only commits by the repository's `user.email`
+/// are listed when it is set, so a teammate's work in a shared clone is not
+/// reported as delivered.
+pub fn commits_in(
+    repo: &Path,
+    date_filter: DateFilter,
+    all_authors: bool,
+) -> Result<Vec<Commit>, String> {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(repo)
+        .args(["log", "--all", "--no-merges", "--numstat", "--reverse"])
+        .arg(format!(
+            "--format={RECORD_SEPARATOR}%H{FIELD_SEPARATOR}%aI{FIELD_SEPARATOR}%an{FIELD_SEPARATOR}%ae{FIELD_SEPARATOR}%s"
+        ));
+    // Loose bounds; the report time zone decides the exact days below
+    if let Some(from) = date_filter.from {
+        command.arg(format!("--since={}T00:00:00Z", from - Duration::days(1)));
+    }
+    if let Some(to) = date_filter.to {
+        command.arg(format!("--until={}T00:00:00Z", to + Duration::days(2)));
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git log failed in {}: {}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let author_email = if all_authors { None } else { user_email(repo) };
+    let log = String::from_utf8_lossy(&output.stdout);
+    Ok(log
+        .split(RECORD_SEPARATOR)
+        .filter_map(parse_commit)
+        .filter(|(_, email, _)| {
+            author_email
+                .as_deref()
+                .map_or(true, |own| email.eq_ignore_ascii_case(own))
+        })
+        .filter(|(_, _, time)| date_filter.contains(*time))
+        .map(|(mut commit, _, time)| {
+            commit.timestamp = date_filter.tz.to_local(time).to_rfc3339();
+            commit
+        })
+        .collect())
+}
+
+/// Add each commit to the topic that was active when it was made
+///
+/// A topic lasts from its start until the next topic starts, or until the end
+/// of its session for the last one, plus a short grace period for committing
+/// after the conversation.
+pub fn attach_commits(topics: &mut [Topic], sessions: &[SessionSummary], commits: &[Commit]) {
+    let grace = Duration::minutes(COMMIT_GRACE_MINUTES);
+    let windows: Vec<Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)>> = topics
+        .iter()
+        .map(|topic| {
+            let start = parse(&topic.started_at)?;
+            let end = topic
+                .completed_at
+                .as_deref()
+                .or_else(|| {
+                    sessions
+                        .iter()
+                        .find(|s| Some(&s.session_id) == topic.session_id.as_ref())
+                        .map(|s| s.end.as_str())
+                })
+                .and_then(parse)
+                .unwrap_or(start);
+            Some((start, end.max(start) + grace))
+        })
+        .collect();
+
+    for commit in commits {
+        let Some(time) = parse(&commit.timestamp) else {
+            continue;
+        };
+        let topic = windows
+            .iter()
+            .enumerate()
+            .filter_map(|(index, window)| window.map(|window| (index, window)))
+            .filter(|(_, (start, end))| *start <= time && time <= *end)
+            .max_by_key(|(_, (start, _))| *start);
+        if let Some((index, _)) = topic {
+            topics[index].commits.push(commit.oneline());
+        }
+    }
+}
+
+/// A `git log` record: header line, then one `--numstat` line per file
+fn parse_commit(record: &str) -> Option<(Commit, String, DateTime<Utc>)> {
+    let mut lines = record.lines();
+    let mut fields = lines.next()?.split(FIELD_SEPARATOR);
+    let hash = fields.next()?.to_string();
+    let time = DateTime::parse_from_rfc3339(fields.next()?).ok()?;
+    let author = fields.next()?.to_string();
+    let email = fields.next()?.to_string();
+    let subject = fields.next().unwrap_or_default().to_string();
+
+    let mut commit = Commit {
+        hash,
+        timestamp: time.to_rfc3339(),
+        author,
+        subject,
+        files: Vec::new(),
+        insertions: 0,
+        deletions: 0,
+    };
+    for line in lines.filter(|line| !line.is_empty()) {
+        let mut columns = line.splitn(3, '\t');
+        let (Some(added), Some(removed), Some(path)) =
+            (columns.next(), columns.next(), columns.next())
+        else {
+            continue;
+        };
+        // Binary files show `-` instead of line counts
+        commit.insertions += added.parse::<usize>().unwrap_or(0);
+        commit.deletions += removed.parse::<usize>().unwrap_or(0);
+        commit.files.push(path.to_string());
+    }
+    Some((commit, email, time.with_timezone(&Utc)))
+}
+
+fn user_email(repo: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["config", "user.email"])
+        .output()
+        .ok()?;
+    let email = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !email.is_empty()).then_some(email)
+}
+
+fn parse(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(timestamp).ok()
+}
@@ -0,0 +1,429 @@
+//! Self-contained HTML report
+//!
+//! One file with inline CSS and SVG charts and no external resources, so it
+//! can be attached to an email or opened offline. Projects are collapsible
+//! sections; the effort distribution is a stacked bar chart, the active time a
+//! calendar heatmap, and the token usage a cost table.
+
+use crate::i18n::Catalog;
+use crate::smart_analyzer::{EffortMetrics, SmartProjectSummary, SmartReport};
+use crate::token_tracker::TokenTracker;
+use crate::work_time::{format_minutes, ActiveTime};
+use chrono::{Datelike, Duration, NaiveDate};
+
+const STYLE: &str = "body{font-family:-apple-system,'Segoe UI',Helvetica,Arial,sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#24292f;line-height:1.5}\
+h1{border-bottom:1px solid #d0d7de;padding-bottom:.3em}\
+h2{margin-top:1.6em}\
+details{border:1px solid #d0d7de;border-radius:6px;margin:1em 0;padding:.5em 1em}\
+summary{cursor:pointer;font-weight:600;font-size:1.15em}\
+table{border-collapse:collapse;margin:.5em 0}\
+th,td{border:1px solid #d0d7de;padding:.25em .6em;text-align:left}\
+td.num{text-align:right;font-variant-numeric:tabular-nums}\
+.legend span{display:inline-block;margin-right:1em}\
+.swatch{display:inline-block;width:.8em;height:.8em;margin-right:.3em;border-radius:2px}\
+.muted{color:#57606a}";
+
+/// Effort categories in chart order, with their colors
+const EFFORT_COLORS: [&str; 4] = ["#2da44e", "#cf222e", "#bf8700", "#8c959f"];
+
+/// Heatmap colors from no activity to four hours or more
+const HEAT_COLORS: [&str; 5] = ["#ebedf0", "#c6e48b", "#7bc96f", "#239a3b", "#196127"];
+const HEAT_CELL: i64 = 14;
+const HEAT_STEP: i64 = 17;
+
+/// Texts of the HTML report, from the message catalog
+struct Labels<'a> {
+    title: &'a str,
+    objective: &'a str,
+    activities: &'a str,
+    delivered: &'a str,
+    improvements: &'a str,
+    unresolved: &'a str,
+    effort: &'a str,
+    /// Development, debugging, configuration, other
+    categories: [&'a str; 4],
+    activity: &'a str,
+    less: &'a str,
+    more: &'a str,
+    active_time: &'a str,
+    sessions: &'a str,
+    minutes: &'a str,
+    commits: &'a str,
+    files: &'a str,
+    tokens: &'a str,
+    /// Project, input, output, total, cost
+    token_columns: [&'a str; 5],
+    total: &'a str,
+    timeline: &'a str,
+}
+
+impl<'a> Labels<'a> {
+    fn new(catalog: &'a Catalog) -> Self {
+        let chart = |key| catalog.get_in("chart", key);
+        Self {
+            title: catalog.get("report.title"),
+            objective: catalog.get("report.objective"),
+            activities: catalog.get("report.activities"),
+            delivered: catalog.get("report.delivered"),
+            improvements: catalog.get("report.improvements"),
+            unresolved: catalog.get("report.unresolved"),
+            effort: chart("effort"),
+            categories: ["development", "debugging", "configuration", "other"].map(chart),
+            activity: chart("activity"),
+            less: chart("less"),
+            more: chart("more"),
+            active_time: catalog.get("active_time.heading"),
+            sessions: catalog.get("sessions.heading"),
+            minutes: catalog.get("sessions.minutes"),
+            commits: catalog.get("commits.heading"),
+            files: catalog.get("files.heading"),
+            tokens: chart("tokens"),
+            token_columns: ["project", "input", "output", "total", "cost"].map(chart),
+            total: chart("total"),
+            timeline: catalog.get("timeline.heading"),
+        }
+    }
+}
+
+/// The report as a standalone HTML document
+pub fn render(report: &SmartReport, lang: &str) -> String {
+    let catalog = Catalog::new(lang);
+    let labels = Labels::new(&catalog);
+    let mut html = String::new();
+    html.push_str(&format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{} - {}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n",
+        escape(lang),
+        labels.title,
+        escape(&report.date)
+    ));
+    html.push_str(&format!(
+        "<h1>{} - {}</h1>\n",
+        labels.title,
+        escape(&report.date)
+    ));
+
+    push_effort_chart(&mut html, &report.projects, &labels);
+    if let Some(active) = &report.active_time {
+        push_heatmap(&mut html, active, &labels);
+    }
+    for project in &report.projects {
+        push_project(&mut html, project, &labels);
+    }
+    push_timeline(&mut html, report, &labels);
+    if let Some(tracker) = &report.token_tracker {
+        push_token_table(&mut html, tracker, &labels);
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn push_project(html: &mut String, project: &SmartProjectSummary, labels: &Labels) {
+    html.push_str(&format!(
+        "<details open>\n<summary>{}</summary>\n<p class=\"muted\">{}</p>\n",
+        escape(&project.title),
+        escape(&project.project)
+    ));
+    html.push_str(&format!(
+        "<p><strong>{}</strong>: {}</p>\n",
+        labels.objective,
+        escape(&project.objective)
+    ));
+    if let Some(active) = &project.active_time {
+        html.push_str(&format!(
+            "<p><strong>{}</strong>: {} ({})</p>\n",
+            labels.active_time,
+            format_minutes(active.minutes),
+            active.span()
+        ));
+    }
+
+    let activities: Vec<String> = project
+        .work_breakdown
+        .primary_activities
+        .iter()
+        .map(|activity| format!("{} - {}", activity.category, activity.summary))
+        .collect();
+    push_list(html, labels.activities, &activities);
+    push_list(html, labels.delivered, &project.outcomes.delivered_value);
+    push_list(
+        html,
+        labels.improvements,
+        &project.outcomes.technical_improvements,
+    );
+    push_list(html, labels.unresolved, &project.outcomes.unresolved_issues);
+
+    let sessions: Vec<String> = project
+        .sessions
+        .iter()
+        .map(|session| {
+            format!(
+                "{} ({} {}): {}",
+                session.time_range(),
+                session.duration_minutes,
+                labels.minutes,
+                session.summary
+            )
+        })
+        .collect();
+    push_list(html, labels.sessions, &sessions);
+
+    let commits: Vec<String> = project
+        .commits
+        .iter()
+        .map(|commit| {
+            format!(
+                "{} (+{} −{})",
+                commit.oneline(),
+                commit.insertions,
+                commit.deletions
+            )
+        })
+        .collect();
+    push_list(html, labels.commits, &commits);
+
+    let files: Vec<String> = project
+        .files
+        .iter()
+        .filter(|file| file.edits > 0)
+        .map(|file| {
+            format!(
+                "{} (+{} −{})",
+                file.path, file.lines_added, file.lines_removed
+            )
+        })
+        .collect();
+    push_list(html, labels.files, &files);
+
+    html.push_str("</details>\n");
+}
+
+fn push_list(html: &mut String, heading: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    html.push_str(&format!("<h3>{heading}</h3>\n<ul>\n"));
+    for item in items {
+        html.push_str(&format!("<li>{}</li>\n", escape(item)));
+    }
+    html.push_str("</ul>\n");
+}
+
+/// One stacked 100% bar per project with a known effort distribution
+fn push_effort_chart(html: &mut String, projects: &[SmartProjectSummary], labels: &Labels) {
+    let shares = |effort: &EffortMetrics| {
+        [
+            effort.development,
+            effort.debugging,
+            effort.configuration,
+            effort.other,
+        ]
+    };
+    let rows: Vec<(&SmartProjectSummary, [f32; 4])> = projects
+        .iter()
+        .map(|project| (project, shares(&project.work_breakdown.effort_distribution)))
+        .filter(|(_, shares)| shares.iter().sum::<f32>() > 0.0)
+        .collect();
+    if rows.is_empty() {
+        return;
+    }
+
+    let (label_width, bar_width, row_height) = (220.0, 480.0, 26.0);
+    html.push_str(&format!("<h2>{}</h2>\n", labels.effort));
+    html.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" role=\"img\" aria-label=\"{}\">\n",
+        label_width + bar_width,
+        row_height * rows.len() as f32,
+        labels.effort
+    ));
+    for (index, (project, shares)) in rows.iter().enumerate() {
+        let y = row_height * index as f32;
+        let title: String = project.title.chars().take(30).collect();
+        html.push_str(&format!(
+            "<text x=\"0\" y=\"{}\" font-size=\"12\">{}</text>\n",
+            y + 16.0,
+            escape(&title)
+        ));
+        let total: f32 = shares.iter().sum();
+        let mut x = label_width;
+        for ((share, color), category) in shares.iter().zip(EFFORT_COLORS).zip(labels.categories) {
+            let width = bar_width * share / total;
+            if width > 0.0 {
+                html.push_str(&format!(
+                    "<rect x=\"{x:.1}\" y=\"{}\" width=\"{width:.1}\" height=\"18\" fill=\"{color}\"><title>{category}: {:.0}%</title></rect>\n",
+                    y + 4.0,
+                    share / total * 100.0
+                ));
+            }
+            x += width;
+        }
+    }
+    html.push_str("</svg>\n<p class=\"legend\">");
+    for (category, color) in labels.categories.iter().zip(EFFORT_COLORS) {
+        html.push_str(&format!(
+            "<span><span class=\"swatch\" style=\"background:{color}\"></span>{category}</span>"
+        ));
+    }
+    html.push_str("</p>\n");
+}
+
+/// Calendar of active time, one column per week from Monday to Sunday
+fn push_heatmap(html: &mut String, active: &ActiveTime, labels: &Labels) {
+    let days: Vec<(NaiveDate, i64)> = active
+        .days
+        .iter()
+        .filter_map(|day| {
+            let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok()?;
+            Some((date, day.minutes))
+        })
+        .collect();
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        return;
+    };
+    let start = first.0 - Duration::days(first.0.weekday().num_days_from_monday() as i64);
+    let weeks = (last.0 - start).num_days() / 7 + 1;
+
+    html.push_str(&format!(
+        "<h2>{}</h2>\n<p>{}: <strong>{}</strong></p>\n",
+        labels.activity,
+        labels.active_time,
+        format_minutes(active.minutes)
+    ));
+    html.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" role=\"img\" aria-label=\"{}\">\n",
+        weeks * HEAT_STEP,
+        7 * HEAT_STEP,
+        labels.activity
+    ));
+    let mut date = start;
+    while date <= last.0 {
+        let minutes = days
+            .iter()
+            .find(|(day, _)| *day == date)
+            .map_or(0, |(_, minutes)| *minutes);
+        let week = (date - start).num_days() / 7;
+        let weekday = date.weekday().num_days_from_monday() as i64;
+        html.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{HEAT_CELL}\" height=\"{HEAT_CELL}\" rx=\"2\" fill=\"{}\"><title>{date}: {}</title></rect>\n",
+            week * HEAT_STEP,
+            weekday * HEAT_STEP,
+            HEAT_COLORS[heat_level(minutes)],
+            format_minutes(minutes)
+        ));
+        date += Duration::days(1);
+    }
+    html.push_str(&format!(
+        "</svg>\n<p class=\"legend muted\">{} ",
+        labels.less
+    ));
+    for color in HEAT_COLORS {
+        html.push_str(&format!(
+            "<span class=\"swatch\" style=\"background:{color}\"></span>"
+        ));
+    }
+    html.push_str(&format!(" {}</p>\n", labels.more));
+}
+
+fn heat_level(minutes: i64) -> usize {
+    match minutes {
+        0 => 0,
+        1..=59 => 1,
+        60..=119 => 2,
+        120..=239 => 3,
+        _ => 4,
+    }
+}
+
+fn push_timeline(html: &mut String, report: &SmartReport, labels: &Labels) {
+    if report.timeline.is_empty() {
+        return;
+    }
+    html.push_str(&format!(
+        "<details open>\n<summary>{}</summary>\n",
+        labels.timeline
+    ));
+    for bucket in &report.timeline {
+        let entries: Vec<String> = bucket
+            .entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {} {}: {}",
+                    entry.time_range(!bucket.is_day()),
+                    entry.status.icon(),
+                    entry.project,
+                    entry.title
+                )
+            })
+            .collect();
+        push_list(html, &escape(&bucket.label()), &entries);
+    }
+    html.push_str("</details>\n");
+}
+
+fn push_token_table(html: &mut String, tracker: &TokenTracker, labels: &Labels) {
+    let cost = |prompt: u32, completion: u32| {
+        tracker
+            .cost_estimate
+            .as_ref()
+            .map_or(String::new(), |cost| {
+                format!(
+                    "${:.4}",
+                    prompt as f64 / 1000.0 * cost.input_cost_per_1k
+                        + completion as f64 / 1000.0 * cost.output_cost_per_1k
+                )
+            })
+    };
+    let row = |name: &str, prompt: u32, completion: u32, total: u32| {
+        format!(
+            "<tr><td>{}</td><td class=\"num\">{prompt}</td><td class=\"num\">{completion}</td><td class=\"num\">{total}</td><td class=\"num\">{}</td></tr>\n",
+            name,
+            cost(prompt, completion)
+        )
+    };
+
+    let [project, input, output, total, cost_column] = labels.token_columns;
+    html.push_str(&format!("<h2>{}</h2>\n", labels.tokens));
+    if let Some(cost) = &tracker.cost_estimate {
+        html.push_str(&format!("<p class=\"muted\">{}</p>\n", escape(&cost.model)));
+    }
+    html.push_str(&format!(
+        "<table>\n<tr><th>{project}</th><th>{input}</th><th>{output}</th><th>{total}</th><th>{cost_column}</th></tr>\n"
+    ));
+    let mut projects: Vec<_> = tracker.project_usage.iter().collect();
+    projects.sort_by_key(|(name, usage)| (std::cmp::Reverse(usage.total_tokens), *name));
+    for (name, usage) in projects {
+        html.push_str(&row(
+            &escape(name),
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            usage.total_tokens,
+        ));
+    }
+    let usage = &tracker.total_usage;
+    html.push_str(&row(
+        &format!("<strong>{}</strong>", labels.total),
+        usage.prompt_tokens,
+        usage.completion_tokens,
+        usage.total_tokens,
+    ));
+    html.push_str("</table>\n");
+}
+
+/// Escape text for HTML element content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
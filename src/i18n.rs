@@ -0,0 +1,176 @@
+//! Message catalog for the text of the reports
+//!
+//! Every language is a TOML file in `locales/`, compiled into the binary. Keys
+//! are the dotted paths of the values (`[sessions] heading` is
+//! `sessions.heading`) and may contain `{name}` placeholders. A key missing in
+//! a language is looked up along its fallback chain: the language itself, the
+//! language named by its `fallback` entry, and English last.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// Languages of `--lang`, each with a catalog
+pub const LANGUAGES: [&str; 19] = [
+    "en", "ja", "zh", "ko", "es", "fr", "de", "pt", "ru", "it", "nl", "pl", "tr", "ar", "hi", "th",
+    "vi", "id", "ms",
+];
+
+/// Language every chain ends with; its catalog has all keys
+const BASE_LANGUAGE: &str = "en";
+
+const LOCALES: [(&str, &str); 19] = [
+    ("en", include_str!("../locales/en.toml")),
+    ("ja", include_str!("../locales/ja.toml")),
+    ("zh", include_str!("../locales/zh.toml")),
+    ("ko", include_str!("../locales/ko.toml")),
+    ("es", include_str!("../locales/es.toml")),
+    ("fr", include_str!("../locales/fr.toml")),
+    ("de", include_str!("../locales/de.toml")),
+    ("pt", include_str!("../locales/pt.toml")),
+    ("ru", include_str!("../locales/ru.toml")),
+    ("it", include_str!("../locales/it.toml")),
+    ("nl", include_str!("../locales/nl.toml")),
+    ("pl", include_str!("../locales/pl.toml")),
+    ("tr", include_str!("../locales/tr.toml")),
+    ("ar", include_str!("../locales/ar.toml")),
+    ("hi", include_str!("../locales/hi.toml")),
+    ("th", include_str!("../locales/th.toml")),
+    ("vi", include_str!("../locales/vi.toml")),
+    ("id", include_str!("../locales/id.toml")),
+    ("ms", include_str!("../locales/ms.toml")),
+];
+
+/// Messages of one language, completed from its fallbacks
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    lang: String,
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Catalog for a language code such as `ja`, `pt_BR` or `zh-TW`; unknown
+    /// languages get the English messages
+    pub fn new(lang: &str) -> Self {
+        let mut messages = HashMap::new();
+        // Apply the chain from English up so the most specific language wins
+        for code in fallback_chain(lang).iter().rev() {
+            if let Some(locale) = load(code) {
+                messages.extend(locale);
+            }
+        }
+        Self {
+            lang: lang.to_string(),
+            messages,
+        }
+    }
+
+    /// Language the catalog was requested for
+    pub fn lang(&self) -> &str {
+        &self.lang
+    }
+
+    /// Message for `key`, or the key itself if no language defines it
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.messages.get(key).map_or(key, String::as_str)
+    }
+
+    /// Message for `key` in `section`, or the key itself if no language defines it
+    pub fn get_in<'a>(&'a self, section: &str, key: &'a str) -> &'a str {
+        self.messages
+            .get(&format!("{section}.{key}"))
+            .map_or(key, String::as_str)
+    }
+
+    /// Message for `key` with its `{name}` placeholders filled in
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut message = self.get(key).to_string();
+        for (name, value) in args {
+            message = message.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        message
+    }
+}
+
+/// Languages consulted for `lang`, most specific first and English last
+pub fn fallback_chain(lang: &str) -> Vec<String> {
+    fn push(chain: &mut Vec<String>, code: &str) {
+        if !chain.iter().any(|known| known == code) {
+            chain.push(code.to_string());
+        }
+    }
+
+    let mut chain = Vec::new();
+    let code = lang.replace('_', "-");
+    push(&mut chain, &code);
+    if let Some((primary, _)) = code.split_once('-') {
+        push(&mut chain, primary);
+    }
+    // Follow `fallback` entries; a language already in the chain ends it
+    let mut index = 0;
+    while index < chain.len() {
+        if let Some(next) = source(&chain[index]).and_then(declared_fallback) {
+            push(&mut chain, &next);
+        }
+        index += 1;
+    }
+    push(&mut chain, BASE_LANGUAGE);
+    chain.retain(|code| source(code).is_some());
+    chain
+}
+
+/// Keys of the English catalog that `lang` takes from its fallbacks instead
+/// of translating them
+pub fn missing_keys(lang: &str) -> Vec<String> {
+    let own = load(lang).unwrap_or_default();
+    let mut missing: Vec<String> = load(BASE_LANGUAGE)
+        .unwrap_or_default()
+        .into_keys()
+        .filter(|key| !own.contains_key(key))
+        .collect();
+    missing.sort();
+    missing
+}
+
+fn source(lang: &str) -> Option<&'static str> {
+    LOCALES
+        .iter()
+        .find(|(code, _)| *code == lang)
+        .map(|(_, source)| *source)
+}
+
+fn declared_fallback(source: &str) -> Option<String> {
+    let table: toml::Table = source.parse().ok()?;
+    table.get("fallback")?.as_str().map(str::to_string)
+}
+
+/// Flattened messages of one locale file, without its `fallback` entry
+fn load(lang: &str) -> Option<HashMap<String, String>> {
+    let table: toml::Table = match source(lang)?.parse() {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("Locale {lang} is not valid TOML: {e}");
+            return None;
+        }
+    };
+    let mut messages = HashMap::new();
+    flatten("", &table, &mut messages);
+    messages.remove("fallback");
+    Some(messages)
+}
+
+fn flatten(prefix: &str, table: &toml::Table, messages: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::String(text) => {
+                messages.insert(path, text.clone());
+            }
+            toml::Value::Table(nested) => flatten(&path, nested, messages),
+            _ => {}
+        }
+    }
+}
@@ -1,8 +1,17 @@
-use crate::ai_analyzer::{analyze_with_ai, AiAnalysisResponse, ConversationData};
+use crate::ai_analyzer::{analyze_with_ai, AiAnalysisResponse, ApiStats, ConversationData};
+use crate::commands::CommandStats;
 use crate::conversation_analyzer::Topic;
+use crate::file_ledger::FileChange;
+use crate::git::Commit;
+use crate::i18n::Catalog;
+use crate::provider::LlmProvider;
+use crate::session::{session_topics, SessionSummary};
+use crate::templates::Templates;
 use crate::token_tracker::TokenTracker;
+use crate::work_time::ActiveTime;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Serialize)]
 pub struct IntelligentReport {
@@ -10,16 +19,39 @@ pub struct IntelligentReport {
     pub projects: Vec<IntelligentProjectSummary>,
     pub overall_insights: String,
     pub token_tracker: Option<TokenTracker>,
+    pub api_stats: Option<ApiStats>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct IntelligentProjectSummary {
+    /// Project path as shown in the report
+    pub project: String,
     pub title: String,
     pub purpose: String,
     pub work_summary: WorkSummary,
     pub key_achievements: Vec<Achievement>,
     pub blockers: Vec<Blocker>,
     pub next_steps: Vec<String>,
+    pub sessions: Vec<SessionSummary>,
+    pub commits: Vec<Commit>,
+    pub files: Vec<FileChange>,
+    pub commands: CommandStats,
+    pub active_time: Option<ActiveTime>,
+}
+
+/// Everything collected about one project while reading its logs
+#[derive(Debug, Clone, Default)]
+pub struct ProjectActivity {
+    pub topics: Vec<Topic>,
+    pub sessions: Vec<SessionSummary>,
+    /// Commits in the project's repository during the period, oldest first
+    pub commits: Vec<Commit>,
+    /// Files created, edited or read, most changed first
+    pub files: Vec<FileChange>,
+    /// Build, test, lint, format and deploy commands and their outcomes
+    pub commands: CommandStats,
+    /// Working time from message timestamps, split into blocks at idle gaps
+    pub active_time: Option<ActiveTime>,
 }
 
 #[derive(Debug, Serialize)]
@@ -70,79 +102,128 @@ pub enum ResolutionStatus {
     Blocked,
 }
 
+/// Summarize every project, with the AI provider when `use_ai` is set.
+///
+/// With `per_session` each session is additionally analyzed on its own and its
+/// AI summary replaces the rule-based one built from the topic titles.
 #[allow(clippy::too_many_arguments)]
 pub async fn create_intelligent_summary(
-    projects: HashMap<String, Vec<Topic>>,
+    mut projects: HashMap<String, ProjectActivity>,
     date_str: String,
     use_ai: bool,
-    api_key: Option<&str>,
+    provider: Option<Arc<dyn LlmProvider>>,
+    templates: &Templates,
     lang: &str,
-    model: &str,
     quiet: bool,
     parallel: usize,
+    per_session: bool,
 ) -> Result<IntelligentReport, Box<dyn std::error::Error>> {
     let mut project_summaries = Vec::new();
     let mut token_tracker_option = None;
+    let mut api_stats_option = None;
+    let ai_provider = provider.filter(|_| use_ai);
+    let catalog = Catalog::new(lang);
 
-    if use_ai && api_key.is_some() {
+    if let Some(provider) = ai_provider {
         // Convert topics to conversation data for AI analysis
-        let conversations: Vec<ConversationData> = projects
+        let mut conversations: Vec<ConversationData> = projects
             .iter()
-            .map(|(name, topics)| ConversationData {
-                project_name: name.clone(),
-                user_messages: extract_user_messages(topics),
-                assistant_actions: extract_assistant_actions(topics),
-                timestamps: extract_timestamps(topics),
+            .map(|(name, project)| {
+                let commits = project.commits.iter().map(Commit::oneline).collect();
+                conversation_data(name.clone(), &project.topics, commits)
             })
             .collect();
 
-        let (ai_results, token_tracker) = analyze_with_ai(
-            api_key.unwrap(),
-            conversations,
-            lang,
-            model,
-            quiet,
-            parallel,
-        )
-        .await?;
+        // Sessions go through the same work queue under their own key
+        if per_session {
+            for (name, project) in &projects {
+                for session in &project.sessions {
+                    let topics: Vec<Topic> = session_topics(&project.topics, &session.session_id)
+                        .into_iter()
+                        .cloned()
+                        .collect();
+                    if !topics.is_empty() {
+                        let commits = topics.iter().flat_map(|t| t.commits.clone()).collect();
+                        conversations.push(conversation_data(
+                            session_key(name, &session.session_id),
+                            &topics,
+                            commits,
+                        ));
+                    }
+                }
+            }
+        }
+
+        let (mut ai_results, token_tracker, api_stats) =
+            analyze_with_ai(provider, templates, conversations, lang, quiet, parallel).await?;
         token_tracker_option = Some(token_tracker);
+        api_stats_option = Some(api_stats);
+
+        for (name, project) in projects.iter_mut() {
+            for session in &mut project.sessions {
+                if let Some(response) = ai_results.remove(&session_key(name, &session.session_id)) {
+                    session.summary = summarize_session_response(response);
+                }
+            }
+        }
 
         // Process successful AI results
         let mut processed_projects = std::collections::HashSet::new();
         for (project_name, ai_response) in ai_results {
-            project_summaries.push(convert_ai_response_to_summary(ai_response));
+            let mut summary = convert_ai_response_to_summary(ai_response, &catalog);
+            summary.project = project_name.clone();
+            if let Some(project) = projects.get(&project_name) {
+                summary.sessions = project.sessions.clone();
+                summary.commits = project.commits.clone();
+                summary.files = project.files.clone();
+                summary.commands = project.commands.clone();
+                summary.active_time = project.active_time.clone();
+            }
+            project_summaries.push(summary);
             processed_projects.insert(project_name);
         }
 
         // Fallback for projects that failed AI analysis
-        for (project_name, topics) in &projects {
+        for (project_name, project) in &projects {
             if !processed_projects.contains(project_name) {
                 eprintln!(
                     "Using fallback analysis for project '{project_name}' after AI analysis failed"
                 );
-                let summary = analyze_project_intelligently(project_name, topics);
+                let summary = analyze_project_intelligently(project_name, project, &catalog);
                 project_summaries.push(summary);
             }
         }
     } else {
         // Fallback to rule-based analysis with smarter heuristics
-        for (project_name, topics) in &projects {
-            let summary = analyze_project_intelligently(project_name, topics);
+        for (project_name, project) in &projects {
+            let summary = analyze_project_intelligently(project_name, project, &catalog);
             project_summaries.push(summary);
         }
     }
 
-    let failed_count = if use_ai && api_key.is_some() {
-        projects.len().saturating_sub(project_summaries.len())
-    } else {
-        0
-    };
-    let mut overall_insights = generate_overall_insights(&project_summaries);
-
-    if failed_count > 0 {
-        overall_insights.push_str(&format!(
-            " Note: {failed_count} project(s) failed to analyze."
-        ));
+    // Per-session analyses are queued under their own key; a failed one leaves
+    // its project's analysis intact
+    let (failed_projects, failed_sessions) =
+        api_stats_option
+            .as_ref()
+            .map_or((0, 0), |stats: &ApiStats| {
+                let projects = stats
+                    .failures
+                    .iter()
+                    .filter(|failure| projects.contains_key(&failure.project))
+                    .count();
+                (projects, stats.failures.len() - projects)
+            });
+    let mut overall_insights = generate_overall_insights(&project_summaries, &catalog);
+
+    for (key, count) in [
+        ("api.projects_failed", failed_projects),
+        ("api.sessions_failed", failed_sessions),
+    ] {
+        if count > 0 {
+            overall_insights.push(' ');
+            overall_insights.push_str(&catalog.format(key, &[("count", &count)]));
+        }
     }
 
     Ok(IntelligentReport {
@@ -150,32 +231,42 @@ pub async fn create_intelligent_summary(
         projects: project_summaries,
         overall_insights,
         token_tracker: token_tracker_option,
+        api_stats: api_stats_option,
     })
 }
 
 fn analyze_project_intelligently(
     project_name: &str,
-    topics: &[Topic],
+    project: &ProjectActivity,
+    catalog: &Catalog,
 ) -> IntelligentProjectSummary {
+    let topics = &project.topics;
     // Use statistical analysis instead of keyword matching
     let activity_patterns = detect_activity_patterns(topics);
-    let work_focus = determine_primary_focus(&activity_patterns);
+    let work_focus = determine_primary_focus(&activity_patterns, catalog);
 
     IntelligentProjectSummary {
-        title: generate_intelligent_title(project_name, topics),
-        purpose: infer_project_purpose(topics),
+        project: project_name.to_string(),
+        title: generate_intelligent_title(project_name, topics, catalog),
+        purpose: infer_project_purpose(topics, catalog),
         work_summary: WorkSummary {
             primary_focus: work_focus,
-            activities: group_activities_intelligently(topics),
+            activities: group_activities_intelligently(topics, catalog),
             time_distribution: calculate_time_distribution(topics),
         },
-        key_achievements: extract_meaningful_achievements(topics),
-        blockers: identify_blockers(topics),
-        next_steps: suggest_next_steps(topics),
+        key_achievements: extract_meaningful_achievements(topics, catalog),
+        blockers: identify_blockers(topics, catalog),
+        next_steps: suggest_next_steps(topics, catalog),
+        sessions: project.sessions.clone(),
+        commits: project.commits.clone(),
+        files: project.files.clone(),
+        commands: project.commands.clone(),
+        active_time: project.active_time.clone(),
     }
 }
 
-fn detect_activity_patterns(topics: &[Topic]) -> HashMap<String, f32> {
+/// Share of the topics per work pattern, keyed by its catalog key in `[analysis]`
+fn detect_activity_patterns(topics: &[Topic]) -> HashMap<&'static str, f32> {
     let mut patterns = HashMap::new();
 
     // Analyze action patterns rather than keywords
@@ -193,37 +284,41 @@ fn detect_activity_patterns(topics: &[Topic]) -> HashMap<String, f32> {
     patterns
 }
 
-fn classify_by_behavior(_intent: &str, steps: &[crate::conversation_analyzer::WorkStep]) -> String {
+fn classify_by_behavior(
+    _intent: &str,
+    steps: &[crate::conversation_analyzer::WorkStep],
+) -> &'static str {
     // Look at the sequence of actions to understand what was done
     let action_sequence: Vec<&str> = steps.iter().map(|s| s.description.as_str()).collect();
 
     // Pattern matching on action sequences
     if action_sequence.contains(&"Create file") && action_sequence.contains(&"Write code") {
-        "Feature Implementation".to_string()
+        "focus_feature"
     } else if action_sequence.contains(&"Read file") && action_sequence.contains(&"Fix error") {
-        "Debugging & Fixes".to_string()
+        "focus_debugging"
     } else if action_sequence.contains(&"Run tests") || action_sequence.contains(&"Verify") {
-        "Quality Assurance".to_string()
+        "focus_quality"
     } else if action_sequence.contains(&"Update config")
         || action_sequence.contains(&"Change settings")
     {
-        "Configuration Management".to_string()
+        "focus_configuration"
     } else {
-        "General Development".to_string()
+        "focus_general"
     }
 }
 
-fn generate_intelligent_title(project_path: &str, topics: &[Topic]) -> String {
+fn generate_intelligent_title(project_path: &str, topics: &[Topic], catalog: &Catalog) -> String {
     // Extract meaningful project name from path and context
     let path_parts: Vec<&str> = project_path.split('/').collect();
     let last_meaningful_part = path_parts
         .iter()
         .rev()
         .find(|&&part| !part.is_empty() && part != "home" && part != "projects")
-        .unwrap_or(&"Project");
+        .copied()
+        .unwrap_or(catalog.get("analysis.project"));
 
     // Look for patterns in topics to enhance the title
-    let main_theme = detect_main_theme(topics);
+    let main_theme = catalog.get_in("analysis", detect_main_theme(topics));
 
     format!(
         "{} - {}",
@@ -232,32 +327,27 @@ fn generate_intelligent_title(project_path: &str, topics: &[Topic]) -> String {
     )
 }
 
-fn detect_main_theme(topics: &[Topic]) -> String {
-    // Analyze all topics to find the overarching theme
-    if topics.is_empty() {
-        return "Development Work".to_string();
-    }
-
+/// Catalog key in `[analysis]` of the overarching theme of the topics
+fn detect_main_theme(topics: &[Topic]) -> &'static str {
     // Count different types of work
     let mut theme_counts: HashMap<&str, usize> = HashMap::new();
 
     for topic in topics {
         if topic.user_intent.contains("report") || topic.user_intent.contains("analyze") {
-            *theme_counts.entry("Analytics & Reporting").or_insert(0) += 1;
+            *theme_counts.entry("theme_analytics").or_insert(0) += 1;
         } else if topic.user_intent.contains("display") || topic.user_intent.contains("UI") {
-            *theme_counts.entry("UI Development").or_insert(0) += 1;
+            *theme_counts.entry("theme_ui").or_insert(0) += 1;
         } else if topic.user_intent.contains("fix") || topic.user_intent.contains("error") {
-            *theme_counts.entry("Bug Fixes & Improvements").or_insert(0) += 1;
+            *theme_counts.entry("theme_fixes").or_insert(0) += 1;
         } else {
-            *theme_counts.entry("Feature Development").or_insert(0) += 1;
+            *theme_counts.entry("theme_features").or_insert(0) += 1;
         }
     }
 
     theme_counts
         .into_iter()
         .max_by_key(|(_, count)| *count)
-        .map(|(theme, _)| theme.to_string())
-        .unwrap_or_else(|| "Development Work".to_string())
+        .map_or("theme_default", |(theme, _)| theme)
 }
 
 fn format_project_name(name: &str) -> String {
@@ -279,9 +369,9 @@ fn format_project_name(name: &str) -> String {
         .join(" ")
 }
 
-fn group_activities_intelligently(topics: &[Topic]) -> Vec<Activity> {
+fn group_activities_intelligently(topics: &[Topic], catalog: &Catalog) -> Vec<Activity> {
     let mut activities = Vec::new();
-    let mut activity_groups: HashMap<String, Vec<&Topic>> = HashMap::new();
+    let mut activity_groups: HashMap<&str, Vec<&Topic>> = HashMap::new();
 
     // Group by semantic similarity, not keywords
     for topic in topics {
@@ -290,11 +380,12 @@ fn group_activities_intelligently(topics: &[Topic]) -> Vec<Activity> {
     }
 
     // Convert groups to activities
-    for (group_name, group_topics) in activity_groups {
+    for (group, group_topics) in activity_groups {
         if !group_topics.is_empty() {
+            let category = catalog.get_in("analysis", group);
             activities.push(Activity {
-                description: create_activity_description(&group_name, group_topics.len()),
-                category: group_name.clone(),
+                description: create_activity_description(category, group_topics.len(), catalog),
+                category: category.to_string(),
                 impact_level: assess_impact_level(&group_topics),
             });
         }
@@ -314,32 +405,49 @@ fn group_activities_intelligently(topics: &[Topic]) -> Vec<Activity> {
     activities
 }
 
-fn determine_semantic_group(topic: &Topic) -> String {
+/// Catalog key in `[analysis]` of the kind of work done in the topic
+fn determine_semantic_group(topic: &Topic) -> &'static str {
     // Group by the actual work done, not keywords
     let intent_lower = topic.user_intent.to_lowercase();
-    let has_code_changes = topic.steps.iter().any(|s| {
-        s.description.contains("Write")
-            || s.description.contains("Edit")
-            || s.description.contains("Create")
-    });
-    let has_analysis = topic.steps.iter().any(|s| {
-        s.description.contains("Read")
-            || s.description.contains("Analyze")
-            || s.description.contains("Search")
-    });
+    // Tool calls are matched by name; step descriptions are in the report language
+    let tools = || topic.steps.iter().flat_map(|s| &s.tools);
+    let described = |patterns: &[&str]| {
+        topic
+            .steps
+            .iter()
+            .any(|s| patterns.iter().any(|p| s.description.contains(p)))
+    };
+    let is_edit = |name: &str| matches!(name, "Write" | "Edit" | "MultiEdit");
+    let has_code_changes =
+        tools().any(|t| is_edit(&t.name)) || described(&["Write", "Edit", "Create"]);
+    let has_analysis = tools().any(|t| matches!(t.name.as_str(), "Read" | "Grep"))
+        || described(&["Read", "Analyze", "Search"]);
+    let has_config_changes = intent_lower.contains("config")
+        || intent_lower.contains("setting")
+        || topic.user_intent.contains("設定")
+        || tools().any(|t| {
+            is_edit(&t.name)
+                && t.file.as_deref().is_some_and(|file| {
+                    [".toml", ".json", ".yml", ".yaml"]
+                        .iter()
+                        .any(|ext| file.ends_with(ext))
+                })
+        });
 
     if has_code_changes && intent_lower.contains("implement") {
-        "New Feature Implementation".to_string()
+        "group_feature"
     } else if has_code_changes && (intent_lower.contains("fix") || intent_lower.contains("error")) {
-        "Bug Fixes and Error Resolution".to_string()
+        "group_bug_fixes"
+    } else if has_config_changes {
+        "group_configuration"
     } else if has_analysis && !has_code_changes {
-        "Code Analysis and Research".to_string()
+        "group_analysis"
     } else if intent_lower.contains("test") {
-        "Testing and Validation".to_string()
+        "group_testing"
     } else if intent_lower.contains("document") {
-        "Documentation Updates".to_string()
+        "group_documentation"
     } else {
-        "Development Tasks".to_string()
+        "group_development"
     }
 }
 
@@ -366,22 +474,34 @@ fn assess_impact_level(topics: &[&Topic]) -> ImpactLevel {
     }
 }
 
-fn create_activity_description(category: &str, count: usize) -> String {
+fn create_activity_description(category: &str, count: usize, catalog: &Catalog) -> String {
     if count == 1 {
         category.to_string()
     } else {
-        format!("{category} ({count} tasks)")
+        catalog.format(
+            "analysis.tasks",
+            &[("category", &category), ("count", &count)],
+        )
     }
 }
 
 fn calculate_time_distribution(topics: &[Topic]) -> HashMap<String, f32> {
     let mut distribution = HashMap::new();
     let total_steps: usize = topics.iter().map(|t| t.steps.len()).sum();
+    if total_steps == 0 {
+        return distribution;
+    }
 
+    // Bucket the semantic groups into the effort categories used by the report
     for topic in topics {
-        let category = determine_semantic_group(topic);
+        let bucket = match determine_semantic_group(topic) {
+            "group_feature" | "group_development" => "Development",
+            "group_bug_fixes" => "Debugging",
+            "group_configuration" => "Configuration",
+            _ => "Other",
+        };
         let steps = topic.steps.len() as f32;
-        *distribution.entry(category).or_insert(0.0) += steps;
+        *distribution.entry(bucket.to_string()).or_insert(0.0) += steps;
     }
 
     // Convert to percentages
@@ -392,12 +512,12 @@ fn calculate_time_distribution(topics: &[Topic]) -> HashMap<String, f32> {
     distribution
 }
 
-fn extract_meaningful_achievements(topics: &[Topic]) -> Vec<Achievement> {
+fn extract_meaningful_achievements(topics: &[Topic], catalog: &Catalog) -> Vec<Achievement> {
     let mut achievements = Vec::new();
 
     for topic in topics {
         if let crate::conversation_analyzer::TopicOutcome::Completed(_) = &topic.outcome {
-            if let Some(achievement) = create_achievement_from_topic(topic) {
+            if let Some(achievement) = create_achievement_from_topic(topic, catalog) {
                 achievements.push(achievement);
             }
         }
@@ -408,23 +528,35 @@ fn extract_meaningful_achievements(topics: &[Topic]) -> Vec<Achievement> {
     achievements.truncate(5); // Keep top 5
 
     achievements
+        .into_iter()
+        .map(|(_, achievement)| achievement)
+        .collect()
 }
 
-fn create_achievement_from_topic(topic: &Topic) -> Option<Achievement> {
+/// The achievement with the rank of its impact, higher is bigger
+fn create_achievement_from_topic(topic: &Topic, catalog: &Catalog) -> Option<(u8, Achievement)> {
     let intent = &topic.user_intent;
     let has_significant_changes = topic.steps.len() > 3;
 
     if has_significant_changes {
-        Some(Achievement {
-            description: summarize_achievement(intent, &topic.steps),
-            impact: assess_achievement_impact(topic),
-        })
+        let (rank, impact) = assess_achievement_impact(topic);
+        Some((
+            rank,
+            Achievement {
+                description: summarize_achievement(intent, &topic.steps, catalog),
+                impact: catalog.get_in("analysis", impact).to_string(),
+            },
+        ))
     } else {
         None
     }
 }
 
-fn summarize_achievement(intent: &str, steps: &[crate::conversation_analyzer::WorkStep]) -> String {
+fn summarize_achievement(
+    intent: &str,
+    steps: &[crate::conversation_analyzer::WorkStep],
+    catalog: &Catalog,
+) -> String {
     // Create a meaningful summary based on intent and actions
     let key_actions: Vec<&str> = steps
         .iter()
@@ -440,10 +572,12 @@ fn summarize_achievement(intent: &str, steps: &[crate::conversation_analyzer::Wo
     if key_actions.is_empty() {
         simplify_intent(intent)
     } else {
-        format!(
-            "{} through {}",
-            simplify_intent(intent),
-            summarize_actions(&key_actions)
+        catalog.format(
+            "analysis.achieved_through",
+            &[
+                ("intent", &simplify_intent(intent)),
+                ("actions", &summarize_actions(&key_actions, catalog)),
+            ],
         )
     }
 }
@@ -457,30 +591,29 @@ fn simplify_intent(intent: &str) -> String {
         .join(" ")
 }
 
-fn summarize_actions(actions: &[&str]) -> String {
+/// The single distinct action, or how many there were; `actions` is not empty
+fn summarize_actions(actions: &[&str], catalog: &Catalog) -> String {
     let unique_actions: std::collections::HashSet<_> = actions.iter().cloned().collect();
     if unique_actions.len() == 1 {
-        actions
-            .first()
-            .map(|action| action.to_lowercase())
-            .unwrap_or_else(|| "unknown action".to_string())
+        actions[0].to_lowercase()
     } else {
-        format!("{} actions", unique_actions.len())
+        catalog.format("analysis.actions", &[("count", &unique_actions.len())])
     }
 }
 
-fn assess_achievement_impact(topic: &Topic) -> String {
+/// Rank and catalog key in `[analysis]` of the topic's impact
+fn assess_achievement_impact(topic: &Topic) -> (u8, &'static str) {
     match topic.steps.len() {
-        0..=2 => "Minor improvement".to_string(),
-        3..=5 => "Moderate enhancement".to_string(),
-        _ => "Significant advancement".to_string(),
+        0..=2 => (0, "impact_minor"),
+        3..=5 => (1, "impact_moderate"),
+        _ => (2, "impact_significant"),
     }
 }
 
-fn deduplicate_achievements(achievements: &mut Vec<Achievement>) {
-    // Remove similar achievements
-    achievements.sort_by(|a, b| b.impact.cmp(&a.impact));
-    achievements.dedup_by(|a, b| similarity_score(&a.description, &b.description) > 0.7);
+fn deduplicate_achievements(achievements: &mut Vec<(u8, Achievement)>) {
+    // Remove similar achievements, keeping the biggest
+    achievements.sort_by_key(|(rank, _)| std::cmp::Reverse(*rank));
+    achievements.dedup_by(|(_, a), (_, b)| similarity_score(&a.description, &b.description) > 0.7);
 }
 
 fn similarity_score(a: &str, b: &str) -> f32 {
@@ -497,21 +630,21 @@ fn similarity_score(a: &str, b: &str) -> f32 {
     }
 }
 
-fn identify_blockers(topics: &[Topic]) -> Vec<Blocker> {
+fn identify_blockers(topics: &[Topic], catalog: &Catalog) -> Vec<Blocker> {
     let mut blockers = Vec::new();
 
     for topic in topics {
         match &topic.outcome {
             crate::conversation_analyzer::TopicOutcome::Failed(msg) => {
                 blockers.push(Blocker {
-                    issue: create_blocker_description(topic, msg),
+                    issue: create_blocker_description(topic, msg, catalog),
                     severity: assess_severity(topic),
                     resolution_status: ResolutionStatus::Blocked,
                 });
             }
             crate::conversation_analyzer::TopicOutcome::PartiallyCompleted(msg) => {
                 blockers.push(Blocker {
-                    issue: create_blocker_description(topic, msg),
+                    issue: create_blocker_description(topic, msg, catalog),
                     severity: Severity::Major,
                     resolution_status: ResolutionStatus::InProgress,
                 });
@@ -523,11 +656,16 @@ fn identify_blockers(topics: &[Topic]) -> Vec<Blocker> {
     blockers
 }
 
-fn create_blocker_description(topic: &Topic, error_msg: &str) -> String {
-    format!(
-        "While {}: {}",
-        simplify_intent(&topic.user_intent).to_lowercase(),
-        simplify_error_message(error_msg)
+fn create_blocker_description(topic: &Topic, error_msg: &str, catalog: &Catalog) -> String {
+    catalog.format(
+        "analysis.blocker",
+        &[
+            (
+                "intent",
+                &simplify_intent(&topic.user_intent).to_lowercase(),
+            ),
+            ("error", &simplify_error_message(error_msg)),
+        ],
     )
 }
 
@@ -554,7 +692,7 @@ fn assess_severity(topic: &Topic) -> Severity {
     }
 }
 
-fn suggest_next_steps(topics: &[Topic]) -> Vec<String> {
+fn suggest_next_steps(topics: &[Topic], catalog: &Catalog) -> Vec<String> {
     let mut suggestions = Vec::new();
 
     // Based on failures and partial completions
@@ -564,7 +702,7 @@ fn suggest_next_steps(topics: &[Topic]) -> Vec<String> {
             crate::conversation_analyzer::TopicOutcome::Failed(_)
                 | crate::conversation_analyzer::TopicOutcome::PartiallyCompleted(_)
         ) {
-            if let Some(suggestion) = generate_suggestion_for_topic(topic) {
+            if let Some(suggestion) = generate_suggestion_for_topic(topic, catalog) {
                 suggestions.push(suggestion);
             }
         }
@@ -574,23 +712,19 @@ fn suggest_next_steps(topics: &[Topic]) -> Vec<String> {
     suggestions
 }
 
-fn generate_suggestion_for_topic(topic: &Topic) -> Option<String> {
-    match &topic.outcome {
-        crate::conversation_analyzer::TopicOutcome::Failed(_) => Some(format!(
-            "Retry {}",
-            simplify_intent(&topic.user_intent).to_lowercase()
-        )),
-        crate::conversation_analyzer::TopicOutcome::PartiallyCompleted(_) => Some(format!(
-            "Complete remaining tasks for {}",
-            simplify_intent(&topic.user_intent).to_lowercase()
-        )),
-        _ => None,
-    }
+fn generate_suggestion_for_topic(topic: &Topic, catalog: &Catalog) -> Option<String> {
+    let key = match &topic.outcome {
+        crate::conversation_analyzer::TopicOutcome::Failed(_) => "analysis.retry",
+        crate::conversation_analyzer::TopicOutcome::PartiallyCompleted(_) => "analysis.complete",
+        _ => return None,
+    };
+    let intent = simplify_intent(&topic.user_intent).to_lowercase();
+    Some(catalog.format(key, &[("intent", &intent)]))
 }
 
-fn infer_project_purpose(topics: &[Topic]) -> String {
+fn infer_project_purpose(topics: &[Topic], catalog: &Catalog) -> String {
     if topics.is_empty() {
-        return "Project development and maintenance".to_string();
+        return catalog.get("analysis.purpose_default").to_string();
     }
 
     // Look at the first few topics to understand the overall goal
@@ -601,53 +735,91 @@ fn infer_project_purpose(topics: &[Topic]) -> String {
         .collect();
 
     // Synthesize a purpose from the intents
-    synthesize_purpose(&initial_intents)
+    catalog
+        .get_in("analysis", synthesize_purpose(&initial_intents))
+        .to_string()
 }
 
-fn synthesize_purpose(intents: &[&str]) -> String {
+/// Catalog key in `[analysis]` of the purpose behind the intents
+fn synthesize_purpose(intents: &[&str]) -> &'static str {
     // This would be more sophisticated in production
     if intents
         .iter()
         .any(|i| i.contains("report") || i.contains("analyze"))
     {
-        "Building analytics and reporting capabilities".to_string()
+        "purpose_analytics"
     } else if intents
         .iter()
         .any(|i| i.contains("UI") || i.contains("display"))
     {
-        "Developing user interface components".to_string()
+        "purpose_ui"
     } else {
-        "Software development and improvement".to_string()
+        "purpose_software"
     }
 }
 
-fn determine_primary_focus(patterns: &HashMap<String, f32>) -> String {
-    patterns
+fn determine_primary_focus(patterns: &HashMap<&'static str, f32>, catalog: &Catalog) -> String {
+    let focus = patterns
         .iter()
         .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-        .map(|(k, _)| k.clone())
-        .unwrap_or_else(|| "General Development".to_string())
+        .map_or("focus_general", |(k, _)| k);
+    catalog.get_in("analysis", focus).to_string()
 }
 
-fn generate_overall_insights(summaries: &[IntelligentProjectSummary]) -> String {
+fn generate_overall_insights(summaries: &[IntelligentProjectSummary], catalog: &Catalog) -> String {
     // Analyze across all projects for patterns
     let total_achievements: usize = summaries.iter().map(|s| s.key_achievements.len()).sum();
 
     let total_blockers: usize = summaries.iter().map(|s| s.blockers.len()).sum();
 
+    let assessment = if total_blockers > total_achievements {
+        "analysis.insights_blocked"
+    } else {
+        "analysis.insights_progress"
+    };
     format!(
-        "Completed {} projects with {} key achievements and {} blockers to address. {}",
-        summaries.len(),
-        total_achievements,
-        total_blockers,
-        if total_blockers > total_achievements {
-            "Focus needed on resolving technical debt."
-        } else {
-            "Good progress with manageable technical challenges."
-        }
+        "{} {}",
+        catalog.format(
+            "analysis.insights",
+            &[
+                ("projects", &summaries.len()),
+                ("achievements", &total_achievements),
+                ("blockers", &total_blockers),
+            ],
+        ),
+        catalog.get(assessment)
     )
 }
 
+fn conversation_data(
+    project_name: String,
+    topics: &[Topic],
+    commits: Vec<String>,
+) -> ConversationData {
+    ConversationData {
+        project_name,
+        user_messages: extract_user_messages(topics),
+        assistant_actions: extract_assistant_actions(topics),
+        timestamps: extract_timestamps(topics),
+        summaries: extract_summaries(topics),
+        commits,
+    }
+}
+
+/// Key of a session in the AI work queue (also its cache key)
+fn session_key(project_name: &str, session_id: &str) -> String {
+    format!("{project_name} [session {session_id}]")
+}
+
+/// One line describing what a session achieved
+fn summarize_session_response(response: AiAnalysisResponse) -> String {
+    if response.achievements.is_empty() {
+        response.project_purpose
+    } else {
+        response.achievements.join("; ")
+    }
+}
+
 fn extract_user_messages(topics: &[Topic]) -> Vec<String> {
     topics.iter().map(|t| t.user_intent.clone()).collect()
 }
@@ -659,12 +831,26 @@ fn extract_assistant_actions(topics: &[Topic]) -> Vec<String> {
         .collect()
 }
 
+fn extract_summaries(topics: &[Topic]) -> Vec<String> {
+    let mut summaries: Vec<String> = Vec::new();
+    for summary in topics.iter().flat_map(|t| &t.summaries) {
+        if !summaries.contains(summary) {
+            summaries.push(summary.clone());
+        }
+    }
+    summaries
+}
+
 fn extract_timestamps(topics: &[Topic]) -> Vec<String> {
     topics.iter().map(|t| t.timestamp.clone()).collect()
 }
 
-fn convert_ai_response_to_summary(response: AiAnalysisResponse) -> IntelligentProjectSummary {
+fn convert_ai_response_to_summary(
+    response: AiAnalysisResponse,
+    catalog: &Catalog,
+) -> IntelligentProjectSummary {
     IntelligentProjectSummary {
+        project: String::new(),
         title: response.project_title,
         purpose: response.project_purpose,
         work_summary: WorkSummary {
@@ -672,7 +858,7 @@ fn convert_ai_response_to_summary(response: AiAnalysisResponse) -> IntelligentPr
                 .main_activities
                 .first()
                 .map(|a| a.category.clone())
-                .unwrap_or_else(|| "General Development".to_string()),
+                .unwrap_or_else(|| catalog.get("analysis.focus_general").to_string()),
             activities: response
                 .main_activities
                 .into_iter()
@@ -693,7 +879,7 @@ fn convert_ai_response_to_summary(response: AiAnalysisResponse) -> IntelligentPr
             .into_iter()
             .map(|a| Achievement {
                 description: a,
-                impact: "Completed successfully".to_string(),
+                impact: catalog.get("analysis.impact_completed").to_string(),
             })
             .collect(),
         blockers: response
@@ -706,5 +892,10 @@ fn convert_ai_response_to_summary(response: AiAnalysisResponse) -> IntelligentPr
             })
             .collect(),
         next_steps: vec![],
+        sessions: vec![],
+        commits: vec![],
+        files: vec![],
+        commands: CommandStats::default(),
+        active_time: None,
     }
 }
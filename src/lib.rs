@@ -1,14 +1,30 @@
 pub mod ai_analyzer;
-pub mod ai_analyzer_parallel;
 pub mod cache;
+pub mod chunker;
 pub mod cli;
+pub mod commands;
 pub mod config;
 pub mod conversation_analyzer;
+pub mod conversation_tree;
+pub mod date_filter;
 pub mod error;
+pub mod file_ledger;
+pub mod git;
+pub mod html_report;
+pub mod i18n;
 pub mod intelligent_summary;
+pub mod log_index;
+pub mod log_reader;
 pub mod logger;
 pub mod parser;
+pub mod project_filter;
+pub mod project_path;
+pub mod provider;
+pub mod session;
 pub mod smart_analyzer;
 pub mod templates;
+pub mod timeline;
+pub mod timesheet;
 pub mod token_tracker;
 pub mod work_report_v2;
+pub mod work_time;
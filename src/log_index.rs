@@ -0,0 +1,448 @@
+//! Persistent index of parsed log files
+//!
+//! Old Claude Code logs never change and active ones only grow, so parsing
+//! every file on every run is wasted work. The index keeps the records of each
+//! file in the cache directory, keyed by path, size and modification time:
+//! unchanged files are loaded from the index (or skipped when their timestamps
+//! lie outside the date range), appended files are read from where the last
+//! run stopped, and only new or rewritten files are parsed in full.
+//!
+//! A damaged manifest or data file is not an error: the affected logs are
+//! simply parsed again and the index is rewritten.
+//!
+//! The index may share its directory with other files (`--index-dir`), so it
+//! only ever deletes its manifest and its own `idx-*.bin` data files.
+//!
+//! The index holds parsed records, not analysis results: topics, sessions and
+//! metrics are still computed from the records on every run.
+
+use crate::commands;
+use crate::date_filter::DateFilter;
+use crate::log_reader::{map_file, read_records, FileScan};
+use crate::logger;
+use crate::parser::{LogEntry, SummaryEntry};
+use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+/// Bumped whenever the stored records or file names change, which rebuilds the index
+const INDEX_VERSION: u32 = 4;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Data files are `idx-<hash>.bin` (JSON inside), apart from the API cache's `<key>.json`
+const DATA_FILE_PREFIX: &str = "idx-";
+const DATA_FILE_EXTENSION: &str = ".bin";
+
+/// Bytes at the end of the indexed part that must be unchanged for an append
+const TAIL_BYTES: usize = 256;
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    files: HashMap<PathBuf, FileRecord>,
+}
+
+/// What the index knows about one log file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileRecord {
+    size: u64,
+    modified: SystemTime,
+    /// Length of the part of the log that is in the index
+    indexed_len: u64,
+    /// Hash of the last bytes of the indexed part, to detect rewritten files
+    tail_hash: u64,
+    /// Earliest and latest entry timestamp (RFC 3339)
+    first: Option<String>,
+    last: Option<String>,
+    has_summaries: bool,
+    /// Name of the data file in the index directory
+    data: String,
+}
+
+/// Contents of a data file: the records of one log file
+#[derive(Serialize, Deserialize)]
+struct StoredRecords {
+    path: PathBuf,
+    indexed_len: u64,
+    entries: Vec<LogEntry>,
+    summaries: Vec<SummaryEntry>,
+}
+
+/// How the files of the last [`LogIndex::scan_files`] call were read
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IndexStats {
+    /// Unchanged files loaded from the index
+    pub reused: usize,
+    /// Unchanged files outside the date range, not loaded at all
+    pub skipped: usize,
+    /// Files that grew and were read from the previous end
+    pub appended: usize,
+    /// New, rewritten or damaged files parsed in full
+    pub parsed: usize,
+}
+
+enum Source {
+    Reused,
+    Skipped,
+    Appended,
+    Parsed,
+}
+
+pub struct LogIndex {
+    dir: PathBuf,
+    files: HashMap<PathBuf, FileRecord>,
+    stats: IndexStats,
+}
+
+impl LogIndex {
+    /// Open the index in `dir`, starting empty if it is missing or damaged
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+
+        let files = match fs::read(dir.join(MANIFEST_FILE)) {
+            Ok(content) => match serde_json::from_slice::<Manifest>(&content) {
+                Ok(manifest) if manifest.version == INDEX_VERSION => manifest.files,
+                Ok(_) => HashMap::new(),
+                Err(e) => {
+                    logger::warn(&format!("Log index is damaged and will be rebuilt: {e}"));
+                    HashMap::new()
+                }
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            files,
+            stats: IndexStats::default(),
+        })
+    }
+
+    /// `index` below the API response cache directory
+    pub fn default_dir() -> PathBuf {
+        let cache_dir = if let Ok(home) = std::env::var("HOME") {
+            PathBuf::from(home).join(".cache").join("cc2report")
+        } else {
+            PathBuf::from(".cc2report-cache")
+        };
+        cache_dir.join("index")
+    }
+
+    /// Delete the index in `dir` so every log file is parsed again
+    ///
+    /// A directory without a manifest is not an index and is left alone;
+    /// otherwise only the manifest and the data files are removed, and the
+    /// directory itself only if nothing else is left in it.
+    pub fn clear(dir: &Path) -> io::Result<()> {
+        if !dir.join(MANIFEST_FILE).is_file() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(is_data_file)
+            {
+                fs::remove_file(&path)?;
+            }
+        }
+        fs::remove_file(dir.join(MANIFEST_FILE))?;
+        let _ = fs::remove_dir(dir);
+        Ok(())
+    }
+
+    /// Number of indexed log files
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn stats(&self) -> IndexStats {
+        self.stats
+    }
+
+    /// Like [`crate::log_reader::scan_files`], reading from the index where possible
+    pub fn scan_files(
+        &mut self,
+        paths: &[PathBuf],
+        date_filter: DateFilter,
+    ) -> Vec<io::Result<FileScan>> {
+        let results: Vec<_> = paths
+            .par_iter()
+            .map(|path| {
+                let key = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+                let result = self.load(path, &key, date_filter);
+                (key, result)
+            })
+            .collect();
+
+        self.stats = IndexStats::default();
+        results
+            .into_iter()
+            .map(|(key, result)| {
+                let (scan, record, source) = result?;
+                match source {
+                    Source::Reused => self.stats.reused += 1,
+                    Source::Skipped => self.stats.skipped += 1,
+                    Source::Appended => self.stats.appended += 1,
+                    Source::Parsed => self.stats.parsed += 1,
+                }
+                if let Some(record) = record {
+                    self.files.insert(key, record);
+                }
+                Ok(scan)
+            })
+            .collect()
+    }
+
+    /// Write the manifest, dropping the records of deleted log files and the
+    /// data files no record refers to (left by rewritten logs or older versions)
+    pub fn save(&mut self) -> io::Result<()> {
+        self.files.retain(|path, _| path.exists());
+
+        let manifest = Manifest {
+            version: INDEX_VERSION,
+            files: self.files.clone(),
+        };
+        write_atomically(
+            &self.dir.join(MANIFEST_FILE),
+            &serde_json::to_vec(&manifest)?,
+        )?;
+
+        let referenced: HashSet<&str> = self.files.values().map(|r| r.data.as_str()).collect();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if is_data_file(name) && !referenced.contains(name) {
+                let _ = fs::remove_file(&path);
+            }
+        }
+        Ok(())
+    }
+
+    fn load(
+        &self,
+        path: &Path,
+        key: &Path,
+        date_filter: DateFilter,
+    ) -> io::Result<(FileScan, Option<FileRecord>, Source)> {
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        let size = metadata.len();
+        let modified = metadata.modified()?;
+        let record = self.files.get(key);
+
+        if let Some(record) = record.filter(|r| r.size == size && r.modified == modified) {
+            if record.outside(date_filter) {
+                let scan = FileScan {
+                    skipped: true,
+                    ..FileScan::default()
+                };
+                return Ok((scan, None, Source::Skipped));
+            }
+            if let Some(stored) = self.read_stored(record, key) {
+                return Ok((stored.into_scan(date_filter), None, Source::Reused));
+            }
+        }
+
+        let mmap = if size > 0 {
+            Some(map_file(&file)?)
+        } else {
+            None
+        };
+        let data: &[u8] = mmap.as_deref().unwrap_or_default();
+
+        // Logs only grow: keep the indexed part if its end is still in place
+        let previous = record
+            .filter(|r| {
+                r.size < size && tail_hash(data, r.indexed_len as usize) == Some(r.tail_hash)
+            })
+            .and_then(|r| self.read_stored(r, key));
+        let source = if previous.is_some() {
+            Source::Appended
+        } else {
+            Source::Parsed
+        };
+        let mut stored = previous.unwrap_or_else(|| StoredRecords {
+            path: key.to_path_buf(),
+            indexed_len: 0,
+            entries: Vec::new(),
+            summaries: Vec::new(),
+        });
+
+        let mut scan = FileScan::default();
+        let indexed_len = read_records(
+            path,
+            data,
+            stored.indexed_len as usize,
+            DateFilter::all(),
+            &mut scan,
+        );
+        stored.indexed_len = indexed_len as u64;
+        stored.entries.extend(scan.entries.into_iter().map(compact));
+        stored.summaries.extend(scan.summaries);
+
+        let record = FileRecord::new(size, modified, data, &stored, data_file_name(key));
+        let record =
+            match write_atomically(&self.dir.join(&record.data), &serde_json::to_vec(&stored)?) {
+                Ok(()) => Some(record),
+                Err(e) => {
+                    logger::warn(&format!(
+                        "Could not update the log index for {}: {e}",
+                        path.display()
+                    ));
+                    None
+                }
+            };
+
+        let mut result = stored.into_scan(date_filter);
+        result.errors = scan.errors;
+        Ok((result, record, source))
+    }
+
+    /// Stored records of a file, or `None` if the data file is missing or damaged
+    fn read_stored(&self, record: &FileRecord, key: &Path) -> Option<StoredRecords> {
+        let content = fs::read(self.dir.join(&record.data)).ok()?;
+        serde_json::from_slice::<StoredRecords>(&content)
+            .ok()
+            .filter(|stored| stored.path == key && stored.indexed_len == record.indexed_len)
+    }
+}
+
+impl FileRecord {
+    fn new(
+        size: u64,
+        modified: SystemTime,
+        data: &[u8],
+        stored: &StoredRecords,
+        data_file: String,
+    ) -> Self {
+        let timestamps = || stored.entries.iter().filter_map(|e| e.timestamp.as_deref());
+        let by_time = |t: &&str| parse_timestamp(t);
+        Self {
+            size,
+            modified,
+            indexed_len: stored.indexed_len,
+            tail_hash: tail_hash(data, stored.indexed_len as usize).unwrap_or_default(),
+            first: timestamps().min_by_key(by_time).map(str::to_string),
+            last: timestamps().max_by_key(by_time).map(str::to_string),
+            has_summaries: !stored.summaries.is_empty(),
+            data: data_file,
+        }
+    }
+
+    /// True if none of the file's records can be in the date range
+    fn outside(&self, date_filter: DateFilter) -> bool {
+        if date_filter.is_all() || self.has_summaries {
+            // Summaries are linked by leafUuid regardless of their date
+            return false;
+        }
+
+        let parse = |t: &Option<String>| t.as_deref().and_then(parse_timestamp);
+        match (parse(&self.first), parse(&self.last)) {
+            (Some(first), Some(last)) => date_filter.is_after(first) || date_filter.is_before(last),
+            _ => true,
+        }
+    }
+}
+
+impl StoredRecords {
+    fn into_scan(self, date_filter: DateFilter) -> FileScan {
+        let in_range = |entry: &LogEntry| {
+            entry
+                .timestamp
+                .as_deref()
+                .and_then(parse_timestamp)
+                .is_some_and(|t| date_filter.contains(t))
+        };
+        FileScan {
+            entries: self.entries.into_iter().filter(in_range).collect(),
+            summaries: self.summaries,
+            ..FileScan::default()
+        }
+    }
+}
+
+/// Drop the parts of an entry the analysis never reads: tool output is
+/// usually the bulk of a log (whole files, command output). Only the test
+/// runners' summary lines of a command's output are kept.
+fn compact(mut entry: LogEntry) -> LogEntry {
+    entry.tool_use_result = entry
+        .tool_use_result
+        .as_ref()
+        .and_then(commands::compact_result);
+    if let Some(blocks) = entry
+        .message
+        .as_mut()
+        .and_then(|message| message.content.as_mut())
+        .and_then(|content| content.as_array_mut())
+    {
+        for block in blocks {
+            if block.get("type").and_then(|v| v.as_str()) == Some("tool_result") {
+                if let Some(block) = block.as_object_mut() {
+                    block.remove("content");
+                }
+            }
+        }
+    }
+    entry
+}
+
+fn tail_hash(data: &[u8], end: usize) -> Option<u64> {
+    let tail = data.get(end.saturating_sub(TAIL_BYTES)..end)?;
+    Some(fnv1a(tail))
+}
+
+fn data_file_name(key: &Path) -> String {
+    format!(
+        "{DATA_FILE_PREFIX}{:016x}{DATA_FILE_EXTENSION}",
+        fnv1a(key.to_string_lossy().as_bytes())
+    )
+}
+
+fn is_data_file(name: &str) -> bool {
+    name.starts_with(DATA_FILE_PREFIX) && name.ends_with(DATA_FILE_EXTENSION)
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` it is the same in every Rust release,
+/// so hashes written to disk stay valid after a toolchain upgrade
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Write through a temporary file so a crash never leaves half a file behind
+fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let temp = path.with_extension(format!(
+        "{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&temp, content)?;
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        e
+    })
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
@@ -0,0 +1,211 @@
+//! Fast, memory-bounded reading of Claude Code JSONL logs
+//!
+//! Files are memory-mapped and scanned in parallel. Every line is first read
+//! as a small header (type and timestamp only) so message content is skipped
+//! without allocating; only lines inside the date filter are materialized as
+//! [`LogEntry`] values. Files whose modification time or first/last timestamp
+//! lie outside the filter are skipped without reading the rest of the file.
+
+use crate::date_filter::DateFilter;
+use crate::parser::{LogEntry, LogRecord, SummaryEntry};
+use chrono::{DateTime, Utc};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Records of one log file that fall inside the date filter
+#[derive(Debug, Default)]
+pub struct FileScan {
+    pub entries: Vec<LogEntry>,
+    /// Summary records are kept regardless of the date: they are linked to
+    /// their conversation by `leafUuid` after all files are read
+    pub summaries: Vec<SummaryEntry>,
+    /// Lines that are not valid JSON, as "path:line: error"
+    pub errors: Vec<String>,
+    /// True when the pre-check skipped the file without reading its lines
+    pub skipped: bool,
+}
+
+/// The fields needed to decide whether a line is read in full
+#[derive(Deserialize)]
+struct LineHeader<'a> {
+    #[serde(rename = "type", borrow)]
+    entry_type: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    timestamp: Option<Cow<'a, str>>,
+}
+
+impl LineHeader<'_> {
+    fn is_summary(&self) -> bool {
+        self.entry_type.as_deref() == Some("summary")
+    }
+
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.timestamp.as_deref().and_then(parse_timestamp)
+    }
+}
+
+/// All `.jsonl` files directly inside `dir`, sorted by name
+pub fn jsonl_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Scan files in parallel; results are in the order of `paths`
+pub fn scan_files(paths: &[PathBuf], date_filter: DateFilter) -> Vec<io::Result<FileScan>> {
+    paths
+        .par_iter()
+        .map(|path| scan_file(path, date_filter))
+        .collect()
+}
+
+/// Read the in-range entries and all summary records of one log file
+pub fn scan_file(path: &Path, date_filter: DateFilter) -> io::Result<FileScan> {
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    if metadata.len() == 0 || modified_before_range(&metadata, date_filter) {
+        return Ok(FileScan {
+            skipped: metadata.len() > 0,
+            ..FileScan::default()
+        });
+    }
+
+    let mmap = map_file(&file)?;
+    let data: &[u8] = &mmap;
+
+    if timestamps_outside_range(data, date_filter) {
+        return Ok(FileScan {
+            skipped: true,
+            ..FileScan::default()
+        });
+    }
+
+    let mut scan = FileScan::default();
+    read_records(path, data, 0, date_filter, &mut scan);
+    Ok(scan)
+}
+
+/// Memory-map a log file for reading
+pub(crate) fn map_file(file: &File) -> io::Result<Mmap> {
+    // SAFETY: Claude Code only appends to its logs, and an append past the
+    // mapped length does not affect the mapping. A file truncated while it is
+    // read is not supported, as with any other reader of a live log.
+    unsafe { Mmap::map(file) }
+}
+
+/// Parse the lines of `data` from byte `start` on into `scan`
+///
+/// Returns the offset up to which the data was read completely. A last line
+/// without a newline that does not parse is still being written; it is left
+/// for the next read instead of being reported as an error.
+pub(crate) fn read_records(
+    path: &Path,
+    data: &[u8],
+    start: usize,
+    date_filter: DateFilter,
+    scan: &mut FileScan,
+) -> usize {
+    let mut number = data[..start].iter().filter(|&&byte| byte == b'\n').count();
+    let mut offset = start;
+    let mut consumed = start;
+
+    for line in data[start..].split(|&byte| byte == b'\n') {
+        number += 1;
+        let end = offset + line.len();
+        let terminated = end < data.len();
+        offset = end + 1;
+
+        if !is_blank(line) {
+            match read_line(line, date_filter) {
+                Ok(Some(LogRecord::Entry(entry))) => scan.entries.push(entry),
+                Ok(Some(LogRecord::Summary(summary))) => scan.summaries.push(summary),
+                Ok(None) => {}
+                Err(_) if !terminated => break,
+                Err(e) => scan
+                    .errors
+                    .push(format!("{}:{}: {e}", path.display(), number)),
+            }
+        }
+        consumed = if terminated { offset } else { end };
+    }
+
+    consumed
+}
+
+/// Parse one line if it is a summary or an entry inside the date range
+fn read_line(line: &[u8], date_filter: DateFilter) -> serde_json::Result<Option<LogRecord>> {
+    let header = serde_json::from_slice::<LineHeader>(line)?;
+    let wanted = header.is_summary()
+        || header
+            .timestamp()
+            .is_some_and(|timestamp| date_filter.contains(timestamp));
+    if !wanted {
+        return Ok(None);
+    }
+    serde_json::from_slice::<LogRecord>(line).map(Some)
+}
+
+/// Non-empty lines of a mapped file
+fn lines(data: &[u8]) -> impl DoubleEndedIterator<Item = &[u8]> {
+    data.split(|&byte| byte == b'\n')
+        .filter(|line| !is_blank(line))
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(u8::is_ascii_whitespace)
+}
+
+/// A file last written before the range starts cannot contain entries in it
+fn modified_before_range(metadata: &std::fs::Metadata, date_filter: DateFilter) -> bool {
+    metadata
+        .modified()
+        .map(|modified| date_filter.is_before(DateTime::<Utc>::from(modified)))
+        .unwrap_or(false)
+}
+
+/// Compare the first and last timestamps of an append-only log with the range
+fn timestamps_outside_range(data: &[u8], date_filter: DateFilter) -> bool {
+    if date_filter.to.is_some() {
+        // Summaries written at the start of a resumed session describe older
+        // conversations, so such a file is read even if it starts too late
+        let mut saw_summary = false;
+        let first = lines(data).find_map(|line| {
+            let header = serde_json::from_slice::<LineHeader>(line).ok()?;
+            saw_summary |= header.is_summary();
+            header.timestamp()
+        });
+        if !saw_summary && first.is_some_and(|first| date_filter.is_after(first)) {
+            return true;
+        }
+    }
+
+    if date_filter.from.is_some() {
+        let last = lines(data).rev().find_map(|line| {
+            serde_json::from_slice::<LineHeader>(line)
+                .ok()
+                .and_then(|header| header.timestamp())
+        });
+        if last.is_some_and(|last| date_filter.is_before(last)) {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
@@ -1,11 +1,17 @@
 use cc2report::{
     cache, cli,
     config::{Config, OutputFormat},
-    error::{ApiError, AppError, Result},
-    logger, smart_analyzer, templates,
+    date_filter::DateFilter,
+    error::{AppError, Result},
+    html_report,
+    log_index::LogIndex,
+    logger,
+    project_filter::ProjectFilter,
+    provider, templates,
+    timeline::GroupBy,
+    timesheet,
     work_report_v2::analyze_conversations_with_ai,
 };
-use chrono::{Datelike, NaiveDate};
 use std::path::PathBuf;
 
 #[tokio::main]
@@ -40,6 +46,12 @@ async fn run() -> Result<()> {
     // Parse date filters
     let date_filter = parse_date_filter(&matches)?;
 
+    if matches.get_flag("reindex") {
+        LogIndex::clear(&config.processing.index_dir)
+            .map_err(|e| AppError::Cache(format!("Failed to clear the log index: {e}")))?;
+        logger::info("Log index cleared; all log files will be parsed again.");
+    }
+
     // Run analysis
     run_analysis(config, date_filter, &matches).await
 }
@@ -79,75 +91,60 @@ fn handle_cache_info() -> Result<()> {
     Ok(())
 }
 
-fn parse_date_filter(
-    matches: &clap::ArgMatches,
-) -> Result<Option<(Option<NaiveDate>, Option<NaiveDate>)>> {
-    if let Some(date_str) = matches.get_one::<String>("date") {
-        let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-            .map_err(|e| AppError::Config(format!("Invalid date format: {e}")))?;
-        Ok(Some((Some(date), Some(date))))
-    } else if let (Some(from_str), Some(to_str)) = (
-        matches.get_one::<String>("from"),
-        matches.get_one::<String>("to"),
-    ) {
-        let from_date = NaiveDate::parse_from_str(from_str, "%Y-%m-%d")
-            .map_err(|e| AppError::Config(format!("Invalid from date: {e}")))?;
-        let to_date = NaiveDate::parse_from_str(to_str, "%Y-%m-%d")
-            .map_err(|e| AppError::Config(format!("Invalid to date: {e}")))?;
-        Ok(Some((Some(from_date), Some(to_date))))
-    } else if matches.get_flag("weekly") {
-        let today = chrono::Local::now().naive_local().date();
-        let weekday = today.weekday();
-        let days_since_monday = weekday.num_days_from_monday();
-        let monday = today - chrono::Duration::days(days_since_monday as i64);
-        let sunday = monday + chrono::Duration::days(6);
-        Ok(Some((Some(monday), Some(sunday))))
-    } else if matches.get_flag("monthly") {
-        let today = chrono::Local::now().naive_local().date();
-        let first_day = NaiveDate::from_ymd_opt(today.year(), today.month(), 1)
-            .ok_or_else(|| AppError::Config("Invalid date calculation".to_string()))?;
-        let last_day = if today.month() == 12 {
-            NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)
-                .ok_or_else(|| AppError::Config("Invalid date calculation".to_string()))?
-                - chrono::Duration::days(1)
-        } else {
-            NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)
-                .ok_or_else(|| AppError::Config("Invalid date calculation".to_string()))?
-                - chrono::Duration::days(1)
-        };
-        Ok(Some((Some(first_day), Some(last_day))))
-    } else if matches.get_flag("all") {
-        // When --all is specified, return None to indicate no date filter
-        Ok(None)
-    } else {
-        // Default behavior: when no date options are specified, use today
-        let today = chrono::Local::now().naive_local().date();
-        Ok(Some((Some(today), Some(today))))
-    }
+fn parse_date_filter(matches: &clap::ArgMatches) -> Result<DateFilter> {
+    DateFilter::from_matches(matches).map_err(AppError::Config)
 }
 
 async fn run_analysis(
     config: Config,
-    date_filter: Option<(Option<NaiveDate>, Option<NaiveDate>)>,
+    date_filter: DateFilter,
     matches: &clap::ArgMatches,
 ) -> Result<()> {
-    let api_key = config.api.openai_api_key.ok_or(ApiError::MissingApiKey)?;
+    let provider = if config.processing.offline {
+        logger::info("Starting offline analysis (rule-based summarizer)...");
+        None
+    } else {
+        logger::info(&format!(
+            "Starting analysis with {} ({})...",
+            config.api.provider.display_name(),
+            config.api.model
+        ));
+        Some(provider::create_provider(&config.api)?)
+    };
+
+    let project_filter = ProjectFilter::new(
+        &config.processing.include_projects,
+        &config.processing.exclude_projects,
+    )
+    .map_err(AppError::Config)?;
 
-    logger::info("Starting analysis...");
+    // Loaded once: the prompts are used by the analysis, the layout for the Markdown output
+    let templates = templates::Templates::load();
 
     // Run AI analysis
     let mut report = analyze_conversations_with_ai(
         &config.processing.log_dir,
+        &config.processing.index_dir,
         date_filter,
-        &api_key,
+        &project_filter,
+        provider,
+        &templates,
         &config.output.language,
-        &config.api.model,
         config.output.quiet,
         config.processing.parallel_requests,
+        config.processing.per_session,
+        config.processing.idle_gap_minutes,
+        config.processing.all_authors,
     )
     .await
     .map_err(|e| AppError::Processing(format!("Analysis failed: {e}")))?;
 
+    let group_by = config
+        .output
+        .group_by
+        .unwrap_or_else(|| GroupBy::for_period(&date_filter, &report.timeline));
+    report.group_timeline(group_by);
+
     // Handle token tracking display
     if !config.output.show_token_usage {
         report.token_tracker = None;
@@ -156,25 +153,35 @@ async fn run_analysis(
     // Generate output
     let output_path = matches.get_one::<String>("output").map(PathBuf::from);
 
-    match config.output.format {
-        OutputFormat::Json => {
-            let json_output = serde_json::to_string_pretty(&report).map_err(AppError::Json)?;
-
-            if let Some(path) = output_path.as_deref() {
-                std::fs::write(path, json_output).map_err(AppError::Io)?;
-                logger::info(&format!("Report written to: {}", path.display()));
-            } else {
-                println!("{json_output}");
-            }
-        }
+    let mut document = match config.output.format {
+        OutputFormat::Json => serde_json::to_string_pretty(&report).map_err(AppError::Json)?,
+        OutputFormat::Csv => timesheet::to_csv(&report),
+        OutputFormat::Ics => timesheet::to_ics(&report, &config.output.language),
+        OutputFormat::Html => html_report::render(&report, &config.output.language),
         OutputFormat::Markdown => {
-            smart_analyzer::generate_smart_report(
-                &report,
-                output_path.as_deref(),
-                &config.output.language,
-            )
-            .map_err(|e| AppError::Processing(format!("Report generation failed: {e}")))?;
+            let document = match &config.output.template {
+                Some(path) => Some(std::fs::read_to_string(path).map_err(|e| {
+                    AppError::Config(format!("Cannot read template {}: {e}", path.display()))
+                })?),
+                None => None,
+            };
+            templates
+                .report
+                .render(document.as_deref(), &report, &config.output.language)
+                .map_err(|e| AppError::Processing(format!("Template rendering failed: {e:#}")))?
         }
+    };
+
+    // Pretty-printed JSON and custom templates may stop without a line break
+    if !document.ends_with('\n') {
+        document.push('\n');
+    }
+
+    if let Some(path) = output_path.as_deref() {
+        std::fs::write(path, document).map_err(AppError::Io)?;
+        logger::info(&format!("Report written to: {}", path.display()));
+    } else {
+        print!("{document}");
     }
 
     Ok(())
@@ -1,25 +1,29 @@
 #![allow(clippy::collapsible_if)]
 
-use chrono::{DateTime, NaiveDate, Utc};
+use crate::date_filter::DateFilter;
+use crate::i18n::Catalog;
+use crate::log_reader::{jsonl_files, scan_files};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 #[allow(clippy::large_enum_variant)]
 pub enum LogRecord {
-    Entry(LogEntry),
+    // Tried first: every LogEntry field but `type` is optional, so a summary would also parse as an entry
     Summary(SummaryEntry),
+    Entry(LogEntry),
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LogEntry {
     #[serde(rename = "parentUuid")]
     pub parent_uuid: Option<String>,
+    /// Last message before a compaction, set on the boundary that starts the new root
+    #[serde(rename = "logicalParentUuid")]
+    pub logical_parent_uuid: Option<String>,
     #[serde(rename = "isSidechain")]
     pub is_sidechain: Option<bool>,
     #[serde(rename = "userType")]
@@ -41,7 +45,7 @@ pub struct LogEntry {
     pub tool_use_result: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SummaryEntry {
     #[serde(rename = "type")]
     pub entry_type: String,
@@ -50,7 +54,7 @@ pub struct SummaryEntry {
     pub leaf_uuid: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Message {
     pub role: Option<String>,
     pub content: Option<serde_json::Value>,
@@ -68,6 +72,8 @@ pub struct ProjectSummary {
     pub cost_usd: f64,
     pub duration_ms: u64,
     pub models: HashMap<String, usize>,
+    /// Conversation summaries Claude Code wrote for this project
+    pub summaries: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -82,15 +88,10 @@ pub struct DailyReport {
     pub tools_used: HashMap<String, usize>,
 }
 
-fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
-    DateTime::parse_from_rfc3339(timestamp)
-        .ok()
-        .map(|dt| dt.with_timezone(&Utc))
-}
-
 pub fn analyze_logs(
     log_dir: &Path,
-    date_filter: Option<(Option<NaiveDate>, Option<NaiveDate>)>,
+    date_filter: DateFilter,
+    lang: &str,
 ) -> Result<DailyReport, Box<dyn std::error::Error>> {
     let mut projects: HashMap<String, ProjectSummary> = HashMap::new();
     let mut project_sessions: HashMap<String, HashSet<String>> = HashMap::new();
@@ -100,150 +101,125 @@ pub fn analyze_logs(
     let mut all_sessions: HashSet<String> = HashSet::new();
     let mut global_models: HashMap<String, usize> = HashMap::new();
     let mut tools_used: HashMap<String, usize> = HashMap::new();
+    let mut project_uuids: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut summary_records: HashMap<String, Vec<SummaryEntry>> = HashMap::new();
 
-    // Traverse all project directories
+    // Collect the JSONL files of all project directories
+    let mut project_files = Vec::new();
     for entry in std::fs::read_dir(log_dir)? {
-        let entry = entry?;
-        let path = entry.path();
+        let path = entry?.path();
 
         if path.is_dir() {
             let project_name = path
                 .file_name()
                 .map(|name| name.to_string_lossy().to_string())
                 .unwrap_or_else(|| "unknown-project".to_string());
+            project_files.push((project_name, jsonl_files(&path)?));
+        }
+    }
 
-            // Process all JSONL files in the project directory
-            for jsonl_entry in std::fs::read_dir(&path)? {
-                let jsonl_entry = jsonl_entry?;
-                let jsonl_path = jsonl_entry.path();
+    // Scan all files in parallel; the date filter is applied while reading
+    let paths: Vec<_> = project_files
+        .iter()
+        .flat_map(|(_, files)| files.iter().cloned())
+        .collect();
+    let mut scans = scan_files(&paths, date_filter).into_iter();
+
+    for (project_name, files) in project_files {
+        for scan in scans.by_ref().take(files.len()) {
+            let scan = scan?;
+            for error in &scan.errors {
+                eprintln!("Error parsing JSON: {error}");
+            }
 
-                if jsonl_path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                    let file = File::open(&jsonl_path)?;
-                    let reader = BufReader::new(file);
+            for entry in scan.entries {
+                // Track sessions
+                if let Some(session_id) = &entry.session_id {
+                    all_sessions.insert(session_id.clone());
+                    project_sessions
+                        .entry(project_name.clone())
+                        .or_default()
+                        .insert(session_id.clone());
+                }
 
-                    for line in reader.lines() {
-                        let line = line?;
-                        if line.trim().is_empty() {
-                            continue;
-                        }
+                // Initialize project summary if needed
+                let project_summary =
+                    projects
+                        .entry(project_name.clone())
+                        .or_insert_with(|| ProjectSummary {
+                            project_path: project_name.clone(),
+                            sessions: 0,
+                            messages: 0,
+                            cost_usd: 0.0,
+                            duration_ms: 0,
+                            models: HashMap::new(),
+                            summaries: Vec::new(),
+                        });
+
+                // Remember message ids so summaries can be linked later
+                if let Some(uuid) = &entry.uuid {
+                    project_uuids
+                        .entry(project_name.clone())
+                        .or_default()
+                        .insert(uuid.clone());
+                }
 
-                        match serde_json::from_str::<LogRecord>(&line) {
-                            Ok(LogRecord::Entry(entry)) => {
-                                // Parse timestamp and filter by date
-                                if let Some(timestamp_str) = &entry.timestamp {
-                                    if let Some(timestamp) = parse_timestamp(timestamp_str) {
-                                        let entry_date = timestamp.date_naive();
+                // Count messages
+                if entry.entry_type == "user" || entry.entry_type == "assistant" {
+                    total_messages += 1;
+                    project_summary.messages += 1;
+                }
 
-                                        if let Some((from_date, to_date)) = date_filter {
-                                            let in_range = match (from_date, to_date) {
-                                                (Some(from), Some(to)) => {
-                                                    entry_date >= from && entry_date <= to
-                                                }
-                                                (Some(from), None) => entry_date >= from,
-                                                (None, Some(to)) => entry_date <= to,
-                                                (None, None) => true,
-                                            };
-                                            if !in_range {
-                                                continue;
-                                            }
-                                        }
+                // Track costs
+                if let Some(cost) = entry.cost_usd {
+                    total_cost += cost;
+                    project_summary.cost_usd += cost;
+                }
 
-                                        // Track sessions
-                                        if let Some(session_id) = &entry.session_id {
-                                            all_sessions.insert(session_id.clone());
-                                            project_sessions
-                                                .entry(project_name.clone())
-                                                .or_default()
-                                                .insert(session_id.clone());
-                                        }
+                // Track duration
+                if let Some(duration) = entry.duration_ms {
+                    total_duration += duration;
+                    project_summary.duration_ms += duration;
+                }
 
-                                        // Initialize project summary if needed
-                                        let project_summary = projects
-                                            .entry(project_name.clone())
-                                            .or_insert_with(|| ProjectSummary {
-                                                project_path: project_name.clone(),
-                                                sessions: 0,
-                                                messages: 0,
-                                                cost_usd: 0.0,
-                                                duration_ms: 0,
-                                                models: HashMap::new(),
-                                            });
+                // Track models
+                if let Some(message) = &entry.message {
+                    if let Some(model) = &message.model {
+                        *global_models.entry(model.clone()).or_default() += 1;
+                        *project_summary.models.entry(model.clone()).or_default() += 1;
+                    }
+                }
 
-                                        // Count messages
-                                        if entry.entry_type == "user"
-                                            || entry.entry_type == "assistant"
+                // Track tool usage
+                if entry.entry_type == "assistant" {
+                    if let Some(message) = &entry.message {
+                        if let Some(content) = &message.content {
+                            if let Some(array) = content.as_array() {
+                                for item in array {
+                                    if let Some(obj) = item.as_object() {
+                                        if obj.get("type").and_then(|v| v.as_str())
+                                            == Some("tool_use")
                                         {
-                                            total_messages += 1;
-                                            project_summary.messages += 1;
-                                        }
-
-                                        // Track costs
-                                        if let Some(cost) = entry.cost_usd {
-                                            total_cost += cost;
-                                            project_summary.cost_usd += cost;
-                                        }
-
-                                        // Track duration
-                                        if let Some(duration) = entry.duration_ms {
-                                            total_duration += duration;
-                                            project_summary.duration_ms += duration;
-                                        }
-
-                                        // Track models
-                                        if let Some(message) = &entry.message {
-                                            if let Some(model) = &message.model {
-                                                *global_models.entry(model.clone()).or_default() +=
-                                                    1;
-                                                *project_summary
-                                                    .models
-                                                    .entry(model.clone())
+                                            if let Some(tool_name) =
+                                                obj.get("name").and_then(|v| v.as_str())
+                                            {
+                                                *tools_used
+                                                    .entry(tool_name.to_string())
                                                     .or_default() += 1;
                                             }
                                         }
-
-                                        // Track tool usage
-                                        if entry.entry_type == "assistant" {
-                                            if let Some(message) = &entry.message {
-                                                if let Some(content) = &message.content {
-                                                    if let Some(array) = content.as_array() {
-                                                        for item in array {
-                                                            if let Some(obj) = item.as_object() {
-                                                                if obj
-                                                                    .get("type")
-                                                                    .and_then(|v| v.as_str())
-                                                                    == Some("tool_use")
-                                                                {
-                                                                    if let Some(tool_name) = obj
-                                                                        .get("name")
-                                                                        .and_then(|v| v.as_str())
-                                                                    {
-                                                                        *tools_used
-                                                                            .entry(
-                                                                                tool_name
-                                                                                    .to_string(),
-                                                                            )
-                                                                            .or_default() += 1;
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
                                     }
                                 }
                             }
-                            Ok(LogRecord::Summary(_)) => {
-                                // Skip summary entries for now
-                            }
-                            Err(e) => {
-                                eprintln!("Error parsing JSON: {e} in line: {line}");
-                            }
                         }
                     }
                 }
             }
+
+            summary_records
+                .entry(project_name.clone())
+                .or_default()
+                .extend(scan.summaries);
         }
     }
 
@@ -252,20 +228,22 @@ pub fn analyze_logs(
         if let Some(sessions) = project_sessions.get(project_name) {
             summary.sessions = sessions.len();
         }
-    }
 
-    let date_str = match date_filter {
-        Some((Some(from), Some(to))) if from == to => from.format("%Y-%m-%d").to_string(),
-        Some((Some(from), Some(to))) => {
-            format!("{} to {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d"))
+        // Keep the summaries whose conversation falls into the date range
+        if let (Some(records), Some(uuids)) = (
+            summary_records.remove(project_name),
+            project_uuids.get(project_name),
+        ) {
+            summary.summaries = records
+                .into_iter()
+                .filter(|record| uuids.contains(&record.leaf_uuid))
+                .map(|record| record.summary)
+                .collect();
         }
-        Some((Some(from), None)) => format!("From {}", from.format("%Y-%m-%d")),
-        Some((None, Some(to))) => format!("Until {}", to.format("%Y-%m-%d")),
-        _ => "All dates".to_string(),
-    };
+    }
 
     Ok(DailyReport {
-        date: date_str,
+        date: date_filter.label(&Catalog::new(lang)),
         projects: projects.into_values().collect(),
         total_sessions: all_sessions.len(),
         total_messages,
@@ -0,0 +1,71 @@
+//! Include/exclude globs that select the projects of a report
+//!
+//! A pattern is matched against the readable project name, the working
+//! directories (`cwd`) recorded in its log entries, and the last component of
+//! each, so `client-*`, `~/work/*` and `/srv/repos/*` all work. A leading `~/`
+//! also matches the home directory in recorded paths.
+
+use glob::Pattern;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default)]
+pub struct ProjectFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl ProjectFilter {
+    /// Every project
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Projects matching any `include` pattern (all if none) and no `exclude` pattern
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, String> {
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// True if the project named `name`, run in the directories `cwds`, is selected
+    pub fn matches<'a>(&self, name: &str, cwds: impl IntoIterator<Item = &'a str>) -> bool {
+        if self.is_all() {
+            return true;
+        }
+
+        let mut candidates = vec![name];
+        for cwd in cwds {
+            candidates.push(cwd);
+            candidates.extend(Path::new(cwd).file_name().and_then(|last| last.to_str()));
+        }
+        candidates.extend(Path::new(name).file_name().and_then(|last| last.to_str()));
+
+        let any = |patterns: &[Pattern]| {
+            patterns
+                .iter()
+                .any(|pattern| candidates.iter().any(|c| pattern.matches(c)))
+        };
+        (self.include.is_empty() || any(&self.include)) && !any(&self.exclude)
+    }
+}
+
+/// Compile patterns, adding a home-expanded copy of each `~/` pattern
+fn compile(patterns: &[String]) -> Result<Vec<Pattern>, String> {
+    let home = std::env::var("HOME").ok();
+    let mut compiled = Vec::new();
+    for pattern in patterns {
+        let parse = |pattern: &str| {
+            Pattern::new(pattern).map_err(|e| format!("Invalid project pattern '{pattern}': {e}"))
+        };
+        compiled.push(parse(pattern)?);
+        if let (Some(rest), Some(home)) = (pattern.strip_prefix("~/"), &home) {
+            compiled.push(parse(&format!("{}/{rest}", home.trim_end_matches('/')))?);
+        }
+    }
+    Ok(compiled)
+}
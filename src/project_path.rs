@@ -0,0 +1,120 @@
+//! Real project paths behind Claude Code's encoded log directories
+//!
+//! Claude Code stores each project's logs in a directory named after the
+//! working directory with every `/` (and other punctuation) replaced by `-`,
+//! so `~/src/my-app` and `~/src/my/app` share one encoding and the name cannot
+//! be decoded reliably. The `cwd` recorded in the log entries is the real
+//! path; it is then resolved to the root of its git repository, so linked
+//! worktrees and sessions started in subdirectories count as one project.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Repository root (or working directory) of a log directory's sessions
+///
+/// Prefers the recorded `cwd` whose encoding equals the directory name, then
+/// the most frequent `cwd`, and only decodes the directory name when no entry
+/// recorded one.
+pub fn resolve<'a>(encoded: &str, cwds: impl IntoIterator<Item = &'a str>) -> PathBuf {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut order = Vec::new();
+    for cwd in cwds {
+        let count = counts.entry(cwd).or_insert(0);
+        if *count == 0 {
+            order.push(cwd);
+        }
+        *count += 1;
+    }
+
+    let cwd = order
+        .iter()
+        .find(|cwd| encode(Path::new(cwd)) == encoded)
+        // Reversed so that the first of equally frequent directories wins
+        .or_else(|| order.iter().rev().max_by_key(|cwd| counts[*cwd]))
+        .map(PathBuf::from);
+
+    match cwd {
+        Some(cwd) => repository_root(&cwd),
+        None => decode(encoded),
+    }
+}
+
+/// Claude Code's directory name for a working directory
+pub fn encode(path: &Path) -> String {
+    path.to_string_lossy()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Best-effort path for a directory name whose entries recorded no `cwd`
+///
+/// Hyphens in the original directory names are indistinguishable from path
+/// separators, so `-home-me-my-app` decodes to `/home/me/my/app`.
+pub fn decode(encoded: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "/{}",
+        encoded.trim_start_matches('-').replace('-', "/")
+    ))
+}
+
+/// Top of the git work tree containing `path`; the main work tree for a
+/// linked worktree. Paths outside a repository, or missing on this machine,
+/// are returned unchanged.
+pub fn repository_root(path: &Path) -> PathBuf {
+    for dir in path.ancestors() {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return dir.to_path_buf();
+        }
+        if dot_git.is_file() {
+            return main_worktree(dir, &dot_git).unwrap_or_else(|| dir.to_path_buf());
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Main work tree of a linked worktree, whose `.git` file points to
+/// `<repo>/.git/worktrees/<name>`; None for submodules and broken links
+fn main_worktree(dir: &Path, dot_git: &Path) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(dot_git).ok()?;
+    let git_dir = dir.join(content.trim().strip_prefix("gitdir:")?.trim());
+    let common_dir = std::fs::read_to_string(git_dir.join("commondir")).ok()?;
+    let common_dir = normalize(&git_dir.join(common_dir.trim()));
+    common_dir.parent().map(Path::to_path_buf)
+}
+
+/// Remove `.` and `..` components without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Path for display, with the home directory shortened to `~`
+pub fn display_name(path: &Path) -> String {
+    if let Some(home) = std::env::var_os("HOME").filter(|home| !home.is_empty()) {
+        if let Ok(rest) = path.strip_prefix(&home) {
+            return if rest.as_os_str().is_empty() {
+                "~".to_string()
+            } else {
+                format!("~/{}", rest.display())
+            };
+        }
+    }
+    path.display().to_string()
+}
@@ -0,0 +1,638 @@
+use crate::chunker::context_limit_for_model;
+use crate::config::{ApiConfig, ProviderKind};
+use crate::error::ApiError;
+use crate::logger;
+use crate::token_tracker::TokenUsage;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const ANTHROPIC_MAX_TOKENS: u32 = 4096;
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(120);
+
+/// Role of a message sent to the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::User,
+            content: content.into(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::Assistant,
+            content: content.into(),
+        }
+    }
+}
+
+/// A provider independent completion request
+#[derive(Debug, Clone)]
+pub struct CompletionRequest {
+    pub system: String,
+    pub messages: Vec<ChatMessage>,
+    /// Constrain the reply to JSON matching this schema, if the backend supports it
+    pub response_schema: Option<ResponseSchema>,
+}
+
+/// JSON schema the model's reply must follow
+///
+/// Sent as `response_format: json_schema` to OpenAI-compatible APIs and as a
+/// forced tool call to Anthropic; either way the reply text is the JSON document.
+#[derive(Debug, Clone)]
+pub struct ResponseSchema {
+    pub name: String,
+    pub description: String,
+    pub schema: serde_json::Value,
+}
+
+/// Text returned by the model together with its token usage
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub text: String,
+    pub usage: Option<TokenUsage>,
+    /// Number of retries needed before the request succeeded
+    pub retries: u32,
+}
+
+/// A chat model backend able to answer a system + user prompt
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Which backend this provider talks to
+    fn kind(&self) -> ProviderKind;
+
+    /// Model name as given in the configuration (used for caching and pricing)
+    fn model(&self) -> &str;
+
+    /// Context window of the model in tokens, prompt and reply combined
+    fn context_limit(&self) -> usize;
+
+    /// Send the request and return the model's text reply
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion, ApiError>;
+}
+
+/// Build the provider selected in the configuration
+pub fn create_provider(config: &ApiConfig) -> Result<Arc<dyn LlmProvider>, ApiError> {
+    let client = reqwest::Client::builder()
+        .default_headers(extra_headers(config)?)
+        .build()
+        .map_err(|e| ApiError::Network(e.to_string()))?;
+
+    // The hosted APIs always need a key; custom endpoints decide for themselves
+    let api_key = match config.api_key() {
+        Some(key) => Some(key.to_string()),
+        None if config.provider.api_key_env().is_some() && config.base_url.is_none() => {
+            return Err(ApiError::MissingApiKey)
+        }
+        None => None,
+    };
+
+    // Don't send a bearer token when the user supplies their own auth header
+    let custom_auth = config.extra_headers.keys().any(|name| {
+        name.eq_ignore_ascii_case("authorization") || name.eq_ignore_ascii_case("api-key")
+    });
+
+    let context_limit = config
+        .context_limit
+        .unwrap_or_else(|| context_limit_for_model(&config.model));
+
+    let provider: Arc<dyn LlmProvider> = match config.provider {
+        ProviderKind::OpenAi | ProviderKind::Ollama => Arc::new(OpenAiCompatibleProvider {
+            kind: config.provider,
+            client,
+            endpoint: endpoint_url(config.base_url(), "chat/completions"),
+            api_key: api_key.filter(|_| !custom_auth),
+            model: config.model.clone(),
+            context_limit,
+            temperature: config.temperature,
+            retry: RetryPolicy::from_config(config),
+        }),
+        ProviderKind::Anthropic => Arc::new(AnthropicProvider {
+            client,
+            endpoint: endpoint_url(config.base_url(), "v1/messages"),
+            api_key,
+            model: config.model.clone(),
+            context_limit,
+            temperature: config.temperature,
+            retry: RetryPolicy::from_config(config),
+        }),
+    };
+
+    Ok(provider)
+}
+
+/// Join an API path onto a base URL, keeping any query string (Azure's `api-version`) at the end
+pub fn endpoint_url(base_url: &str, path: &str) -> String {
+    let (base, query) = match base_url.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (base_url, None),
+    };
+
+    let mut url = format!("{}/{}", base.trim_end_matches('/'), path);
+    if let Some(query) = query {
+        url.push('?');
+        url.push_str(query);
+    }
+    url
+}
+
+fn extra_headers(config: &ApiConfig) -> Result<HeaderMap, ApiError> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.extra_headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| ApiError::InvalidHeader(format!("{name}: {e}")))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| ApiError::InvalidHeader(format!("{name}: {e}")))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// OpenAI Chat Completions API, also spoken by Ollama and other local servers
+pub struct OpenAiCompatibleProvider {
+    kind: ProviderKind,
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    context_limit: usize,
+    temperature: f32,
+    retry: RetryPolicy,
+}
+
+impl OpenAiCompatibleProvider {
+    fn model_name(&self) -> &str {
+        if self.kind != ProviderKind::OpenAi {
+            return &self.model;
+        }
+
+        match self.model.as_str() {
+            "gpt-4o" => "chatgpt-4o-latest",
+            "gpt-4o-mini" => "gpt-4o-mini",
+            "gpt-4-turbo" => "gpt-4-turbo-preview",
+            "gpt-3.5-turbo" => "gpt-3.5-turbo",
+            _ => &self.model, // fallback to provided name
+        }
+    }
+
+    async fn send(
+        &self,
+        request_body: &serde_json::Value,
+    ) -> Result<(serde_json::Value, u32), ApiError> {
+        let mut builder = self
+            .client
+            .post(&self.endpoint)
+            .header("Content-Type", "application/json");
+        if let Some(ref api_key) = self.api_key {
+            builder = builder.header("Authorization", format!("Bearer {api_key}"));
+        }
+
+        self.retry.send_json(&builder.json(request_body)).await
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn kind(&self) -> ProviderKind {
+        self.kind
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn context_limit(&self) -> usize {
+        self.context_limit
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion, ApiError> {
+        let mut messages = vec![serde_json::json!({
+            "role": "system",
+            "content": request.system
+        })];
+        messages.extend(
+            request
+                .messages
+                .iter()
+                .map(|m| serde_json::json!({ "role": m.role, "content": m.content })),
+        );
+
+        let mut request_body = serde_json::json!({
+            "model": self.model_name(),
+            "messages": messages,
+            "temperature": self.temperature
+        });
+        if let Some(ref schema) = request.response_schema {
+            request_body["response_format"] = serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": schema.name,
+                    "description": schema.description,
+                    "schema": schema.schema,
+                    "strict": true
+                }
+            });
+        }
+
+        let (api_response, retries) = match self.send(&request_body).await {
+            // Older models and some compatible servers reject json_schema; ask again without it
+            Err(ApiError::RequestFailed {
+                status: 400,
+                message,
+            }) if request.response_schema.is_some() && message.contains("response_format") => {
+                logger::warn(&format!(
+                    "{} does not support structured output, retrying without a JSON schema",
+                    self.model_name()
+                ));
+                if let Some(body) = request_body.as_object_mut() {
+                    body.remove("response_format");
+                }
+                self.send(&request_body).await?
+            }
+            result => result?,
+        };
+
+        let text = api_response
+            .get("choices")
+            .and_then(|choices| choices.as_array())
+            .and_then(|arr| arr.first())
+            .and_then(|choice| choice.get("message"))
+            .and_then(|message| message.get("content"))
+            .and_then(|content| content.as_str())
+            .ok_or_else(|| ApiError::InvalidResponse("no content in response".to_string()))?;
+
+        // Compatible servers (Ollama, vLLM, proxies) often report only some of the counts
+        let usage = api_response["usage"].as_object().map(|usage| {
+            let prompt_tokens = token_count(usage, "prompt_tokens");
+            let completion_tokens = token_count(usage, "completion_tokens");
+            TokenUsage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: usage
+                    .get("total_tokens")
+                    .and_then(serde_json::Value::as_u64)
+                    .map_or(prompt_tokens + completion_tokens, |total| total as u32),
+            }
+        });
+
+        Ok(Completion {
+            text: text.to_string(),
+            usage,
+            retries,
+        })
+    }
+}
+
+/// Anthropic Messages API
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    context_limit: usize,
+    temperature: f32,
+    retry: RetryPolicy,
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Anthropic
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn context_limit(&self) -> usize {
+        self.context_limit
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion, ApiError> {
+        let mut request_body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": ANTHROPIC_MAX_TOKENS,
+            "system": request.system,
+            "messages": request.messages,
+            "temperature": self.temperature
+        });
+        // Anthropic has no JSON mode; forcing a single tool call gives schema-checked input instead
+        if let Some(ref schema) = request.response_schema {
+            request_body["tools"] = serde_json::json!([{
+                "name": schema.name,
+                "description": schema.description,
+                "input_schema": schema.schema
+            }]);
+            request_body["tool_choice"] = serde_json::json!({
+                "type": "tool",
+                "name": schema.name
+            });
+        }
+
+        let mut builder = self
+            .client
+            .post(&self.endpoint)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .header("Content-Type", "application/json");
+        if let Some(ref api_key) = self.api_key {
+            builder = builder.header("x-api-key", api_key);
+        }
+
+        let builder = builder.json(&request_body);
+        let (api_response, retries) = self.retry.send_json(&builder).await?;
+
+        let content = api_response
+            .get("content")
+            .and_then(|content| content.as_array())
+            .ok_or_else(|| ApiError::InvalidResponse("no content in response".to_string()))?;
+
+        let tool_input = content
+            .iter()
+            .find(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+            .and_then(|block| block.get("input"));
+        let text: String = match tool_input {
+            Some(input) => input.to_string(),
+            None => content
+                .iter()
+                .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+                .collect(),
+        };
+
+        let usage = api_response["usage"].as_object().map(|usage| {
+            let prompt_tokens = token_count(usage, "input_tokens");
+            let completion_tokens = token_count(usage, "output_tokens");
+            TokenUsage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            }
+        });
+
+        Ok(Completion {
+            text,
+            usage,
+            retries,
+        })
+    }
+}
+
+/// Count `key` of a `usage` object, 0 when the server left it out
+fn token_count(usage: &serde_json::Map<String, serde_json::Value>, key: &str) -> u32 {
+    usage
+        .get(key)
+        .and_then(serde_json::Value::as_u64)
+        .unwrap_or(0) as u32
+}
+
+/// How failed requests are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+/// A failed attempt and whether it is worth trying again
+struct AttemptError {
+    error: ApiError,
+    retryable: bool,
+    retry_after: Option<Duration>,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &ApiConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            base_delay: Duration::from_millis(config.retry_delay_ms),
+            max_delay: MAX_RETRY_DELAY,
+        }
+    }
+
+    /// Exponential backoff with jitter: a random delay in [d/2, d] where d = base * 2^retry
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        let jitter_ms = random_u64() % (half.as_millis() as u64 + 1);
+        half + Duration::from_millis(jitter_ms)
+    }
+
+    /// Send the request, retrying rate limits, server errors and network failures
+    async fn send_json(
+        &self,
+        builder: &reqwest::RequestBuilder,
+    ) -> Result<(serde_json::Value, u32), ApiError> {
+        let mut retries = 0;
+
+        loop {
+            let request = builder
+                .try_clone()
+                .ok_or_else(|| ApiError::Network("request body cannot be retried".to_string()))?;
+
+            let failure = match send_once(request).await {
+                Ok(value) => return Ok((value, retries)),
+                Err(failure) => failure,
+            };
+
+            if !failure.retryable || retries >= self.max_retries {
+                return Err(if retries == 0 {
+                    failure.error
+                } else {
+                    ApiError::RetriesExhausted {
+                        retries,
+                        last_error: Box::new(failure.error),
+                    }
+                });
+            }
+
+            let delay = failure
+                .retry_after
+                .unwrap_or_else(|| self.backoff(retries))
+                .min(self.max_delay);
+            logger::warn(&format!(
+                "{} - retrying in {:.1}s ({}/{})",
+                failure.error,
+                delay.as_secs_f64(),
+                retries + 1,
+                self.max_retries
+            ));
+            tokio::time::sleep(delay).await;
+            retries += 1;
+        }
+    }
+}
+
+async fn send_once(request: reqwest::RequestBuilder) -> Result<serde_json::Value, AttemptError> {
+    let response = request.send().await.map_err(|e| AttemptError {
+        retryable: e.is_timeout() || e.is_connect(),
+        error: ApiError::Network(e.to_string()),
+        retry_after: None,
+    })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let retry_after = retry_after_from_headers(response.headers());
+        let retryable =
+            matches!(status.as_u16(), 408 | 409 | 429 | 529) || status.is_server_error();
+        let error = if status.as_u16() == 429 {
+            ApiError::RateLimitExceeded
+        } else {
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("failed to read error body: {e}"));
+            ApiError::RequestFailed {
+                status: status.as_u16(),
+                message,
+            }
+        };
+        return Err(AttemptError {
+            error,
+            retryable,
+            retry_after,
+        });
+    }
+
+    response
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| AttemptError {
+            error: ApiError::InvalidResponse(e.to_string()),
+            retryable: false,
+            retry_after: None,
+        })
+}
+
+/// How long the server asks us to wait, from `Retry-After`, `retry-after-ms`,
+/// OpenAI's `x-ratelimit-reset-*` or Anthropic's `anthropic-ratelimit-*-reset` headers,
+/// at most the maximum backoff
+pub fn retry_after_from_headers(headers: &HeaderMap) -> Option<Duration> {
+    requested_wait(headers).map(|wait| wait.min(MAX_RETRY_DELAY))
+}
+
+fn requested_wait(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.trim().parse::<f64>().ok()) {
+        return seconds(ms / 1000.0);
+    }
+
+    if let Some(value) = header("retry-after") {
+        if let Ok(wait) = value.trim().parse::<f64>() {
+            return seconds(wait);
+        }
+        if let Ok(date) = chrono::DateTime::parse_from_rfc2822(value.trim()) {
+            return Some(duration_until(date.with_timezone(&chrono::Utc)));
+        }
+    }
+
+    // Only the limits that are exhausted matter; fall back to all of them if unknown
+    let openai_resets = |only_exhausted: bool| {
+        ["requests", "tokens"]
+            .iter()
+            .filter(|kind| {
+                !only_exhausted
+                    || header(&format!("x-ratelimit-remaining-{kind}")).map(str::trim) == Some("0")
+            })
+            .filter_map(|kind| header(&format!("x-ratelimit-reset-{kind}")))
+            .filter_map(parse_reset_duration)
+            .max()
+    };
+    if let Some(reset) = openai_resets(true).or_else(|| openai_resets(false)) {
+        return Some(reset);
+    }
+
+    [
+        "anthropic-ratelimit-requests-reset",
+        "anthropic-ratelimit-tokens-reset",
+        "anthropic-ratelimit-input-tokens-reset",
+        "anthropic-ratelimit-output-tokens-reset",
+    ]
+    .iter()
+    .filter_map(|name| header(name))
+    .filter_map(|value| chrono::DateTime::parse_from_rfc3339(value.trim()).ok())
+    .map(|date| duration_until(date.with_timezone(&chrono::Utc)))
+    .max()
+}
+
+/// Parse OpenAI reset durations such as `20ms`, `1s`, `6m0s` or `1h2m3.5s`
+pub fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut chars = value.trim().chars().peekable();
+    let mut parsed_any = false;
+
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+
+        let amount: f64 = number.parse().ok()?;
+        number.clear();
+        let unit_seconds = match c {
+            'h' => 3600.0,
+            'm' if chars.peek() == Some(&'s') => {
+                chars.next();
+                0.001
+            }
+            'm' => 60.0,
+            's' => 1.0,
+            _ => return None,
+        };
+        total += amount * unit_seconds;
+        parsed_any = true;
+    }
+
+    if !number.is_empty() {
+        // A bare number is seconds
+        total += number.parse::<f64>().ok()?;
+        parsed_any = true;
+    }
+
+    if parsed_any {
+        seconds(total)
+    } else {
+        None
+    }
+}
+
+/// A wait given in seconds; negative waits are zero, and NaN, infinite or
+/// overflowing values are `None` rather than a panic
+fn seconds(value: f64) -> Option<Duration> {
+    let value = if value < 0.0 { 0.0 } else { value };
+    Duration::try_from_secs_f64(value).ok()
+}
+
+fn duration_until(date: chrono::DateTime<chrono::Utc>) -> Duration {
+    (date - chrono::Utc::now())
+        .to_std()
+        .unwrap_or(Duration::ZERO)
+}
+
+fn random_u64() -> u64 {
+    // RandomState is seeded randomly per instance, which is plenty for jitter
+    RandomState::new().build_hasher().finish()
+}
@@ -0,0 +1,162 @@
+//! Session level view of a project's conversations
+//!
+//! Every Claude Code session carries a `sessionId`. A project directory can
+//! hold many sessions, so besides the project-wide topics the report tracks
+//! when each session ran, how many messages it had and what it worked on.
+
+use crate::conversation_analyzer::Topic;
+use crate::date_filter::ReportTimeZone;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Maximum number of topic titles used for a rule-based session summary
+const SUMMARY_TOPICS: usize = 3;
+
+/// Time span and message count of one session, collected while reading the logs
+#[derive(Debug, Clone)]
+pub struct SessionActivity {
+    pub session_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub message_count: usize,
+}
+
+/// Collects [`SessionActivity`] for every session seen in a project
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    sessions: HashMap<String, SessionActivity>,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a log entry of `session_id`; `is_message` marks user/assistant messages
+    pub fn record(&mut self, session_id: &str, timestamp: DateTime<Utc>, is_message: bool) {
+        let session = self
+            .sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| SessionActivity {
+                session_id: session_id.to_string(),
+                start: timestamp,
+                end: timestamp,
+                message_count: 0,
+            });
+
+        session.start = session.start.min(timestamp);
+        session.end = session.end.max(timestamp);
+        if is_message {
+            session.message_count += 1;
+        }
+    }
+
+    /// All recorded sessions, oldest first
+    pub fn into_sessions(self) -> Vec<SessionActivity> {
+        let mut sessions: Vec<SessionActivity> = self.sessions.into_values().collect();
+        sessions.sort_by_key(|session| session.start);
+        sessions
+    }
+}
+
+/// What one session achieved, as shown in the report
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub start: String,
+    pub end: String,
+    pub duration_minutes: i64,
+    pub message_count: usize,
+    pub summary: String,
+    /// Summaries Claude Code itself wrote for the session's conversations
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub claude_summaries: Vec<String>,
+}
+
+impl SessionSummary {
+    /// The same session with start and end as wall-clock time in `tz`
+    pub fn in_time_zone(self, tz: ReportTimeZone) -> Self {
+        let localize = |timestamp: &str| {
+            DateTime::parse_from_rfc3339(timestamp)
+                .map(|t| tz.to_local(t.with_timezone(&Utc)).to_rfc3339())
+                .unwrap_or_else(|_| timestamp.to_string())
+        };
+        Self {
+            start: localize(&self.start),
+            end: localize(&self.end),
+            ..self
+        }
+    }
+
+    /// "2025-06-29 10:00–11:30", with the end date repeated when the session spans midnight
+    pub fn time_range(&self) -> String {
+        let parse = |timestamp: &str| DateTime::parse_from_rfc3339(timestamp).ok();
+        match (parse(&self.start), parse(&self.end)) {
+            (Some(start), Some(end)) if start.date_naive() == end.date_naive() => {
+                format!("{}–{}", start.format("%Y-%m-%d %H:%M"), end.format("%H:%M"))
+            }
+            (Some(start), Some(end)) => format!(
+                "{}–{}",
+                start.format("%Y-%m-%d %H:%M"),
+                end.format("%Y-%m-%d %H:%M")
+            ),
+            _ => format!("{}–{}", self.start, self.end),
+        }
+    }
+}
+
+/// Topics that started in the given session
+pub fn session_topics<'a>(topics: &'a [Topic], session_id: &str) -> Vec<&'a Topic> {
+    topics
+        .iter()
+        .filter(|topic| topic.session_id.as_deref() == Some(session_id))
+        .collect()
+}
+
+/// Rule-based session summaries
+///
+/// Uses the summaries Claude Code wrote for the session when there are any,
+/// otherwise the titles of the session's topics.
+pub fn summarize_sessions(sessions: Vec<SessionActivity>, topics: &[Topic]) -> Vec<SessionSummary> {
+    sessions
+        .into_iter()
+        .map(|session| {
+            let session_topics = session_topics(topics, &session.session_id);
+            let mut claude_summaries: Vec<String> = Vec::new();
+            for summary in session_topics.iter().flat_map(|topic| &topic.summaries) {
+                if !claude_summaries.contains(summary) {
+                    claude_summaries.push(summary.clone());
+                }
+            }
+
+            let titles: Vec<&str> = if claude_summaries.is_empty() {
+                session_topics
+                    .iter()
+                    .map(|topic| topic.title.as_str())
+                    .collect()
+            } else {
+                claude_summaries.iter().map(String::as_str).collect()
+            };
+            let mut summary = titles
+                .iter()
+                .take(SUMMARY_TOPICS)
+                .copied()
+                .collect::<Vec<_>>()
+                .join("; ");
+            if titles.len() > SUMMARY_TOPICS {
+                summary.push_str(&format!(" (+{})", titles.len() - SUMMARY_TOPICS));
+            }
+
+            SessionSummary {
+                start: session.start.to_rfc3339(),
+                end: session.end.to_rfc3339(),
+                duration_minutes: (session.end - session.start).num_minutes(),
+                message_count: session.message_count,
+                session_id: session.session_id,
+                summary,
+                claude_summaries,
+            }
+        })
+        .collect()
+}
@@ -1,3 +1,10 @@
+use crate::commands::CommandStats;
+use crate::file_ledger::FileChange;
+use crate::git::{Commit, DiffStat};
+use crate::session::SessionSummary;
+use crate::templates::Templates;
+use crate::timeline::{self, GroupBy, TimelineBucket};
+use crate::work_time::ActiveTime;
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
@@ -7,6 +14,25 @@ pub struct SmartReport {
     pub projects: Vec<SmartProjectSummary>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub token_tracker: Option<crate::token_tracker::TokenTracker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_stats: Option<crate::ai_analyzer::ApiStats>,
+    /// Topics by day or week; empty when grouping by project only
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub timeline: Vec<TimelineBucket>,
+    /// Working time over all projects, per day
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_time: Option<ActiveTime>,
+}
+
+impl SmartReport {
+    /// Regroup the timeline by day or week, or drop it when grouping by project
+    pub fn group_timeline(&mut self, group_by: GroupBy) {
+        let entries = std::mem::take(&mut self.timeline)
+            .into_iter()
+            .flat_map(|bucket| bucket.entries)
+            .collect();
+        self.timeline = timeline::group(entries, group_by);
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -19,10 +45,27 @@ pub struct ExecutiveSummary {
 
 #[derive(Debug, Serialize)]
 pub struct SmartProjectSummary {
+    /// Project path, with `~` for the home directory
+    pub project: String,
     pub title: String,
     pub objective: String,
     pub work_breakdown: WorkBreakdown,
     pub outcomes: ProjectOutcomes,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sessions: Vec<SessionSummary>,
+    /// Commits made in the project's repository during the period
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub commits: Vec<Commit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diffstat: Option<DiffStat>,
+    /// Files created, edited or read in the period, from the tool calls
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileChange>,
+    /// Build, test, lint, format and deploy commands and the project's test health
+    #[serde(skip_serializing_if = "CommandStats::is_empty")]
+    pub commands: CommandStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_time: Option<ActiveTime>,
 }
 
 #[derive(Debug, Serialize)]
@@ -61,21 +104,13 @@ pub struct ProjectOutcomes {
     pub unresolved_issues: Vec<String>,
 }
 
+/// Write the report with the built-in layout of [`Templates`] to `output_path`, or print it
 pub fn generate_smart_report(
     report: &SmartReport,
     output_path: Option<&std::path::Path>,
     lang: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut output = String::new();
-
-    match lang {
-        "ja" => generate_japanese_smart_report(&mut output, report),
-        "zh" => generate_chinese_smart_report(&mut output, report),
-        "ko" => generate_korean_smart_report(&mut output, report),
-        "es" => generate_spanish_smart_report(&mut output, report),
-        "fr" => generate_french_smart_report(&mut output, report),
-        _ => generate_english_smart_report(&mut output, report),
-    }
+    let output = Templates::default().report.render(None, report, lang)?;
 
     if let Some(path) = output_path {
         std::fs::write(path, output)?;
@@ -91,283 +126,3 @@ fn get_output_message(_lang: &str, path: &std::path::Path) -> String {
     // Always use English for system messages
     format!("AI work report written to: {}", path.display())
 }
-
-fn generate_japanese_smart_report(output: &mut String, report: &SmartReport) {
-    output.push_str(&format!("# 作業報告書 - {}\n\n", report.date));
-
-    for project in &report.projects {
-        output.push_str(&format!("## {}\n\n", project.title));
-        output.push_str(&format!("**目的**: {}\n\n", project.objective));
-
-        // Work breakdown
-        if !project.work_breakdown.primary_activities.is_empty() {
-            output.push_str("**作業内容**:\n");
-            for activity in &project.work_breakdown.primary_activities {
-                output.push_str(&format!("- {} - {}\n", activity.category, activity.summary));
-            }
-            output.push('\n');
-        }
-
-        // Outcomes
-        if !project.outcomes.delivered_value.is_empty() {
-            output.push_str("**成果**:\n");
-            for value in &project.outcomes.delivered_value {
-                output.push_str(&format!("- {value}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.technical_improvements.is_empty() {
-            output.push_str("**技術的改善**:\n");
-            for improvement in &project.outcomes.technical_improvements {
-                output.push_str(&format!("- {improvement}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.unresolved_issues.is_empty() {
-            output.push_str("**未解決の課題**:\n");
-            for issue in &project.outcomes.unresolved_issues {
-                output.push_str(&format!("- {issue}\n"));
-            }
-            output.push('\n');
-        }
-    }
-
-    // Add token usage summary if available
-    if let Some(ref tracker) = report.token_tracker {
-        output.push_str(&tracker.get_summary_string("ja"));
-    }
-}
-
-fn generate_english_smart_report(output: &mut String, report: &SmartReport) {
-    output.push_str(&format!("# Work Report - {}\n\n", report.date));
-
-    for project in &report.projects {
-        output.push_str(&format!("## {}\n\n", project.title));
-        output.push_str(&format!("**Objective**: {}\n\n", project.objective));
-
-        // Work breakdown
-        if !project.work_breakdown.primary_activities.is_empty() {
-            output.push_str("**Activities**:\n");
-            for activity in &project.work_breakdown.primary_activities {
-                output.push_str(&format!("- {} - {}\n", activity.category, activity.summary));
-            }
-            output.push('\n');
-        }
-
-        // Outcomes
-        if !project.outcomes.delivered_value.is_empty() {
-            output.push_str("**Delivered Value**:\n");
-            for value in &project.outcomes.delivered_value {
-                output.push_str(&format!("- {value}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.technical_improvements.is_empty() {
-            output.push_str("**Technical Improvements**:\n");
-            for improvement in &project.outcomes.technical_improvements {
-                output.push_str(&format!("- {improvement}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.unresolved_issues.is_empty() {
-            output.push_str("**Unresolved Issues**:\n");
-            for issue in &project.outcomes.unresolved_issues {
-                output.push_str(&format!("- {issue}\n"));
-            }
-            output.push('\n');
-        }
-    }
-
-    // Add token usage summary if available
-    if let Some(ref tracker) = report.token_tracker {
-        output.push_str(&tracker.get_summary_string("en"));
-    }
-}
-
-fn generate_chinese_smart_report(output: &mut String, report: &SmartReport) {
-    output.push_str(&format!("# 工作报告 - {}\n\n", report.date));
-
-    for project in &report.projects {
-        output.push_str(&format!("## {}\n\n", project.title));
-        output.push_str(&format!("**目标**: {}\n\n", project.objective));
-
-        if !project.work_breakdown.primary_activities.is_empty() {
-            output.push_str("**工作内容**:\n");
-            for activity in &project.work_breakdown.primary_activities {
-                output.push_str(&format!("- {} - {}\n", activity.category, activity.summary));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.delivered_value.is_empty() {
-            output.push_str("**交付价值**:\n");
-            for value in &project.outcomes.delivered_value {
-                output.push_str(&format!("- {value}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.technical_improvements.is_empty() {
-            output.push_str("**技术改进**:\n");
-            for improvement in &project.outcomes.technical_improvements {
-                output.push_str(&format!("- {improvement}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.unresolved_issues.is_empty() {
-            output.push_str("**未解决的问题**:\n");
-            for issue in &project.outcomes.unresolved_issues {
-                output.push_str(&format!("- {issue}\n"));
-            }
-            output.push('\n');
-        }
-    }
-
-    // Add token usage summary if available
-    if let Some(ref tracker) = report.token_tracker {
-        output.push_str(&tracker.get_summary_string("zh"));
-    }
-}
-
-fn generate_korean_smart_report(output: &mut String, report: &SmartReport) {
-    output.push_str(&format!("# 작업 보고서 - {}\n\n", report.date));
-
-    for project in &report.projects {
-        output.push_str(&format!("## {}\n\n", project.title));
-        output.push_str(&format!("**목표**: {}\n\n", project.objective));
-
-        if !project.work_breakdown.primary_activities.is_empty() {
-            output.push_str("**작업 내용**:\n");
-            for activity in &project.work_breakdown.primary_activities {
-                output.push_str(&format!("- {} - {}\n", activity.category, activity.summary));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.delivered_value.is_empty() {
-            output.push_str("**제공된 가치**:\n");
-            for value in &project.outcomes.delivered_value {
-                output.push_str(&format!("- {value}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.technical_improvements.is_empty() {
-            output.push_str("**기술적 개선사항**:\n");
-            for improvement in &project.outcomes.technical_improvements {
-                output.push_str(&format!("- {improvement}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.unresolved_issues.is_empty() {
-            output.push_str("**미해결 문제**:\n");
-            for issue in &project.outcomes.unresolved_issues {
-                output.push_str(&format!("- {issue}\n"));
-            }
-            output.push('\n');
-        }
-    }
-
-    // Add token usage summary if available
-    if let Some(ref tracker) = report.token_tracker {
-        output.push_str(&tracker.get_summary_string("ko"));
-    }
-}
-
-fn generate_spanish_smart_report(output: &mut String, report: &SmartReport) {
-    output.push_str(&format!("# Informe de Trabajo - {}\n\n", report.date));
-
-    for project in &report.projects {
-        output.push_str(&format!("## {}\n\n", project.title));
-        output.push_str(&format!("**Objetivo**: {}\n\n", project.objective));
-
-        if !project.work_breakdown.primary_activities.is_empty() {
-            output.push_str("**Actividades**:\n");
-            for activity in &project.work_breakdown.primary_activities {
-                output.push_str(&format!("- {} - {}\n", activity.category, activity.summary));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.delivered_value.is_empty() {
-            output.push_str("**Valor entregado**:\n");
-            for value in &project.outcomes.delivered_value {
-                output.push_str(&format!("- {value}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.technical_improvements.is_empty() {
-            output.push_str("**Mejoras técnicas**:\n");
-            for improvement in &project.outcomes.technical_improvements {
-                output.push_str(&format!("- {improvement}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.unresolved_issues.is_empty() {
-            output.push_str("**Problemas no resueltos**:\n");
-            for issue in &project.outcomes.unresolved_issues {
-                output.push_str(&format!("- {issue}\n"));
-            }
-            output.push('\n');
-        }
-    }
-
-    // Add token usage summary if available
-    if let Some(ref tracker) = report.token_tracker {
-        output.push_str(&tracker.get_summary_string("es"));
-    }
-}
-
-fn generate_french_smart_report(output: &mut String, report: &SmartReport) {
-    output.push_str(&format!("# Rapport de Travail - {}\n\n", report.date));
-
-    for project in &report.projects {
-        output.push_str(&format!("## {}\n\n", project.title));
-        output.push_str(&format!("**Objectif**: {}\n\n", project.objective));
-
-        if !project.work_breakdown.primary_activities.is_empty() {
-            output.push_str("**Activités**:\n");
-            for activity in &project.work_breakdown.primary_activities {
-                output.push_str(&format!("- {} - {}\n", activity.category, activity.summary));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.delivered_value.is_empty() {
-            output.push_str("**Valeur livrée**:\n");
-            for value in &project.outcomes.delivered_value {
-                output.push_str(&format!("- {value}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.technical_improvements.is_empty() {
-            output.push_str("**Améliorations techniques**:\n");
-            for improvement in &project.outcomes.technical_improvements {
-                output.push_str(&format!("- {improvement}\n"));
-            }
-            output.push('\n');
-        }
-
-        if !project.outcomes.unresolved_issues.is_empty() {
-            output.push_str("**Problèmes non résolus**:\n");
-            for issue in &project.outcomes.unresolved_issues {
-                output.push_str(&format!("- {issue}\n"));
-            }
-            output.push('\n');
-        }
-    }
-
-    // Add token usage summary if available
-    if let Some(ref tracker) = report.token_tracker {
-        output.push_str(&tracker.get_summary_string("fr"));
-    }
-}
@@ -1,5 +1,12 @@
+use crate::i18n::Catalog;
+use crate::logger;
+use crate::smart_analyzer::SmartReport;
+use crate::work_time::format_minutes;
+use chrono::DateTime;
+use minijinja::{context, Environment, Error, ErrorKind, Value};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::fs;
 use std::path::Path;
 
@@ -16,8 +23,19 @@ pub struct PromptTemplates {
     pub language_instructions: HashMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// MiniJinja templates of the Markdown report
+///
+/// They see the report as serialized in the JSON output (`date`, `summary`,
+/// `projects`, `timeline`, `active_time`, ...) plus `lang`, the rendered
+/// `token_summary` and `api_summary` sections (empty when not shown), and
+/// `t("key")` looks up a message of the report language. The partials can be
+/// included by name from the document, e.g. `{% include "project_section" %}`
+/// inside a `{% for project in projects %}` loop.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ReportTemplates {
+    /// Whole report; when set it replaces the built-in layout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<String>,
     pub header: String,
     pub summary_section: String,
     pub project_section: String,
@@ -32,7 +50,7 @@ impl Default for Templates {
                                 Analyze the conversation and provide a structured JSON summary focusing on what was actually accomplished, \
                                 not just what tools were used. Respond with valid JSON only, without markdown code blocks or any other formatting. \
                                 {language_instruction}".to_string(),
-                user_prompt: "{instructions}\n\n{structure}\n\nProject: {project_name}\nUser Messages:\n{user_messages}\n\n\
+                user_prompt: "{instructions}\n\n{structure}\n\nProject: {project_name}\n{session_summaries}{commits}User Messages:\n{user_messages}\n\n\
                              Assistant Actions:\n{assistant_actions}\n\nFocus on:\n1. What the user was trying to accomplish (intent)\n\
                              2. What was actually achieved (results)\n3. Any problems encountered\n4. The business/technical value delivered\n\n\
                              Important rules:\n- Combine activities of the same category into a single entry\n\
@@ -40,18 +58,157 @@ impl Default for Templates {
                 language_instructions: HashMap::new(),
             },
             report: ReportTemplates {
-                header: "# {title} - {date}\n\n".to_string(),
-                summary_section: "## {section_title}\n\n- **{total_items_label}**: {total_items}\n\
-                                 - **{completion_label}**: {completion_rate}%\n- **{focus_areas_label}**:\n{focus_areas}\n\n\
-                                 **{analysis_label}**: {analysis}\n\n".to_string(),
-                project_section: "### {index}. {title}\n\n**{purpose_label}**: {purpose}\n\n".to_string(),
-                activity_item: "- {category} - {description}\n".to_string(),
+                document: None,
+                header: "# {{ t(\"report.title\") }} - {{ date }}\n\n".to_string(),
+                summary_section: "## {{ t(\"summary.heading\") }}\n\n\
+                                 - **{{ t(\"summary.work_items\") }}**: {{ summary.total_work_items }}\n\
+                                 - **{{ t(\"summary.completion_rate\") }}**: {{ summary.completion_rate | round | int }}%\n\
+                                 {% if summary.key_focus_areas %}- **{{ t(\"summary.focus_areas\") }}**: {{ summary.key_focus_areas | join(\", \") }}\n{% endif %}\n\
+                                 {% if active_time %}**{{ t(\"active_time.heading\") }}**: {{ active_time.minutes | minutes }}\n\n{% endif %}".to_string(),
+                project_section: include_str!("../templates/project_section.md.j2").to_string(),
+                activity_item: "- {{ activity.category }} - {{ activity.summary }}\n".to_string(),
             },
         }
     }
 }
 
+/// Built-in layout: the partials, then working time, timeline and API usage
+const DEFAULT_DOCUMENT: &str = include_str!("../templates/document.md.j2");
+
+impl ReportTemplates {
+    /// Replace partials still written with the `{placeholder}` syntax of
+    /// earlier versions by the defaults, with a warning; they would otherwise
+    /// print the placeholders literally
+    pub fn replace_legacy_partials(&mut self) {
+        let defaults = Templates::default().report;
+        for (name, partial, default) in [
+            ("header", &mut self.header, defaults.header),
+            (
+                "summary_section",
+                &mut self.summary_section,
+                defaults.summary_section,
+            ),
+            (
+                "project_section",
+                &mut self.project_section,
+                defaults.project_section,
+            ),
+            (
+                "activity_item",
+                &mut self.activity_item,
+                defaults.activity_item,
+            ),
+        ] {
+            if let Some(placeholder) = legacy_placeholder(partial) {
+                logger::warn(&format!(
+                    "Template [report] {name} uses the old placeholder {{{placeholder}}}; \
+                     using the default. Report templates are MiniJinja now, e.g. {{{{ date }}}}"
+                ));
+                *partial = default;
+            }
+        }
+    }
+
+    /// Render the report with `document` (or the configured document) as the
+    /// layout and the partials available to `{% include %}`
+    pub fn render(
+        &self,
+        document: Option<&str>,
+        report: &SmartReport,
+        lang: &str,
+    ) -> Result<String, Error> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.add_filter("minutes", |minutes: i64| format_minutes(minutes));
+        env.add_filter("time", format_time);
+        let catalog = Catalog::new(lang);
+        env.add_function("t", move |key: &str| catalog.get(key).to_string());
+        env.add_template("header", &self.header)?;
+        env.add_template("summary_section", &self.summary_section)?;
+        env.add_template("project_section", &self.project_section)?;
+        env.add_template("activity_item", &self.activity_item)?;
+        let document = document
+            .or(self.document.as_deref())
+            .unwrap_or(DEFAULT_DOCUMENT);
+        env.add_template("document", document)?;
+
+        let token_summary = report
+            .token_tracker
+            .as_ref()
+            .map(|tracker| tracker.get_summary_string(lang))
+            .unwrap_or_default();
+        // Retries and failed projects, so a partial report is not mistaken for a complete one
+        let api_summary = report
+            .api_stats
+            .as_ref()
+            .filter(|stats| stats.has_issues())
+            .map(|stats| stats.get_summary_string(lang))
+            .unwrap_or_default();
+
+        env.get_template("document")?.render(context! {
+            lang,
+            token_summary,
+            api_summary,
+            ..Value::from_serialize(report)
+        })
+    }
+}
+
+/// The first `{name}` of the pre-MiniJinja template syntax in `template`
+fn legacy_placeholder(template: &str) -> Option<&str> {
+    let bytes = template.as_bytes();
+    template.match_indices('{').find_map(|(index, _)| {
+        if index > 0 && bytes[index - 1] == b'{' {
+            return None;
+        }
+        let rest = &template[index + 1..];
+        let name_length = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        (name_length > 0 && rest[name_length..].starts_with('}')).then(|| &rest[..name_length])
+    })
+}
+
+/// `{{ session.start | time }}` or `{{ session.start | time("%m-%d %H:%M") }}`
+/// for the RFC 3339 timestamps of the report
+fn format_time(timestamp: &str, format: Option<&str>) -> Result<String, Error> {
+    let time = DateTime::parse_from_rfc3339(timestamp).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("not a timestamp: {timestamp} ({e})"),
+        )
+    })?;
+    let mut formatted = String::new();
+    write!(formatted, "{}", time.format(format.unwrap_or("%H:%M"))).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("invalid time format: {}", format.unwrap_or_default()),
+        )
+    })?;
+    Ok(formatted)
+}
+
 impl Templates {
+    /// Templates from `cc2report.toml` or `~/.config/cc2report/templates.toml`,
+    /// merged with the defaults
+    pub fn load() -> Self {
+        if let Some(template_path) = get_template_path() {
+            match Templates::load_from_file(&template_path) {
+                Ok(t) => {
+                    let mut templates = Templates::merge_with_defaults(Some(t));
+                    templates.report.replace_legacy_partials();
+                    templates
+                }
+                Err(e) => {
+                    eprintln!("Template loading error: {e}. Using defaults.");
+                    Templates::default()
+                }
+            }
+        } else {
+            Templates::default()
+        }
+    }
+
     pub fn load_from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
         let content = fs::read_to_string(path)?;
         let templates: Templates = toml::from_str(&content)?;
@@ -0,0 +1,227 @@
+//! Day and week buckets of topics for multi-day reports
+//!
+//! A project summary says what was done over the whole period; the timeline
+//! says when. Every topic is placed on the calendar day it started in the
+//! report time zone, then days are merged into ISO weeks when requested.
+
+use crate::conversation_analyzer::{Topic, TopicOutcome};
+use crate::date_filter::{DateFilter, ReportTimeZone};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the report is divided: by project only, or also into days or weeks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    #[default]
+    Project,
+    Day,
+    Week,
+}
+
+impl GroupBy {
+    /// Default for a period: days for up to a week, weeks for anything longer
+    ///
+    /// A bound the filter leaves open (`--since` alone, or all time) is taken
+    /// from the first or last day of activity in `timeline`.
+    pub fn for_period(filter: &DateFilter, timeline: &[TimelineBucket]) -> Self {
+        let first_day = || {
+            timeline
+                .first()
+                .and_then(|bucket| bucket.start.parse().ok())
+        };
+        let last_day = || timeline.last().and_then(|bucket| bucket.end.parse().ok());
+        let (Some(from), Some(to)) = (filter.from.or_else(first_day), filter.to.or_else(last_day))
+        else {
+            return Self::Project;
+        };
+        match (to - from).num_days() + 1 {
+            ..=1 => Self::Project,
+            2..=7 => Self::Day,
+            _ => Self::Week,
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "project" => Ok(Self::Project),
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            _ => Err(format!(
+                "Unknown grouping '{value}' (expected day, week or project)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Project => write!(f, "project"),
+            Self::Day => write!(f, "day"),
+            Self::Week => write!(f, "week"),
+        }
+    }
+}
+
+/// Outcome of a topic without the rule-based explanation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineStatus {
+    Completed,
+    Partial,
+    Failed,
+    InProgress,
+}
+
+impl TimelineStatus {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Completed => "✅",
+            Self::Partial => "⚠️",
+            Self::Failed => "❌",
+            Self::InProgress => "⏳",
+        }
+    }
+}
+
+impl From<&TopicOutcome> for TimelineStatus {
+    fn from(outcome: &TopicOutcome) -> Self {
+        match outcome {
+            TopicOutcome::Completed(_) => Self::Completed,
+            TopicOutcome::PartiallyCompleted(_) => Self::Partial,
+            TopicOutcome::Failed(_) => Self::Failed,
+            TopicOutcome::InProgress => Self::InProgress,
+        }
+    }
+}
+
+/// One topic on the timeline; times are RFC 3339 in the report time zone
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    pub project: String,
+    pub title: String,
+    pub status: TimelineStatus,
+    pub started_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    /// Commits made during the topic (`abc1234 Subject`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub commits: Vec<String>,
+}
+
+impl TimelineEntry {
+    /// Start and end as `HH:MM–HH:MM`, prefixed with the date unless the
+    /// bucket is a single day
+    pub fn time_range(&self, with_date: bool) -> String {
+        let Some(start) = parse_local(&self.started_at) else {
+            return self.started_at.clone();
+        };
+        let start_format = if with_date { "%m-%d %H:%M" } else { "%H:%M" };
+        let mut range = start.format(start_format).to_string();
+        if let Some(end) = self.completed_at.as_deref().and_then(parse_local) {
+            let end_format = if end.date_naive() == start.date_naive() {
+                "%H:%M"
+            } else {
+                "%m-%d %H:%M"
+            };
+            range.push_str(&format!("–{}", end.format(end_format)));
+        }
+        range
+    }
+
+    fn date(&self) -> Option<NaiveDate> {
+        parse_local(&self.started_at).map(|start| start.date_naive())
+    }
+}
+
+/// Topics of one day or one ISO week, oldest first
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineBucket {
+    /// First day of the bucket (`YYYY-MM-DD`)
+    pub start: String,
+    /// Last day of the bucket; equal to `start` for a day
+    pub end: String,
+    pub entries: Vec<TimelineEntry>,
+}
+
+impl TimelineBucket {
+    pub fn is_day(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// `2025-06-29` for a day, `2025-06-23 – 2025-06-29` for a week
+    pub fn label(&self) -> String {
+        if self.is_day() {
+            self.start.clone()
+        } else {
+            format!("{} – {}", self.start, self.end)
+        }
+    }
+}
+
+/// Timeline entries for the topics of a project, converted to the report time zone
+pub fn entries_for(project: &str, topics: &[Topic], tz: ReportTimeZone) -> Vec<TimelineEntry> {
+    let local = |timestamp: &str| {
+        DateTime::parse_from_rfc3339(timestamp)
+            .ok()
+            .map(|dt| tz.to_local(dt.with_timezone(&Utc)).to_rfc3339())
+    };
+
+    topics
+        .iter()
+        .filter_map(|topic| {
+            Some(TimelineEntry {
+                project: project.to_string(),
+                title: topic.title.clone(),
+                status: TimelineStatus::from(&topic.outcome),
+                started_at: local(&topic.started_at)?,
+                completed_at: topic.completed_at.as_deref().and_then(local),
+                commits: topic.commits.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Sort entries into day or week buckets; grouping by project yields no buckets
+pub fn group(entries: Vec<TimelineEntry>, group_by: GroupBy) -> Vec<TimelineBucket> {
+    let mut buckets: BTreeMap<NaiveDate, Vec<TimelineEntry>> = BTreeMap::new();
+    for entry in entries {
+        let Some(date) = entry.date() else {
+            continue;
+        };
+        let start = match group_by {
+            GroupBy::Project => continue,
+            GroupBy::Day => date,
+            GroupBy::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+        };
+        buckets.entry(start).or_default().push(entry);
+    }
+
+    buckets
+        .into_iter()
+        .map(|(start, mut entries)| {
+            entries.sort_by_cached_key(|entry| parse_local(&entry.started_at));
+            let end = match group_by {
+                GroupBy::Week => start + Duration::days(6),
+                _ => start,
+            };
+            TimelineBucket {
+                start: start.format("%Y-%m-%d").to_string(),
+                end: end.format("%Y-%m-%d").to_string(),
+                entries,
+            }
+        })
+        .collect()
+}
+
+fn parse_local(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(timestamp).ok()
+}
@@ -0,0 +1,231 @@
+//! Timesheet export of the report's blocks of work
+//!
+//! Every block of active work of every project (see `work_time`) becomes one
+//! CSV row or one iCalendar event with its start, end, active minutes and a
+//! one-line summary of the sessions it belongs to, ready to import into time
+//! tracking tools or a calendar. A session left open over lunch is two blocks,
+//! so the pause is not billed.
+
+use crate::i18n::Catalog;
+use crate::session::SessionSummary;
+use crate::smart_analyzer::{SmartProjectSummary, SmartReport};
+use crate::work_time::WorkBlock;
+use chrono::{DateTime, FixedOffset, Utc};
+
+const CSV_HEADER: &str = "project,session_id,start,end,active_minutes,summary";
+
+/// Lines of an iCalendar file are folded after this many octets (RFC 5545)
+const ICS_LINE_OCTETS: usize = 75;
+
+/// A block of work of one project and the sessions active during it
+struct Row<'a> {
+    project: &'a SmartProjectSummary,
+    block: &'a WorkBlock,
+    sessions: Vec<&'a SessionSummary>,
+}
+
+impl Row<'_> {
+    /// Ids of the block's sessions, separated by spaces
+    fn session_ids(&self) -> String {
+        let ids: Vec<&str> = self
+            .sessions
+            .iter()
+            .map(|session| session.session_id.as_str())
+            .collect();
+        ids.join(" ")
+    }
+
+    /// The summaries of the block's sessions on one line, or the project title without any
+    fn summary(&self) -> String {
+        let mut summaries: Vec<String> = Vec::new();
+        for session in &self.sessions {
+            let summary = session
+                .summary
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !summary.is_empty() && !summaries.contains(&summary) {
+                summaries.push(summary);
+            }
+        }
+        if summaries.is_empty() {
+            self.project
+                .title
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        } else {
+            summaries.join("; ")
+        }
+    }
+}
+
+/// Blocks of work of all projects, oldest first
+fn rows(report: &SmartReport) -> Vec<Row<'_>> {
+    let mut rows: Vec<Row> = report
+        .projects
+        .iter()
+        .flat_map(|project| {
+            project
+                .active_time
+                .iter()
+                .flat_map(|active| &active.work_blocks)
+                .map(move |block| Row {
+                    project,
+                    block,
+                    sessions: project
+                        .sessions
+                        .iter()
+                        .filter(|session| overlaps(session, block))
+                        .collect(),
+                })
+        })
+        .collect();
+    rows.sort_by_key(|row| parse(&row.block.start));
+    rows
+}
+
+fn overlaps(session: &SessionSummary, block: &WorkBlock) -> bool {
+    match (
+        parse(&session.start),
+        parse(&session.end),
+        parse(&block.start),
+        parse(&block.end),
+    ) {
+        (Some(session_start), Some(session_end), Some(block_start), Some(block_end)) => {
+            session_start <= block_end && block_start <= session_end
+        }
+        _ => false,
+    }
+}
+
+/// CSV timesheet (RFC 4180), times in the report time zone
+pub fn to_csv(report: &SmartReport) -> String {
+    let mut output = format!("{CSV_HEADER}\r\n");
+    for row in rows(report) {
+        let time = |timestamp: &str| {
+            parse(timestamp).map_or_else(
+                || timestamp.to_string(),
+                |t| t.format("%Y-%m-%d %H:%M").to_string(),
+            )
+        };
+        let fields = [
+            row.project.project.clone(),
+            row.session_ids(),
+            time(&row.block.start),
+            time(&row.block.end),
+            row.block.minutes.to_string(),
+            row.summary(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        output.push_str(&row.join(","));
+        output.push_str("\r\n");
+    }
+    output
+}
+
+/// Quote a field if needed, and defuse values a spreadsheet would run as a formula
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// iCalendar timesheet (RFC 5545), one event per block of work in UTC
+pub fn to_ics(report: &SmartReport, lang: &str) -> String {
+    let catalog = Catalog::new(lang);
+    let stamp = ics_time(Utc::now());
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//cc2report//Timesheet//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for row in rows(report) {
+        let (Some(start), Some(end)) = (parse(&row.block.start), parse(&row.block.end)) else {
+            continue;
+        };
+        let start = ics_time(start.with_timezone(&Utc));
+        let owner = row
+            .sessions
+            .first()
+            .map_or(row.project.project.as_str(), |session| &session.session_id);
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", ics_text(&format!("{owner}-{start}@cc2report"))),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART:{start}"),
+            format!("DTEND:{}", ics_time(end.with_timezone(&Utc))),
+            format!(
+                "SUMMARY:{}",
+                ics_text(&format!("{}: {}", row.project.project, row.summary()))
+            ),
+            format!(
+                "DESCRIPTION:{}",
+                ics_text(&format!(
+                    "{}\n{} {}, {} {}",
+                    row.project.title,
+                    row.block.minutes,
+                    catalog.get("sessions.minutes"),
+                    row.block.messages,
+                    catalog.get("sessions.messages")
+                ))
+            ),
+            format!("CATEGORIES:{}", ics_text(&row.project.project)),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut output = String::new();
+    for line in lines {
+        output.push_str(&fold(&line));
+        output.push_str("\r\n");
+    }
+    output
+}
+
+fn ics_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a TEXT value
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Split a content line into 75-octet pieces, continued lines starting with a
+/// space, without breaking a UTF-8 character
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+    let mut limit = ICS_LINE_OCTETS;
+    for c in line.chars() {
+        let width = c.len_utf8();
+        if octets + width > limit {
+            folded.push_str("\r\n ");
+            octets = 0;
+            // Continuation lines lose one octet to the leading space
+            limit = ICS_LINE_OCTETS - 1;
+        }
+        folded.push(c);
+        octets += width;
+    }
+    folded
+}
+
+fn parse(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(timestamp).ok()
+}
@@ -1,3 +1,4 @@
+use crate::i18n::Catalog;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -79,118 +80,59 @@ impl TokenTracker {
     }
 
     pub fn get_summary_string(&self, lang: &str) -> String {
+        let catalog = Catalog::new(lang);
+        let label = |key: &str| catalog.get_in("tokens", key).to_string();
         let mut summary = String::new();
 
-        match lang {
-            "ja" => {
-                summary.push_str("\n## トークン使用状況\n\n");
-                summary.push_str("### 総使用量\n");
-                summary.push_str(&format!(
-                    "- 入力トークン: {}\n",
-                    self.total_usage.prompt_tokens
-                ));
-                summary.push_str(&format!(
-                    "- 出力トークン: {}\n",
-                    self.total_usage.completion_tokens
-                ));
-                summary.push_str(&format!(
-                    "- 合計トークン: {}\n\n",
-                    self.total_usage.total_tokens
-                ));
-
-                if let Some(ref cost) = self.cost_estimate {
-                    summary.push_str(&format!("### コスト見積もり ({})\n", cost.model));
-                    summary.push_str(&format!(
-                        "- 入力コスト: ${:.4} (${:.2}/1K tokens)\n",
-                        (self.total_usage.prompt_tokens as f64 / 1000.0) * cost.input_cost_per_1k,
-                        cost.input_cost_per_1k
-                    ));
-                    summary.push_str(&format!(
-                        "- 出力コスト: ${:.4} (${:.2}/1K tokens)\n",
-                        (self.total_usage.completion_tokens as f64 / 1000.0)
-                            * cost.output_cost_per_1k,
-                        cost.output_cost_per_1k
-                    ));
-                    summary.push_str(&format!("- **合計コスト: ${:.4}**\n\n", cost.total_cost));
-                }
-
-                if !self.project_usage.is_empty() {
-                    summary.push_str("### プロジェクト別使用量\n");
-                    let mut projects: Vec<_> = self.project_usage.iter().collect();
-                    projects.sort_by(|a, b| b.1.total_tokens.cmp(&a.1.total_tokens));
-
-                    for (project, usage) in projects.iter().take(10) {
-                        summary
-                            .push_str(&format!("- {}: {} tokens\n", project, usage.total_tokens));
-                    }
-                }
-            }
-            "zh" => {
-                summary.push_str("\n## 令牌使用情况\n\n");
-                summary.push_str("### 总使用量\n");
-                summary.push_str(&format!("- 输入令牌: {}\n", self.total_usage.prompt_tokens));
-                summary.push_str(&format!(
-                    "- 输出令牌: {}\n",
-                    self.total_usage.completion_tokens
-                ));
-                summary.push_str(&format!("- 总令牌: {}\n\n", self.total_usage.total_tokens));
-
-                if let Some(ref cost) = self.cost_estimate {
-                    summary.push_str(&format!("### 成本估算 ({})\n", cost.model));
-                    summary.push_str(&format!(
-                        "- 输入成本: ${:.4}\n",
-                        (self.total_usage.prompt_tokens as f64 / 1000.0) * cost.input_cost_per_1k
-                    ));
-                    summary.push_str(&format!(
-                        "- 输出成本: ${:.4}\n",
-                        (self.total_usage.completion_tokens as f64 / 1000.0)
-                            * cost.output_cost_per_1k
-                    ));
-                    summary.push_str(&format!("- **总成本: ${:.4}**\n", cost.total_cost));
-                }
-            }
-            _ => {
-                summary.push_str("\n## Token Usage Summary\n\n");
-                summary.push_str("### Total Usage\n");
-                summary.push_str(&format!(
-                    "- Input tokens: {}\n",
-                    self.total_usage.prompt_tokens
-                ));
-                summary.push_str(&format!(
-                    "- Output tokens: {}\n",
-                    self.total_usage.completion_tokens
-                ));
-                summary.push_str(&format!(
-                    "- Total tokens: {}\n\n",
-                    self.total_usage.total_tokens
-                ));
-
-                if let Some(ref cost) = self.cost_estimate {
-                    summary.push_str(&format!("### Cost Estimate ({})\n", cost.model));
-                    summary.push_str(&format!(
-                        "- Input cost: ${:.4} (${:.2}/1K tokens)\n",
-                        (self.total_usage.prompt_tokens as f64 / 1000.0) * cost.input_cost_per_1k,
-                        cost.input_cost_per_1k
-                    ));
-                    summary.push_str(&format!(
-                        "- Output cost: ${:.4} (${:.2}/1K tokens)\n",
-                        (self.total_usage.completion_tokens as f64 / 1000.0)
-                            * cost.output_cost_per_1k,
-                        cost.output_cost_per_1k
-                    ));
-                    summary.push_str(&format!("- **Total cost: ${:.4}**\n\n", cost.total_cost));
-                }
-
-                if !self.project_usage.is_empty() {
-                    summary.push_str("### Usage by Project\n");
-                    let mut projects: Vec<_> = self.project_usage.iter().collect();
-                    projects.sort_by(|a, b| b.1.total_tokens.cmp(&a.1.total_tokens));
-
-                    for (project, usage) in projects.iter().take(10) {
-                        summary
-                            .push_str(&format!("- {}: {} tokens\n", project, usage.total_tokens));
-                    }
-                }
+        summary.push_str(&format!("\n## {}\n\n", label("heading")));
+        summary.push_str(&format!("### {}\n", label("total_usage")));
+        summary.push_str(&format!(
+            "- {}: {}\n",
+            label("input"),
+            self.total_usage.prompt_tokens
+        ));
+        summary.push_str(&format!(
+            "- {}: {}\n",
+            label("output"),
+            self.total_usage.completion_tokens
+        ));
+        summary.push_str(&format!(
+            "- {}: {}\n\n",
+            label("total"),
+            self.total_usage.total_tokens
+        ));
+
+        if let Some(ref cost) = self.cost_estimate {
+            summary.push_str(&format!(
+                "### {}\n",
+                catalog.format("tokens.cost_estimate", &[("model", &cost.model)])
+            ));
+            summary.push_str(&format!(
+                "- {}: ${:.4} (${:.2}/1K tokens)\n",
+                label("input_cost"),
+                (self.total_usage.prompt_tokens as f64 / 1000.0) * cost.input_cost_per_1k,
+                cost.input_cost_per_1k
+            ));
+            summary.push_str(&format!(
+                "- {}: ${:.4} (${:.2}/1K tokens)\n",
+                label("output_cost"),
+                (self.total_usage.completion_tokens as f64 / 1000.0) * cost.output_cost_per_1k,
+                cost.output_cost_per_1k
+            ));
+            summary.push_str(&format!(
+                "- **{}: ${:.4}**\n\n",
+                label("total_cost"),
+                cost.total_cost
+            ));
+        }
+
+        if !self.project_usage.is_empty() {
+            summary.push_str(&format!("### {}\n", label("by_project")));
+            let mut projects: Vec<_> = self.project_usage.iter().collect();
+            projects.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.total_tokens));
+
+            for (project, usage) in projects.iter().take(10) {
+                summary.push_str(&format!("- {}: {} tokens\n", project, usage.total_tokens));
             }
         }
 
@@ -206,6 +148,9 @@ fn get_model_pricing(model: &str) -> (f64, f64) {
         "gpt-4o-mini" => (0.00015, 0.0006),               // $0.15/$0.60 per 1M
         "gpt-4-turbo" | "gpt-4-turbo-preview" => (0.01, 0.03), // $10/$30 per 1M
         "gpt-3.5-turbo" => (0.0005, 0.0015),              // $0.50/$1.50 per 1M
+        m if m.starts_with("claude") && m.contains("opus") => (0.015, 0.075), // $15/$75 per 1M
+        m if m.starts_with("claude") && m.contains("haiku") => (0.0008, 0.004), // $0.80/$4 per 1M
+        m if m.starts_with("claude") => (0.003, 0.015),   // Sonnet: $3/$15 per 1M
         _ => (0.0025, 0.01),                              // Default to gpt-4o pricing
     }
 }
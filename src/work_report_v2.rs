@@ -1,12 +1,27 @@
-use crate::conversation_analyzer::{ConversationFlow, Topic};
-use crate::intelligent_summary::create_intelligent_summary;
-use crate::parser::{LogEntry, LogRecord};
-use chrono::{DateTime, NaiveDate, Utc};
+use crate::commands::CommandTracker;
+use crate::conversation_analyzer::ConversationFlow;
+use crate::conversation_tree::ConversationTree;
+use crate::date_filter::DateFilter;
+use crate::file_ledger::FileLedger;
+use crate::git::{self, DiffStat};
+use crate::i18n::Catalog;
+use crate::intelligent_summary::{create_intelligent_summary, ProjectActivity};
+use crate::log_index::LogIndex;
+use crate::log_reader::{jsonl_files, scan_files};
+use crate::logger;
+use crate::parser::{LogEntry, SummaryEntry};
+use crate::project_filter::ProjectFilter;
+use crate::project_path;
+use crate::provider::LlmProvider;
+use crate::session::{summarize_sessions, SessionTracker};
+use crate::templates::Templates;
+use crate::timeline::{self, GroupBy, TimelineEntry};
+use crate::work_time::ActiveTime;
+use chrono::{DateTime, Duration, Utc};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(Default)]
 struct SessionMetrics {
@@ -21,17 +36,26 @@ impl SessionMetrics {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn analyze_conversations_with_ai(
     log_dir: &Path,
-    date_filter: Option<(Option<NaiveDate>, Option<NaiveDate>)>,
-    api_key: &str,
+    index_dir: &Path,
+    date_filter: DateFilter,
+    project_filter: &ProjectFilter,
+    provider: Option<Arc<dyn LlmProvider>>,
+    templates: &Templates,
     lang: &str,
-    model: &str,
     quiet: bool,
     parallel: usize,
+    per_session: bool,
+    idle_gap_minutes: i64,
+    all_authors: bool,
 ) -> Result<crate::smart_analyzer::SmartReport, Box<dyn std::error::Error>> {
-    let mut projects: HashMap<String, Vec<Topic>> = HashMap::new();
+    let mut projects: HashMap<String, ProjectActivity> = HashMap::new();
     let mut session_metrics = SessionMetrics::new();
+    let mut timeline_entries: Vec<TimelineEntry> = Vec::new();
+    let mut activity: Vec<DateTime<Utc>> = Vec::new();
+    let idle_gap = Duration::minutes(idle_gap_minutes);
 
     // Count directories for progress bar
     let dir_count = std::fs::read_dir(log_dir)?.count();
@@ -51,76 +75,176 @@ pub async fn analyze_conversations_with_ai(
         pb
     };
 
-    // 各プロジェクトディレクトリを処理
+    // プロジェクトディレクトリとそのJSONLファイルを列挙
+    let mut project_files = Vec::new();
     for entry in std::fs::read_dir(log_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-
+        let path = entry?.path();
         if path.is_dir() {
             let project_name = path
                 .file_name()
                 .map(|name| name.to_string_lossy().to_string())
                 .unwrap_or_else(|| "unknown-project".to_string());
-            let readable_name = project_name
-                .trim_start_matches('-')
-                .replace('-', "/")
-                .replace("/home/", "~/");
-
-            pb.set_message(format!("Processing: {readable_name}"));
-
-            let mut flow = ConversationFlow::new();
-
-            // プロジェクト内のすべてのJSONLファイルを処理
-            for jsonl_entry in std::fs::read_dir(&path)? {
-                let jsonl_entry = jsonl_entry?;
-                let jsonl_path = jsonl_entry.path();
-
-                if jsonl_path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                    process_conversation_file(
-                        &jsonl_path,
-                        &mut flow,
-                        date_filter,
-                        &mut session_metrics,
-                    )?;
-                }
+            project_files.push((project_name, jsonl_files(&path)?));
+        } else {
+            pb.inc(1);
+        }
+    }
+
+    // すべてのファイルを並列に読み込み
+    let paths: Vec<PathBuf> = project_files
+        .iter()
+        .flat_map(|(_, files)| files.iter().cloned())
+        .collect();
+    let mut scans = match LogIndex::open(index_dir) {
+        Ok(mut index) => {
+            let scans = index.scan_files(&paths, date_filter);
+            if let Err(e) = index.save() {
+                logger::warn(&format!("Could not save the log index: {e}"));
             }
+            scans
+        }
+        Err(e) => {
+            logger::warn(&format!("Log index unavailable, reading all files: {e}"));
+            scan_files(&paths, date_filter)
+        }
+    }
+    .into_iter();
+
+    // 記録された cwd からリポジトリのルートを求め、同じルートのディレクトリをまとめる
+    // (worktree やサブディレクトリで起動したセッションも同じプロジェクトになる)
+    let mut roots: HashMap<PathBuf, usize> = HashMap::new();
+    let mut groups: Vec<(PathBuf, Vec<LogEntry>, Vec<SummaryEntry>)> = Vec::new();
+    for (project_name, files) in project_files {
+        let mut entries = Vec::new();
+        let mut summaries = Vec::new();
+        for scan in scans.by_ref().take(files.len()) {
+            let scan = scan?;
+            entries.extend(scan.entries);
+            // leaf_uuid で会話に紐付けるため、全ファイル読み込み後まで保持
+            summaries.extend(scan.summaries);
+        }
+
+        let root = project_path::resolve(
+            &project_name,
+            entries.iter().filter_map(|entry| entry.cwd.as_deref()),
+        );
+        let index = *roots.entry(root.clone()).or_insert_with(|| {
+            groups.push((root, Vec::new(), Vec::new()));
+            groups.len() - 1
+        });
+        groups[index].1.extend(entries);
+        groups[index].2.extend(summaries);
+        pb.inc(1);
+    }
 
-            flow.finalize();
+    for (root, entries, summaries) in groups {
+        let readable_name = project_path::display_name(&root);
+        pb.set_message(format!("Processing: {readable_name}"));
+
+        // --project / --exclude-project と設定ファイルの除外リスト
+        let cwds: HashSet<&str> = entries.iter().filter_map(|e| e.cwd.as_deref()).collect();
+        if !project_filter.matches(&readable_name, cwds) {
+            continue;
+        }
 
-            if !flow.topics.is_empty() {
-                projects.insert(readable_name, flow.topics);
+        // Abandoned branches were still paid for, so cost counts every entry
+        session_metrics.total_cost += entries
+            .iter()
+            .filter_map(|entry| entry.cost_usd)
+            .sum::<f64>();
+        // All branches took time to write, so active time also counts every entry
+        let timestamps: Vec<DateTime<Utc>> = entries
+            .iter()
+            .filter_map(|entry| entry.timestamp.as_deref().and_then(parse_timestamp))
+            .collect();
+
+        // セッションごとに会話ツリーを組み立て、有効なブランチだけを解析
+        let mut flow = ConversationFlow::with_language(lang);
+        let mut sessions = SessionTracker::new();
+        let mut ledger = FileLedger::new(&root);
+        let mut commands = CommandTracker::new();
+        for tree in build_session_trees(entries) {
+            for (entry, sidechain) in tree.walk() {
+                track_session_metrics(entry, &mut sessions, &mut session_metrics);
+                flow.set_session(entry.session_id.as_deref());
+                flow.process_entry(entry, sidechain);
+                ledger.record_entry(entry);
+                commands.record_entry(entry);
             }
         }
 
-        pb.inc(1);
+        flow.finalize();
+
+        // Summaries may precede or follow their conversation, even in another file
+        for summary in &summaries {
+            flow.attach_summary(&summary.leaf_uuid, &summary.summary);
+        }
+
+        if !flow.topics.is_empty() {
+            let sessions: Vec<_> = summarize_sessions(sessions.into_sessions(), &flow.topics)
+                .into_iter()
+                .map(|session| session.in_time_zone(date_filter.tz))
+                .collect();
+
+            // 期間内のコミットを取得し、その時点のトピックに紐付ける
+            let commits = repository_commits(&root, date_filter, all_authors);
+            git::attach_commits(&mut flow.topics, &sessions, &commits);
+
+            let active_time =
+                ActiveTime::from_timestamps(timestamps.iter().copied(), idle_gap, date_filter.tz);
+            activity.extend(timestamps);
+
+            timeline_entries.extend(timeline::entries_for(
+                &readable_name,
+                &flow.topics,
+                date_filter.tz,
+            ));
+            projects.insert(
+                readable_name,
+                ProjectActivity {
+                    topics: flow.topics,
+                    sessions,
+                    commits,
+                    files: ledger.into_files(),
+                    commands: commands.into_stats(),
+                    active_time,
+                },
+            );
+        }
     }
 
     pb.finish_with_message("Directory scan complete!");
 
-    // AI を使用してインテリジェントサマリーを生成
-    let date_str = format_date_range(date_filter);
+    // AI を使用してインテリジェントサマリーを生成（オフライン時はルールベース）
+    let catalog = Catalog::new(lang);
+    let date_str = date_filter.label(&catalog);
+    let use_ai = provider.is_some();
     let intelligent_report = create_intelligent_summary(
         projects,
         date_str,
-        true,
-        Some(api_key),
+        use_ai,
+        provider,
+        templates,
         lang,
-        model,
         quiet,
         parallel,
+        per_session,
     )
     .await?;
 
     // SmartReport 形式に変換
-    Ok(convert_intelligent_to_smart(
-        intelligent_report,
-        session_metrics,
-    ))
+    // 日ごとのタイムライン（週単位やプロジェクト単位への切り替えは group_timeline で行う）
+    let mut report = convert_intelligent_to_smart(intelligent_report, session_metrics, &catalog);
+    report.timeline = timeline::group(timeline_entries, GroupBy::Day);
+    // Projects worked on side by side count once in the total
+    report.active_time = ActiveTime::from_timestamps(activity, idle_gap, date_filter.tz);
+    Ok(report)
 }
 
 fn convert_intelligent_to_smart(
     intelligent: crate::intelligent_summary::IntelligentReport,
     metrics: SessionMetrics,
+    catalog: &Catalog,
 ) -> crate::smart_analyzer::SmartReport {
     use crate::smart_analyzer::*;
 
@@ -157,6 +281,7 @@ fn convert_intelligent_to_smart(
             };
 
             SmartProjectSummary {
+                project: proj.project,
                 title: proj.title,
                 objective: proj.purpose,
                 work_breakdown: WorkBreakdown {
@@ -189,6 +314,12 @@ fn convert_intelligent_to_smart(
                     },
                 },
                 outcomes,
+                sessions: proj.sessions,
+                diffstat: (!proj.commits.is_empty()).then(|| DiffStat::of(&proj.commits)),
+                commits: proj.commits,
+                files: proj.files,
+                commands: proj.commands,
+                active_time: proj.active_time,
             }
         })
         .collect();
@@ -199,24 +330,33 @@ fn convert_intelligent_to_smart(
             total_work_items: metrics.total_messages,
             completion_rate: 85.0, // Default estimate
             key_focus_areas: extract_key_focus_areas(&project_summaries),
-            productivity_insights: if metrics.total_cost > 0.0 {
-                format!(
-                    "{} Total cost: ${:.2}, Sessions: {}",
-                    intelligent.overall_insights,
-                    metrics.total_cost,
-                    metrics.total_sessions.len()
-                )
-            } else {
-                format!(
-                    "{} Sessions: {}, Messages: {}",
-                    intelligent.overall_insights,
-                    metrics.total_sessions.len(),
-                    metrics.total_messages
-                )
-            },
+            productivity_insights: format!(
+                "{} {}",
+                intelligent.overall_insights,
+                if metrics.total_cost > 0.0 {
+                    catalog.format(
+                        "analysis.cost_and_sessions",
+                        &[
+                            ("cost", &format!("{:.2}", metrics.total_cost)),
+                            ("sessions", &metrics.total_sessions.len()),
+                        ],
+                    )
+                } else {
+                    catalog.format(
+                        "analysis.sessions_and_messages",
+                        &[
+                            ("sessions", &metrics.total_sessions.len()),
+                            ("messages", &metrics.total_messages),
+                        ],
+                    )
+                }
+            ),
         },
         projects: project_summaries,
         token_tracker: intelligent.token_tracker,
+        api_stats: intelligent.api_stats,
+        timeline: Vec::new(),
+        active_time: None,
     }
 }
 
@@ -233,95 +373,59 @@ fn extract_key_focus_areas(projects: &[crate::smart_analyzer::SmartProjectSummar
     }
 
     let mut categories: Vec<_> = category_counts.into_iter().collect();
-    categories.sort_by(|a, b| b.1.cmp(&a.1));
+    categories.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
     categories.into_iter().take(3).map(|(cat, _)| cat).collect()
 }
 
-fn process_conversation_file(
-    path: &Path,
-    flow: &mut ConversationFlow,
-    date_filter: Option<(Option<NaiveDate>, Option<NaiveDate>)>,
-    metrics: &mut SessionMetrics,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
-        }
+/// Group entries by session and build one conversation tree per session, oldest session first
+fn build_session_trees(entries: Vec<LogEntry>) -> Vec<ConversationTree> {
+    let mut by_session: HashMap<Option<String>, Vec<LogEntry>> = HashMap::new();
+    for entry in entries {
+        by_session
+            .entry(entry.session_id.clone())
+            .or_default()
+            .push(entry);
+    }
 
-        match serde_json::from_str::<LogRecord>(&line) {
-            Ok(LogRecord::Entry(entry)) => {
-                if let Some(timestamp_str) = entry.timestamp.clone() {
-                    if let Some(timestamp) = parse_timestamp(&timestamp_str) {
-                        if !is_in_date_range(timestamp.date_naive(), date_filter) {
-                            continue;
-                        }
+    let mut sessions: Vec<Vec<LogEntry>> = by_session.into_values().collect();
+    sessions.sort_by_cached_key(|entries| {
+        entries
+            .iter()
+            .filter_map(|entry| entry.timestamp.as_deref().and_then(parse_timestamp))
+            .min()
+    });
 
-                        // Track metrics first
-                        if let Some(session_id) = &entry.session_id {
-                            metrics.total_sessions.insert(session_id.clone());
-                        }
-                        if let Some(cost) = entry.cost_usd {
-                            metrics.total_cost += cost;
-                        }
-                        if entry.entry_type == "user" || entry.entry_type == "assistant" {
-                            metrics.total_messages += 1;
-                        }
+    sessions.into_iter().map(ConversationTree::build).collect()
+}
 
-                        process_conversation_entry(entry, flow, &timestamp_str);
-                    }
-                }
-            }
-            Ok(LogRecord::Summary(_)) => {
-                // サマリーエントリはスキップ
-            }
-            Err(_) => {
-                // パースできないエントリはスキップ
-            }
-        }
+/// Count an entry of the active conversation towards the session and report totals
+fn track_session_metrics(
+    entry: &LogEntry,
+    sessions: &mut SessionTracker,
+    metrics: &mut SessionMetrics,
+) {
+    let is_message = entry.entry_type == "user" || entry.entry_type == "assistant";
+    if is_message {
+        metrics.total_messages += 1;
     }
 
-    Ok(())
-}
-
-fn process_conversation_entry(entry: LogEntry, flow: &mut ConversationFlow, timestamp: &str) {
-    match entry.entry_type.as_str() {
-        "user" => {
-            if let Some(message) = &entry.message {
-                if let Some(content) = extract_message_content(message) {
-                    flow.analyze_user_message(&content, timestamp);
-                }
-            }
-        }
-        "assistant" => {
-            if let Some(message) = &entry.message {
-                flow.analyze_assistant_response(message);
-            }
+    if let Some(session_id) = &entry.session_id {
+        metrics.total_sessions.insert(session_id.clone());
+        if let Some(timestamp) = entry.timestamp.as_deref().and_then(parse_timestamp) {
+            sessions.record(session_id, timestamp, is_message);
         }
-        _ => {}
     }
 }
 
-fn extract_message_content(message: &crate::parser::Message) -> Option<String> {
-    if let Some(content) = &message.content {
-        if let Some(text) = content.as_str() {
-            return Some(text.to_string());
-        } else if let Some(array) = content.as_array() {
-            for item in array {
-                if let Some(obj) = item.as_object() {
-                    if obj.get("type").and_then(|v| v.as_str()) == Some("text") {
-                        if let Some(text) = obj.get("content").and_then(|v| v.as_str()) {
-                            return Some(text.to_string());
-                        }
-                    }
-                }
-            }
-        }
+/// Commits of the period if `root` is a git repository; git errors only produce a warning
+fn repository_commits(root: &Path, date_filter: DateFilter, all_authors: bool) -> Vec<git::Commit> {
+    if !root.join(".git").exists() {
+        return Vec::new();
     }
-    None
+    git::commits_in(root, date_filter, all_authors).unwrap_or_else(|e| {
+        logger::warn(&format!("Skipping commits of {}: {e}", root.display()));
+        Vec::new()
+    })
 }
 
 fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
@@ -329,31 +433,3 @@ fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
         .ok()
         .map(|dt| dt.with_timezone(&Utc))
 }
-
-fn is_in_date_range(
-    date: NaiveDate,
-    filter: Option<(Option<NaiveDate>, Option<NaiveDate>)>,
-) -> bool {
-    if let Some((from_date, to_date)) = filter {
-        match (from_date, to_date) {
-            (Some(from), Some(to)) => date >= from && date <= to,
-            (Some(from), None) => date >= from,
-            (None, Some(to)) => date <= to,
-            (None, None) => true,
-        }
-    } else {
-        true
-    }
-}
-
-fn format_date_range(date_filter: Option<(Option<NaiveDate>, Option<NaiveDate>)>) -> String {
-    match date_filter {
-        Some((Some(from), Some(to))) if from == to => from.format("%Y-%m-%d").to_string(),
-        Some((Some(from), Some(to))) => {
-            format!("{} から {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d"))
-        }
-        Some((Some(from), None)) => format!("{} 以降", from.format("%Y-%m-%d")),
-        Some((None, Some(to))) => format!("{} まで", to.format("%Y-%m-%d")),
-        _ => "全期間".to_string(),
-    }
-}
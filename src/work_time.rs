@@ -0,0 +1,217 @@
+//! Active working time estimated from message timestamps
+//!
+//! Messages closer together than the idle gap belong to one block of work and
+//! a longer pause ends the block, so the sum of the blocks is the time actually
+//! spent at the keyboard. Every block ends one idle gap after its last message,
+//! the time spent reading the answer. A session's start-to-end span would also
+//! count lunch breaks and a laptop left open overnight.
+
+use crate::date_filter::ReportTimeZone;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Offset, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Pause after which the next message starts a new block of work
+pub const DEFAULT_IDLE_GAP_MINUTES: i64 = 15;
+
+/// Uninterrupted stretch of work, times in the report time zone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkBlock {
+    pub start: String,
+    pub end: String,
+    pub minutes: i64,
+    /// Log entries in the block
+    pub messages: usize,
+}
+
+/// Active time of one day in the report time zone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DailyActiveTime {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub minutes: i64,
+    pub first_activity: String,
+    pub last_activity: String,
+    pub blocks: usize,
+    pub longest_block_minutes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActiveTime {
+    pub minutes: i64,
+    pub blocks: usize,
+    pub first_activity: String,
+    pub last_activity: String,
+    pub longest_block: WorkBlock,
+    /// Every block, oldest first
+    pub work_blocks: Vec<WorkBlock>,
+    pub days: Vec<DailyActiveTime>,
+}
+
+impl ActiveTime {
+    /// Active time of the given timestamps, in any order; None without any
+    ///
+    /// Blocks are formed over all timestamps, so work past midnight stays one
+    /// block, and their minutes are then split at midnight in `tz` so that
+    /// every day gets its own hours.
+    pub fn from_timestamps(
+        timestamps: impl IntoIterator<Item = DateTime<Utc>>,
+        idle_gap: Duration,
+        tz: ReportTimeZone,
+    ) -> Option<Self> {
+        let mut timestamps: Vec<DateTime<Utc>> = timestamps.into_iter().collect();
+        timestamps.sort();
+        let blocks = blocks(&timestamps, idle_gap);
+
+        let mut by_day: BTreeMap<NaiveDate, Day> = BTreeMap::new();
+        for &timestamp in &timestamps {
+            let day = by_day.entry(tz.date_of(timestamp)).or_default();
+            day.first.get_or_insert(timestamp);
+            day.last = Some(timestamp);
+        }
+        for &(block_start, block_end, _) in &blocks {
+            for (from, to) in split_at_midnight(block_start, block_end, tz) {
+                let day = by_day.entry(tz.date_of(from)).or_default();
+                // Only the idle time after a block can reach a day without messages
+                day.first.get_or_insert(from);
+                day.last.get_or_insert(to);
+                day.seconds += (to - from).num_seconds();
+                day.longest_seconds = day.longest_seconds.max((to - from).num_seconds());
+                day.blocks += 1;
+            }
+        }
+
+        let local = |timestamp: DateTime<Utc>| tz.to_local(timestamp).to_rfc3339();
+        let work_blocks: Vec<WorkBlock> = blocks
+            .iter()
+            .map(|&(start, end, messages)| WorkBlock {
+                start: local(start),
+                end: local(end),
+                minutes: (end - start).num_minutes(),
+                messages,
+            })
+            .collect();
+        let longest_block = blocks
+            .iter()
+            .zip(&work_blocks)
+            .max_by_key(|((start, end, _), _)| (*end - *start, std::cmp::Reverse(*start)))?
+            .1
+            .clone();
+        let days: Vec<DailyActiveTime> = by_day
+            .into_iter()
+            .filter_map(|(date, day)| {
+                Some(DailyActiveTime {
+                    date: date.to_string(),
+                    minutes: day.seconds / 60,
+                    first_activity: local(day.first?),
+                    last_activity: local(day.last?),
+                    blocks: day.blocks,
+                    longest_block_minutes: day.longest_seconds / 60,
+                })
+            })
+            .collect();
+
+        Some(Self {
+            minutes: days.iter().map(|day| day.minutes).sum(),
+            blocks: blocks.len(),
+            first_activity: local(*timestamps.first()?),
+            last_activity: local(*timestamps.last()?),
+            longest_block,
+            work_blocks,
+            days,
+        })
+    }
+
+    /// "10:02–18:40", or with dates when the activity spans several days
+    pub fn span(&self) -> String {
+        time_range(&self.first_activity, &self.last_activity)
+    }
+}
+
+/// Activity of one calendar day while the blocks are split up
+#[derive(Default)]
+struct Day {
+    first: Option<DateTime<Utc>>,
+    last: Option<DateTime<Utc>>,
+    seconds: i64,
+    longest_seconds: i64,
+    blocks: usize,
+}
+
+/// Start, end and number of timestamps of each block of sorted timestamps
+///
+/// Every block, a lone message included, ends one idle gap after its last
+/// message. The next message is further away than the idle gap, otherwise it
+/// would belong to the same block, so this never overlaps the next block.
+fn blocks(
+    timestamps: &[DateTime<Utc>],
+    idle_gap: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>, usize)> {
+    let mut blocks: Vec<(DateTime<Utc>, DateTime<Utc>, usize)> = Vec::new();
+    for &timestamp in timestamps {
+        match blocks.last_mut() {
+            Some((_, end, count)) if timestamp - *end <= idle_gap => {
+                *end = timestamp;
+                *count += 1;
+            }
+            _ => blocks.push((timestamp, timestamp, 1)),
+        }
+    }
+    for (_, end, _) in &mut blocks {
+        *end += idle_gap;
+    }
+    blocks
+}
+
+/// `start..end` cut into one piece per calendar day in `tz`
+fn split_at_midnight(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    tz: ReportTimeZone,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut pieces = Vec::new();
+    let mut from = start;
+    while from < end {
+        let to = next_midnight(from, tz).min(end);
+        pieces.push((from, to));
+        from = to;
+    }
+    pieces
+}
+
+/// First instant after `timestamp` that falls on the next day in `tz`
+fn next_midnight(timestamp: DateTime<Utc>, tz: ReportTimeZone) -> DateTime<Utc> {
+    let midnight = tz
+        .date_of(timestamp)
+        .succ_opt()
+        .unwrap_or(NaiveDate::MAX)
+        .and_time(NaiveTime::MIN);
+    // The offset at midnight may differ from the current one across a DST change
+    let mut candidate = midnight - tz.to_local(timestamp).offset().fix();
+    candidate = midnight - tz.to_local(candidate.and_utc()).offset().fix();
+    candidate.and_utc().max(timestamp + Duration::seconds(1))
+}
+
+fn time_range(start: &str, end: &str) -> String {
+    let parse = |timestamp: &str| DateTime::parse_from_rfc3339(timestamp).ok();
+    match (parse(start), parse(end)) {
+        (Some(start), Some(end)) if start.date_naive() == end.date_naive() => {
+            format!("{}–{}", start.format("%H:%M"), end.format("%H:%M"))
+        }
+        (Some(start), Some(end)) => format!(
+            "{}–{}",
+            start.format("%m-%d %H:%M"),
+            end.format("%m-%d %H:%M")
+        ),
+        _ => format!("{start}–{end}"),
+    }
+}
+
+/// "3h 05m", or "45m" under an hour
+pub fn format_minutes(minutes: i64) -> String {
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
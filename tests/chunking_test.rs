@@ -0,0 +1,194 @@
+mod common;
+
+use cc2report::ai_analyzer::{analyze_with_ai, ConversationData};
+use cc2report::chunker::{
+    context_limit_for_model, conversation_tokens, estimate_tokens, split_conversation,
+    truncate_to_tokens, DEFAULT_CONTEXT_LIMIT,
+};
+use cc2report::config::{Config, ProviderKind};
+use cc2report::templates::Templates;
+use common::{analysis_json, isolate_home, openai_reply, StubResponse, StubServer};
+
+fn busy_conversation(name: &str, messages: usize) -> ConversationData {
+    ConversationData {
+        project_name: name.to_string(),
+        user_messages: (0..messages)
+            .map(|i| format!("Request {i}: {}", "refactor the parser ".repeat(20)))
+            .collect(),
+        assistant_actions: (0..messages)
+            .map(|i| format!("Edited src/parser_{i}.rs"))
+            .collect(),
+        timestamps: (0..messages)
+            .map(|i| format!("2025-06-29T10:{:02}:00.000Z", i % 60))
+            .collect(),
+        summaries: vec!["Refactored the parser module".to_string()],
+        commits: vec![],
+    }
+}
+
+#[test]
+fn test_estimate_tokens() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("abcd"), 1);
+    assert_eq!(estimate_tokens("abcde"), 2);
+    // CJK characters count as roughly one token each
+    assert_eq!(estimate_tokens("日本語"), 3);
+}
+
+#[test]
+fn test_context_limit_for_model() {
+    assert_eq!(context_limit_for_model("gpt-4o-mini"), 128_000);
+    assert_eq!(context_limit_for_model("gpt-3.5-turbo"), 16_385);
+    assert_eq!(context_limit_for_model("claude-sonnet-4-5"), 200_000);
+    assert_eq!(context_limit_for_model("llama3.1"), DEFAULT_CONTEXT_LIMIT);
+}
+
+#[test]
+fn test_small_conversation_is_not_split() {
+    let conv = busy_conversation("small", 2);
+    let chunks = split_conversation(&conv, 10_000);
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].user_messages, conv.user_messages);
+}
+
+#[test]
+fn test_split_respects_budget_and_order() {
+    let conv = busy_conversation("busy", 40);
+    let budget = 600;
+    assert!(conversation_tokens(&conv) > budget);
+
+    let chunks = split_conversation(&conv, budget);
+    assert!(chunks.len() > 1);
+    for chunk in &chunks {
+        assert!(conversation_tokens(chunk) <= budget, "chunk over budget");
+        assert_eq!(chunk.project_name, "busy");
+        assert_eq!(chunk.summaries, conv.summaries);
+    }
+
+    let users: Vec<String> = chunks
+        .iter()
+        .flat_map(|c| c.user_messages.clone())
+        .collect();
+    let actions: Vec<String> = chunks
+        .iter()
+        .flat_map(|c| c.assistant_actions.clone())
+        .collect();
+    let timestamps: Vec<String> = chunks.iter().flat_map(|c| c.timestamps.clone()).collect();
+    assert_eq!(users, conv.user_messages);
+    assert_eq!(actions, conv.assistant_actions);
+    assert_eq!(timestamps, conv.timestamps);
+}
+
+#[test]
+fn test_oversized_message_is_truncated() {
+    let conv = ConversationData {
+        project_name: "huge".to_string(),
+        user_messages: vec!["x".repeat(40_000)],
+        assistant_actions: vec![],
+        timestamps: vec![],
+        summaries: vec![],
+        commits: vec![],
+    };
+
+    let chunks = split_conversation(&conv, 1_000);
+    assert_eq!(chunks.len(), 1);
+    assert!(conversation_tokens(&chunks[0]) <= 1_000);
+    assert!(chunks[0].user_messages[0].ends_with('…'));
+
+    assert_eq!(truncate_to_tokens("短い", 10), "短い");
+    assert_eq!(truncate_to_tokens("日本語のテキスト", 3), "日本語…");
+}
+
+#[tokio::test]
+async fn test_map_reduce_over_small_context() {
+    isolate_home();
+    let server = StubServer::start(vec![StubResponse::json(
+        200,
+        openai_reply(&analysis_json("Merged Project")),
+    )]);
+
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::Ollama;
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+    config.api.context_limit = Some(2_048);
+
+    let project_name = format!("map-reduce-{}", std::process::id());
+    let conversations = vec![busy_conversation(&project_name, 30)];
+
+    let provider = cc2report::provider::create_provider(&config.api).unwrap();
+    let (results, tracker, stats) = analyze_with_ai(
+        provider,
+        &Templates::default(),
+        conversations,
+        "en",
+        true,
+        1,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results[&project_name].project_title, "Merged Project");
+
+    let requests = server.requests();
+    let map_requests = requests
+        .iter()
+        .filter(|r| {
+            r.json()["messages"][1]["content"]
+                .as_str()
+                .unwrap()
+                .contains("(This is part ")
+        })
+        .count();
+    let reduce_requests = requests.len() - map_requests;
+    assert!(map_requests > 1, "conversation should be split");
+    assert!(reduce_requests >= 1, "partials should be merged");
+    assert!(requests.last().unwrap().json()["messages"][1]["content"]
+        .as_str()
+        .unwrap()
+        .contains("Partial analyses"));
+
+    assert_eq!(stats.requests as usize, requests.len());
+    assert_eq!(
+        tracker.total_usage.total_tokens as usize,
+        150 * requests.len()
+    );
+}
+
+#[tokio::test]
+async fn test_failed_chunk_keeps_the_requests_made_before_it() {
+    isolate_home();
+    let server = StubServer::start(vec![
+        StubResponse::json(200, openai_reply(&analysis_json("First Part"))),
+        StubResponse::json(500, serde_json::json!({"error": {"message": "boom"}})),
+    ]);
+
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::Ollama;
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+    config.api.context_limit = Some(2_048);
+    config.api.max_retries = 1;
+    config.api.retry_delay_ms = 1;
+
+    let project_name = format!("failed-chunk-{}", std::process::id());
+    let conversations = vec![busy_conversation(&project_name, 30)];
+
+    let provider = cc2report::provider::create_provider(&config.api).unwrap();
+    let (results, tracker, stats) = analyze_with_ai(
+        provider,
+        &Templates::default(),
+        conversations,
+        "en",
+        true,
+        1,
+    )
+    .await
+    .unwrap();
+
+    // The first chunk succeeded; the second failed after one retry
+    assert!(results.is_empty());
+    assert_eq!(server.requests().len(), 3);
+    assert_eq!(stats.requests, 2);
+    assert_eq!(stats.retries, 1);
+    assert_eq!(stats.failures.len(), 1);
+    assert_eq!(tracker.total_usage.total_tokens, 150);
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use cc2report::commands::{classify, parse_test_counts, CommandKind, TestCounts};
+use cc2report::date_filter::DateFilter;
+use cc2report::log_index::LogIndex;
+use cc2report::project_filter::ProjectFilter;
+use cc2report::smart_analyzer::generate_smart_report;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use common::TempDir;
+use serde_json::{json, Value};
+
+/// Log lines of a conversation running `commands` in turn, each with its exit status and output
+fn session_log(commands: &[(&str, bool, &str)]) -> Vec<String> {
+    let mut lines = vec![json!({
+        "parentUuid": null, "sessionId": "s-1", "cwd": "/srv/app", "type": "user",
+        "message": {"role": "user", "content": "Make the tests pass"},
+        "uuid": "u-0", "timestamp": "2025-06-29T10:00:00.000Z"
+    })];
+    for (index, (command, is_error, stdout)) in commands.iter().enumerate() {
+        let id = format!("toolu_{index}");
+        lines.push(json!({
+            "parentUuid": format!("u-{index}"), "sessionId": "s-1", "cwd": "/srv/app",
+            "type": "assistant",
+            "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": id, "name": "Bash", "input": {"command": command}}
+            ]},
+            "uuid": format!("a-{index}"), "timestamp": format!("2025-06-29T10:{:02}:00.000Z", index * 2 + 1)
+        }));
+        lines.push(json!({
+            "parentUuid": format!("a-{index}"), "sessionId": "s-1", "cwd": "/srv/app",
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": id, "is_error": is_error, "content": stdout}
+            ]},
+            "toolUseResult": {"stdout": stdout, "stderr": "", "interrupted": false},
+            "uuid": format!("u-{}", index + 1), "timestamp": format!("2025-06-29T10:{:02}:00.000Z", index * 2 + 2)
+        }));
+    }
+    lines.iter().map(Value::to_string).collect()
+}
+
+#[test]
+fn test_commands_are_classified_across_ecosystems() {
+    use CommandKind::*;
+    let cases = [
+        ("cargo build --release", Some(Build)),
+        (
+            "cd crates/core && RUST_LOG=debug cargo test -p core",
+            Some(Test),
+        ),
+        ("cargo fmt && cargo clippy -- -D warnings", Some(Lint)),
+        ("npm run build", Some(Build)),
+        ("pnpm test -- --watch=false", Some(Test)),
+        ("npx eslint src/", Some(Lint)),
+        ("yarn run format:check", Some(Format)),
+        ("python -m pytest tests/ -x", Some(Test)),
+        ("go vet ./...", Some(Lint)),
+        ("go test ./... 2>&1 | tail -20", Some(Test)),
+        ("make", Some(Build)),
+        ("make lint", Some(Lint)),
+        ("make clean", None),
+        ("docker compose build web", Some(Build)),
+        ("docker push registry/app:1.2", Some(Deploy)),
+        ("git status", None),
+        ("ls -la", None),
+    ];
+    for (command, kind) in cases {
+        assert_eq!(classify(command), kind, "{command}");
+    }
+}
+
+#[test]
+fn test_runner_summaries_are_counted() {
+    let counts = |passed, failed, skipped| {
+        Some(TestCounts {
+            passed,
+            failed,
+            skipped,
+        })
+    };
+    let cargo = "running 3 tests\ntest a ... ok\n\ntest result: FAILED. 2 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s\n\n   Doc-tests app\ntest result: ok. 4 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out";
+    assert_eq!(parse_test_counts(cargo), counts(6, 1, 1));
+
+    let pytest = "tests/test_a.py ..F.s\n==== 1 failed, 3 passed, 1 skipped, 1 error in 0.42s ====";
+    assert_eq!(parse_test_counts(pytest), counts(3, 2, 1));
+
+    let jest =
+        "Test Suites: 1 failed, 2 passed, 3 total\nTests:       2 failed, 10 passed, 12 total";
+    assert_eq!(parse_test_counts(jest), counts(10, 2, 0));
+
+    let vitest = " Test Files  1 passed (1)\n      Tests  1 failed | 4 passed | 2 skipped (7)";
+    assert_eq!(parse_test_counts(vitest), counts(4, 1, 2));
+
+    let mocha = "  5 passing (20ms)\n  1 pending\n  2 failing";
+    assert_eq!(parse_test_counts(mocha), counts(5, 2, 1));
+
+    let go_verbose = "=== RUN   TestA\n--- PASS: TestA (0.00s)\n--- FAIL: TestB (0.00s)\nFAIL\nFAIL\texample.com/app\t0.01s";
+    assert_eq!(parse_test_counts(go_verbose), counts(1, 1, 0));
+
+    let go = "ok  \texample.com/app/a\t0.01s\nFAIL\texample.com/app/b\t0.02s";
+    assert_eq!(parse_test_counts(go), counts(1, 1, 0));
+
+    assert_eq!(parse_test_counts("Compiling app v0.1.0\nFinished"), None);
+}
+
+#[test]
+fn test_index_keeps_only_test_summaries_of_command_output() {
+    let root = TempDir::new("commands-index");
+    let noise = "Compiling dep v1.0.0\n".repeat(100);
+    let stdout = format!("{noise}test result: ok. 7 passed; 0 failed; 0 ignored");
+    let file = root.write_log("s.jsonl", &session_log(&[("cargo test", false, &stdout)]));
+
+    let mut index = LogIndex::open(&root.join("index")).unwrap();
+    let entries: Vec<_> = index
+        .scan_files(&[file], DateFilter::all())
+        .into_iter()
+        .flat_map(|scan| scan.unwrap().entries)
+        .collect();
+
+    let result = entries[2].tool_use_result.as_ref().unwrap();
+    assert_eq!(
+        result,
+        &json!({"stdout": "test result: ok. 7 passed; 0 failed; 0 ignored"})
+    );
+    assert!(entries[1].tool_use_result.is_none());
+}
+
+#[tokio::test]
+async fn test_report_shows_command_runs_and_test_health() {
+    let root = TempDir::new("commands-report");
+    let log = session_log(&[
+        ("cargo build", false, "Finished dev profile"),
+        (
+            "cargo test",
+            true,
+            "test result: FAILED. 8 passed; 2 failed; 0 ignored",
+        ),
+        ("cargo clippy", false, ""),
+        (
+            "cargo test",
+            false,
+            "test result: ok. 10 passed; 0 failed; 1 ignored",
+        ),
+        ("git status", false, "nothing to commit"),
+    ]);
+    root.write_log("logs/-srv-app/s.jsonl", &log);
+
+    let report = analyze_conversations_with_ai(
+        &root.join("logs"),
+        &root.join("index"),
+        DateFilter::all(),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let commands = &report.projects[0].commands;
+    assert_eq!(commands.runs[&CommandKind::Test].runs, 2);
+    assert_eq!(commands.runs[&CommandKind::Test].failed, 1);
+    assert_eq!(commands.runs.len(), 3);
+    let tests = commands.tests.as_ref().unwrap();
+    assert!(tests.passing);
+    assert_eq!(tests.last_counts.unwrap().passed, 10);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["projects"][0]["commands"]["runs"]["test"]["failed"], 1);
+
+    let output = root.join("report.md");
+    generate_smart_report(&report, Some(&output), "en").unwrap();
+    let markdown = std::fs::read_to_string(&output).unwrap();
+
+    assert!(markdown.contains("**Commands**: build 1 · test 2 (1 failed) · lint 1\n"));
+    assert!(markdown.contains(
+        "**Test health**: ✅ 2 runs, 1 failed · last run: 10 passed, 0 failed, 1 skipped\n"
+    ));
+}
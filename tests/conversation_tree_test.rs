@@ -0,0 +1,167 @@
+mod common;
+
+use cc2report::conversation_tree::ConversationTree;
+use cc2report::date_filter::DateFilter;
+use cc2report::parser::LogEntry;
+use cc2report::project_filter::ProjectFilter;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use common::TempDir;
+
+fn entry(uuid: &str, parent: Option<&str>, kind: &str, minute: u32, sidechain: bool) -> String {
+    let parent = parent.map_or("null".to_string(), |p| format!("\"{p}\""));
+    let content = if kind == "user" {
+        format!("\"Prompt {uuid}\"")
+    } else {
+        format!("[{{\"type\":\"text\",\"text\":\"Reply {uuid}\"}}]")
+    };
+    format!(
+        r#"{{"parentUuid":{parent},"isSidechain":{sidechain},"sessionId":"s-1","type":"{kind}","message":{{"role":"{kind}","content":{content}}},"uuid":"{uuid}","timestamp":"2025-06-29T10:{minute:02}:00.000Z"}}"#
+    )
+}
+
+fn parse(lines: &[String]) -> Vec<LogEntry> {
+    lines
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn uuids<'a>(entries: impl IntoIterator<Item = &'a LogEntry>) -> Vec<&'a str> {
+    entries
+        .into_iter()
+        .map(|entry| entry.uuid.as_deref().unwrap())
+        .collect()
+}
+
+/// u-1 → a-1 → u-2 → a-2, then u-2 is edited into u-3 → a-3
+fn edited_conversation() -> Vec<String> {
+    vec![
+        entry("u-1", None, "user", 0, false),
+        entry("a-1", Some("u-1"), "assistant", 1, false),
+        entry("u-2", Some("a-1"), "user", 2, false),
+        entry("a-2", Some("u-2"), "assistant", 3, false),
+        entry("u-3", Some("a-1"), "user", 4, false),
+        entry("a-3", Some("u-3"), "assistant", 5, false),
+    ]
+}
+
+#[test]
+fn test_active_branch_follows_latest_leaf() {
+    let tree = ConversationTree::build(parse(&edited_conversation()));
+
+    assert_eq!(uuids(tree.main_thread()), ["u-1", "a-1", "u-3", "a-3"]);
+    assert_eq!(tree.abandoned, 2);
+}
+
+#[test]
+fn test_edited_prompt_after_a_cut_off_parent_replaces_the_original() {
+    // a-0 lies outside the file (or the date range), so u-1 and u-2 are both roots
+    let lines = vec![
+        entry("u-1", Some("a-0"), "user", 0, false),
+        entry("a-1", Some("u-1"), "assistant", 1, false),
+        entry("u-2", Some("a-0"), "user", 2, false),
+        entry("a-2", Some("u-2"), "assistant", 3, false),
+    ];
+    let tree = ConversationTree::build(parse(&lines));
+
+    assert_eq!(uuids(tree.main_thread()), ["u-2", "a-2"]);
+    assert_eq!(tree.abandoned, 2);
+}
+
+/// The boundary Claude Code writes when it compacts a conversation
+fn compact_boundary(uuid: &str, logical_parent: &str, minute: u32) -> String {
+    format!(
+        r#"{{"parentUuid":null,"logicalParentUuid":"{logical_parent}","isSidechain":false,"sessionId":"s-1","type":"system","subtype":"compact_boundary","content":"Conversation compacted","uuid":"{uuid}","timestamp":"2025-06-29T10:{minute:02}:00.000Z"}}"#
+    )
+}
+
+#[test]
+fn test_compaction_keeps_the_work_before_it() {
+    let lines = vec![
+        entry("u-1", None, "user", 0, false),
+        entry("a-1", Some("u-1"), "assistant", 1, false),
+        compact_boundary("c-1", "a-1", 2),
+        entry("u-2", Some("c-1"), "user", 3, false),
+        entry("a-2", Some("u-2"), "assistant", 4, false),
+    ];
+    let tree = ConversationTree::build(parse(&lines));
+    assert_eq!(
+        uuids(tree.main_thread()),
+        ["u-1", "a-1", "c-1", "u-2", "a-2"]
+    );
+    assert_eq!(tree.abandoned, 0);
+
+    // Roots without a parent in the file are chained in time order, not replaced
+    let lines = vec![
+        entry("u-1", None, "user", 0, false),
+        entry("a-1", Some("u-1"), "assistant", 1, false),
+        compact_boundary("c-1", "elsewhere", 2),
+        entry("u-2", Some("c-1"), "user", 3, false),
+    ];
+    let tree = ConversationTree::build(parse(&lines));
+    assert_eq!(uuids(tree.main_thread()), ["u-1", "a-1", "c-1", "u-2"]);
+    assert_eq!(tree.abandoned, 0);
+}
+
+#[test]
+fn test_sidechains_are_separate_threads() {
+    let lines = vec![
+        entry("u-1", None, "user", 0, false),
+        entry("a-1", Some("u-1"), "assistant", 1, false),
+        entry("t-1", Some("a-1"), "user", 2, true),
+        entry("t-2", Some("t-1"), "assistant", 3, true),
+        entry("u-2", Some("a-1"), "user", 4, false),
+        entry("a-2", Some("u-2"), "assistant", 5, false),
+    ];
+    let tree = ConversationTree::build(parse(&lines));
+
+    assert_eq!(uuids(tree.main_thread()), ["u-1", "a-1", "u-2", "a-2"]);
+    let sidechains: Vec<_> = tree.sidechains().collect();
+    assert_eq!(sidechains.len(), 1);
+    assert_eq!(uuids(sidechains[0].iter().copied()), ["t-1", "t-2"]);
+    assert_eq!(tree.abandoned, 0);
+
+    let walk: Vec<_> = tree
+        .walk()
+        .into_iter()
+        .map(|(entry, sidechain)| (entry.uuid.as_deref().unwrap(), sidechain))
+        .collect();
+    assert_eq!(
+        walk,
+        [
+            ("u-1", false),
+            ("a-1", false),
+            ("t-1", true),
+            ("t-2", true),
+            ("u-2", false),
+            ("a-2", false),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_offline_report_skips_abandoned_branches() {
+    let dir = TempDir::new("tree");
+    dir.write_log("logs/-home-user-tree/s-1.jsonl", &edited_conversation());
+
+    let report = analyze_conversations_with_ai(
+        &dir.join("logs"),
+        &dir.join("index"),
+        DateFilter::all(),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(report.summary.total_work_items, 4);
+    assert_eq!(report.projects[0].sessions[0].message_count, 4);
+}
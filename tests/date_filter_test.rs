@@ -0,0 +1,235 @@
+mod common;
+
+use cc2report::cli;
+use cc2report::date_filter::{DateFilter, ReportTimeZone};
+use cc2report::project_filter::ProjectFilter;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use chrono::{DateTime, NaiveDate, Utc};
+use common::TempDir;
+use std::path::Path;
+
+fn at(timestamp: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+}
+
+fn zone(name: &str) -> ReportTimeZone {
+    name.parse().unwrap()
+}
+
+#[test]
+fn test_late_evening_work_counts_on_the_local_day() {
+    let tokyo = DateFilter::day(date(6, 29)).with_tz(zone("Asia/Tokyo"));
+    assert!(tokyo.contains(at("2025-06-28T15:00:00Z")));
+    assert!(tokyo.contains(at("2025-06-29T14:59:59Z")));
+    assert!(!tokyo.contains(at("2025-06-29T15:00:00Z")));
+
+    let utc = DateFilter::day(date(6, 29)).with_tz(zone("UTC"));
+    assert!(!utc.contains(at("2025-06-28T15:00:00Z")));
+    assert!(utc.contains(at("2025-06-29T15:00:00Z")));
+}
+
+#[test]
+fn test_spring_forward_day_is_23_hours() {
+    // New York skips 02:00–03:00 local time on 2025-03-09
+    let filter = DateFilter::day(date(3, 9)).with_tz(zone("America/New_York"));
+
+    assert!(!filter.contains(at("2025-03-09T04:59:59Z"))); // 23:59 EST on the 8th
+    assert!(filter.contains(at("2025-03-09T05:00:00Z"))); // 00:00 EST
+    assert!(filter.contains(at("2025-03-09T06:59:59Z"))); // 01:59 EST
+    assert!(filter.contains(at("2025-03-09T07:00:00Z"))); // 03:00 EDT
+    assert!(filter.contains(at("2025-03-10T03:59:59Z"))); // 23:59 EDT
+    assert!(!filter.contains(at("2025-03-10T04:00:00Z"))); // 00:00 EDT on the 10th
+}
+
+#[test]
+fn test_fall_back_day_is_25_hours() {
+    // New York repeats 01:00–02:00 local time on 2025-11-02
+    let filter = DateFilter::day(date(11, 2)).with_tz(zone("America/New_York"));
+
+    assert!(!filter.contains(at("2025-11-02T03:59:59Z"))); // 23:59 EDT on the 1st
+    assert!(filter.contains(at("2025-11-02T04:00:00Z"))); // 00:00 EDT
+    assert!(filter.contains(at("2025-11-02T05:30:00Z"))); // first 01:30 (EDT)
+    assert!(filter.contains(at("2025-11-02T06:30:00Z"))); // second 01:30 (EST)
+    assert!(filter.contains(at("2025-11-03T04:59:59Z"))); // 23:59 EST
+    assert!(!filter.contains(at("2025-11-03T05:00:00Z"))); // 00:00 EST on the 3rd
+
+    let local = zone("America/New_York").to_local(at("2025-11-02T06:30:00Z"));
+    assert_eq!(local.to_rfc3339(), "2025-11-02T01:30:00-05:00");
+}
+
+#[test]
+fn test_open_ended_ranges() {
+    let filter = DateFilter::range(Some(date(6, 29)), None).with_tz(zone("Asia/Tokyo"));
+    assert!(filter.is_before(at("2025-06-28T14:59:59Z")));
+    assert!(!filter.is_before(at("2025-06-28T15:00:00Z")));
+    assert!(!filter.is_after(at("2030-01-01T00:00:00Z")));
+    assert!(DateFilter::all().contains(at("1999-12-31T23:59:59Z")));
+}
+
+#[test]
+fn test_tz_option() {
+    let matches = cli::build_cli().get_matches_from(["cc2report", "--tz", "Europe/Berlin"]);
+    let tz = matches.get_one::<ReportTimeZone>("tz").unwrap();
+    assert_eq!(tz.to_string(), "Europe/Berlin");
+
+    assert_eq!(zone("local"), ReportTimeZone::Local);
+    assert!("Mars/Olympus_Mons".parse::<ReportTimeZone>().is_err());
+    assert!(cli::build_cli()
+        .try_get_matches_from(["cc2report", "--tz", "Nowhere"])
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_report_uses_the_time_zone() {
+    // The fixture runs 10:00–10:01 UTC on 2025-06-29, which is still June 28 in Pago Pago (UTC-11)
+    let pago_pago = zone("Pacific/Pago_Pago");
+    let index = TempDir::new("tz-index");
+    let report = analyze_conversations_with_ai(
+        Path::new("tests/data"),
+        &index,
+        DateFilter::day(date(6, 28)).with_tz(pago_pago),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(report.date, "2025-06-28 (Pacific/Pago_Pago)");
+    let sessions = &report.projects[0].sessions;
+    assert_eq!(sessions[0].time_range(), "2025-06-28 23:00–23:01");
+    assert!(sessions[0].start.ends_with("-11:00"));
+
+    let utc = analyze_conversations_with_ai(
+        Path::new("tests/data"),
+        &index,
+        DateFilter::day(date(6, 28)).with_tz(zone("UTC")),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+    assert!(utc.projects.is_empty());
+}
+
+/// The period for a command line, with today being Wednesday 2026-10-14
+fn period(args: &[&str]) -> Result<(Option<NaiveDate>, Option<NaiveDate>), String> {
+    let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    let matches = cli::build_cli()
+        .try_get_matches_from(std::iter::once("cc2report").chain(args.iter().copied()))
+        .map_err(|e| e.to_string())?;
+    DateFilter::from_matches_at(&matches, today).map(|filter| (filter.from, filter.to))
+}
+
+fn day(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+#[test]
+fn test_named_periods() {
+    assert_eq!(period(&[]), Ok((day(2026, 10, 14), day(2026, 10, 14))));
+    assert_eq!(
+        period(&["--yesterday"]),
+        Ok((day(2026, 10, 13), day(2026, 10, 13)))
+    );
+    assert_eq!(
+        period(&["--weekly"]),
+        Ok((day(2026, 10, 12), day(2026, 10, 18)))
+    );
+    assert_eq!(
+        period(&["--last-week"]),
+        Ok((day(2026, 10, 5), day(2026, 10, 11)))
+    );
+    assert_eq!(
+        period(&["--monthly"]),
+        Ok((day(2026, 10, 1), day(2026, 10, 31)))
+    );
+    assert_eq!(
+        period(&["--last-month"]),
+        Ok((day(2026, 9, 1), day(2026, 9, 30)))
+    );
+    assert_eq!(
+        period(&["--quarter"]),
+        Ok((day(2026, 10, 1), day(2026, 12, 31)))
+    );
+    assert_eq!(period(&["--all"]), Ok((None, None)));
+}
+
+#[test]
+fn test_since() {
+    assert_eq!(period(&["--since", "3d"]), Ok((day(2026, 10, 11), None)));
+    assert_eq!(period(&["--since", "2w"]), Ok((day(2026, 9, 30), None)));
+    assert_eq!(period(&["--since", "1m"]), Ok((day(2026, 9, 14), None)));
+    assert_eq!(
+        period(&["--since", "2026-10-01"]),
+        Ok((day(2026, 10, 1), None))
+    );
+    assert_eq!(
+        period(&["--since", "2026-W41"]),
+        Ok((day(2026, 10, 5), None))
+    );
+    assert!(period(&["--since", "3y"]).is_err());
+    assert!(period(&["--since", "soon"]).is_err());
+}
+
+#[test]
+fn test_iso_weeks() {
+    assert_eq!(
+        period(&["--date", "2026-W41"]),
+        Ok((day(2026, 10, 5), day(2026, 10, 11)))
+    );
+    // ISO week 1 of 2026 starts in December 2025
+    assert_eq!(
+        period(&["--date", "2026W01"]),
+        Ok((day(2025, 12, 29), day(2026, 1, 4)))
+    );
+    assert_eq!(
+        period(&["--from", "2026-W40", "--to", "2026-W41"]),
+        Ok((day(2026, 9, 28), day(2026, 10, 11)))
+    );
+    assert!(period(&["--date", "2026-W54"]).is_err());
+}
+
+#[test]
+fn test_open_ended_from_and_to() {
+    assert_eq!(
+        period(&["--from", "2026-10-01"]),
+        Ok((day(2026, 10, 1), None))
+    );
+    assert_eq!(
+        period(&["--to", "2026-10-01"]),
+        Ok((None, day(2026, 10, 1)))
+    );
+    assert_eq!(
+        period(&["--from", "yesterday", "--to", "today"]),
+        Ok((day(2026, 10, 13), day(2026, 10, 14)))
+    );
+    assert!(period(&["--from", "2026-10-02", "--to", "2026-10-01"]).is_err());
+}
+
+#[test]
+fn test_conflicting_periods_are_rejected() {
+    assert!(period(&["--weekly", "--last-month"]).is_err());
+    assert!(period(&["--since", "3d", "--from", "2026-10-01"]).is_err());
+    assert!(period(&["--date", "2026-10-01", "--to", "2026-10-02"]).is_err());
+}
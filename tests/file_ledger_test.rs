@@ -0,0 +1,113 @@
+mod common;
+
+use cc2report::date_filter::DateFilter;
+use cc2report::file_ledger::{changed_lines, FileLedger};
+use cc2report::project_filter::ProjectFilter;
+use cc2report::smart_analyzer::generate_smart_report;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use common::TempDir;
+use serde_json::json;
+use std::path::Path;
+
+#[test]
+fn test_changed_lines_ignore_unchanged_context() {
+    assert_eq!(changed_lines("a\nb\nc", "a\nB\nc"), (1, 1));
+    assert_eq!(changed_lines("a\nc", "a\nb\nb2\nc"), (2, 0));
+    assert_eq!(changed_lines("a\nb\nc", "a"), (0, 2));
+    assert_eq!(changed_lines("", "x\ny"), (2, 0));
+    assert_eq!(changed_lines("same", "same"), (0, 0));
+}
+
+#[test]
+fn test_ledger_counts_reads_edits_and_new_files() {
+    let mut ledger = FileLedger::new(Path::new("/srv/app"));
+    ledger.record_tool_use("Read", &json!({"file_path": "/srv/app/src/lib.rs"}));
+    ledger.record_tool_use(
+        "Edit",
+        &json!({"file_path": "/srv/app/src/lib.rs", "old_string": "fn a() {}", "new_string": "fn a() {}\nfn b() {}"}),
+    );
+    ledger.record_tool_use(
+        "MultiEdit",
+        &json!({"file_path": "/srv/app/src/lib.rs", "edits": [
+            {"old_string": "x", "new_string": "y"},
+            {"old_string": "old\nlines", "new_string": ""}
+        ]}),
+    );
+    ledger.record_tool_use(
+        "Write",
+        &json!({"file_path": "/srv/app/src/new.rs", "content": "one\ntwo\nthree\n"}),
+    );
+    ledger.record_tool_use("Read", &json!({"file_path": "/etc/hosts"}));
+    ledger.record_tool_use("Bash", &json!({"command": "ls"}));
+    ledger.record_tool_use("Grep", &json!({"pattern": "x", "file_path": "/srv/app/x"}));
+
+    let files = ledger.into_files();
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, ["src/lib.rs", "src/new.rs", "/etc/hosts"]);
+
+    let lib = &files[0];
+    assert!(!lib.created);
+    assert_eq!((lib.reads, lib.edits), (1, 2));
+    assert_eq!((lib.lines_added, lib.lines_removed), (2, 3));
+
+    let new = &files[1];
+    assert!(new.created);
+    assert_eq!((new.edits, new.lines_added), (1, 3));
+    assert_eq!(files[2].edits, 0);
+}
+
+#[tokio::test]
+async fn test_report_lists_files_touched() {
+    let root = TempDir::new("ledger");
+
+    let tool_use = json!([
+        {"type": "text", "text": "Adding the parser"},
+        {"type": "tool_use", "id": "t1", "name": "Write",
+         "input": {"file_path": "/srv/app/src/parser.rs", "content": "fn parse() {}\n"}},
+        {"type": "tool_use", "id": "t2", "name": "Read",
+         "input": {"file_path": "/srv/app/Cargo.toml"}}
+    ]);
+    let lines = [
+        json!({"parentUuid": null, "sessionId": "s-1", "cwd": "/srv/app", "type": "user",
+               "message": {"role": "user", "content": "Add a parser"},
+               "uuid": "u-1", "timestamp": "2025-06-29T10:00:00.000Z"}),
+        json!({"parentUuid": "u-1", "sessionId": "s-1", "cwd": "/srv/app", "type": "assistant",
+               "message": {"role": "assistant", "content": tool_use},
+               "uuid": "a-1", "timestamp": "2025-06-29T10:01:00.000Z"}),
+    ];
+    let content: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    root.write_log("logs/-srv-app/s.jsonl", &content);
+
+    let report = analyze_conversations_with_ai(
+        &root.join("logs"),
+        &root.join("index"),
+        DateFilter::all(),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let files = &report.projects[0].files;
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].path, "src/parser.rs");
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["projects"][0]["files"][0]["lines_added"], 1);
+
+    let output = root.join("report.md");
+    generate_smart_report(&report, Some(&output), "en").unwrap();
+    let markdown = std::fs::read_to_string(&output).unwrap();
+
+    assert!(markdown.contains("**Files touched**:"));
+    assert!(markdown.contains("| `src/parser.rs` (new) | 1 | 0 | +1 −0 |"));
+    assert!(markdown.contains("| `Cargo.toml` | 0 | 1 | +0 −0 |"));
+}
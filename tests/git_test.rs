@@ -0,0 +1,221 @@
+mod common;
+
+use cc2report::cli;
+use cc2report::config::{Config, ProviderKind};
+use cc2report::date_filter::{DateFilter, ReportTimeZone};
+use cc2report::git::{commits_in, DiffStat};
+use cc2report::project_filter::ProjectFilter;
+use cc2report::provider::create_provider;
+use cc2report::smart_analyzer::generate_smart_report;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use chrono::NaiveDate;
+use common::{analysis_json, isolate_home, openai_reply, StubResponse, StubServer, TempDir};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn git(repo: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+/// Commit `file` with `lines` lines at `time` (UTC) as `author`
+fn commit(repo: &Path, time: &str, subject: &str, file: &str, lines: usize, author: &str) {
+    std::fs::write(repo.join(file), "line\n".repeat(lines)).unwrap();
+    git(repo, &["add", file]);
+    let date = format!("2025-06-{time}+00:00");
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["commit", "-q", "-m", subject, "--author", author])
+        .env("GIT_AUTHOR_DATE", &date)
+        .env("GIT_COMMITTER_DATE", &date)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+const ME: &str = "Me <me@example.com>";
+
+/// A repository with commits around two sessions on June 29, and its log directory
+fn fixture(name: &str) -> (TempDir, PathBuf) {
+    let root = TempDir::new(&format!("git-{name}"));
+    let repo = root.join("repo");
+    std::fs::create_dir_all(&repo).unwrap();
+    git(&repo, &["init", "-q"]);
+    git(&repo, &["config", "user.email", "me@example.com"]);
+    git(&repo, &["config", "user.name", "Me"]);
+
+    commit(&repo, "27T09:00:00", "Initial commit", "README.md", 1, ME);
+    commit(&repo, "29T10:20:00", "Add parser", "parser.rs", 40, ME);
+    commit(
+        &repo,
+        "29T12:00:00",
+        "Teammate change",
+        "other.rs",
+        5,
+        "Other <other@example.com>",
+    );
+    commit(&repo, "29T12:30:00", "Tidy config", "config.rs", 3, ME);
+    commit(&repo, "29T14:03:00", "Fix login", "parser.rs", 42, ME);
+
+    let entry = |session: &str, kind: &str, time: &str, content: &str| {
+        let parent = match kind {
+            "user" => "null".to_string(),
+            _ => format!("\"{session}-user\""),
+        };
+        format!(
+            r#"{{"parentUuid":{parent},"sessionId":"{session}","cwd":"{}","type":"{kind}","message":{{"role":"{kind}","content":{content}}},"uuid":"{session}-{kind}","timestamp":"2025-06-29T{time}:00.000Z"}}"#,
+            repo.display()
+        )
+    };
+    let reply = r#"[{"type":"text","text":"Done"}]"#;
+    let lines = [
+        entry("s-1", "user", "10:00", "\"Add the parser\""),
+        entry("s-1", "assistant", "10:05", reply),
+        entry("s-2", "user", "14:00", "\"Fix the login bug\""),
+        entry("s-2", "assistant", "14:05", reply),
+    ];
+    root.write_log("logs/project/s.jsonl", &lines);
+    let logs = root.join("logs");
+    (root, logs)
+}
+
+fn june_29() -> DateFilter {
+    DateFilter::day(NaiveDate::from_ymd_opt(2025, 6, 29).unwrap())
+        .with_tz(ReportTimeZone::Named(chrono_tz::UTC))
+}
+
+fn subjects(commits: &[cc2report::git::Commit]) -> Vec<&str> {
+    commits.iter().map(|c| c.subject.as_str()).collect()
+}
+
+#[test]
+fn test_commits_in_period_by_own_author() {
+    let (root, _) = fixture("log");
+    let commits = commits_in(&root.join("repo"), june_29(), false).unwrap();
+
+    assert_eq!(
+        subjects(&commits),
+        ["Add parser", "Tidy config", "Fix login"]
+    );
+    assert_eq!(commits[0].files, ["parser.rs"]);
+    assert_eq!(commits[0].insertions, 40);
+    assert_eq!(commits[2].insertions, 2);
+    assert_eq!(commits[0].timestamp, "2025-06-29T10:20:00+00:00");
+    assert_eq!(
+        DiffStat::of(&commits),
+        DiffStat {
+            files_changed: 2,
+            insertions: 45,
+            deletions: 0,
+        }
+    );
+
+    assert!(commits_in(&root, june_29(), false).is_err());
+}
+
+#[test]
+fn test_all_authors_includes_teammates() {
+    let (root, _) = fixture("authors");
+    let commits = commits_in(&root.join("repo"), june_29(), true).unwrap();
+
+    assert_eq!(
+        subjects(&commits),
+        ["Add parser", "Teammate change", "Tidy config", "Fix login"]
+    );
+    assert_eq!(commits[1].author, "Other");
+
+    let matches = cli::build_cli().get_matches_from(["cc2report", "--all-authors"]);
+    assert!(Config::from_args(&matches).unwrap().processing.all_authors);
+    let matches = cli::build_cli().get_matches_from(["cc2report"]);
+    assert!(!Config::from_args(&matches).unwrap().processing.all_authors);
+}
+
+#[tokio::test]
+async fn test_commits_are_attached_to_topics_and_reported() {
+    let (root, logs) = fixture("report");
+    let report = analyze_conversations_with_ai(
+        &logs,
+        &root.join("index"),
+        june_29(),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let project = &report.projects[0];
+    assert_eq!(
+        subjects(&project.commits),
+        ["Add parser", "Tidy config", "Fix login"]
+    );
+    assert_eq!(project.diffstat.as_ref().unwrap().files_changed, 2);
+
+    // 10:20 is within the grace period after the first session; 12:30 belongs to no topic
+    let entries = &report.timeline[0].entries;
+    assert_eq!(entries[0].commits.len(), 1);
+    assert!(entries[0].commits[0].ends_with(" Add parser"));
+    assert!(entries[1].commits[0].ends_with(" Fix login"));
+
+    let output = root.join("report.md");
+    generate_smart_report(&report, Some(&output), "en").unwrap();
+    let markdown = std::fs::read_to_string(&output).unwrap();
+    assert!(markdown.contains("**Commits**:"));
+    assert!(markdown.contains(" Tidy config (+3 −0)"));
+    assert!(markdown.contains("**Diffstat**: 2 files changed, +45 −0"));
+}
+
+#[tokio::test]
+async fn test_commit_subjects_are_sent_to_the_model() {
+    isolate_home();
+    let (root, logs) = fixture("prompt");
+    let server = StubServer::start(vec![StubResponse::json(
+        200,
+        openai_reply(&analysis_json("Parser Project")),
+    )]);
+
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::Ollama;
+    config.api.model = format!("git-model-{}", std::process::id());
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+    let provider = create_provider(&config.api).unwrap();
+
+    let report = analyze_conversations_with_ai(
+        &logs,
+        &root.join("index"),
+        june_29(),
+        &ProjectFilter::all(),
+        Some(provider),
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let prompt = server.requests()[0].json()["messages"][1]["content"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(prompt.contains("Commits (made in this period"));
+    assert!(prompt.contains(" Fix login\n"));
+    assert!(!prompt.contains("Teammate change"));
+    assert_eq!(report.projects[0].commits.len(), 3);
+}
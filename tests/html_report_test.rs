@@ -0,0 +1,122 @@
+mod common;
+
+use cc2report::cli;
+use cc2report::config::{Config, OutputFormat};
+use cc2report::date_filter::{DateFilter, ReportTimeZone};
+use cc2report::html_report::render;
+use cc2report::project_filter::ProjectFilter;
+use cc2report::smart_analyzer::SmartReport;
+use cc2report::templates::Templates;
+use cc2report::token_tracker::{TokenTracker, TokenUsage};
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use common::TempDir;
+
+/// Two days of `/srv/<b>shop</b>` a week apart, with token usage and a known effort split
+async fn report(name: &str) -> SmartReport {
+    let root = TempDir::new(&format!("html-{name}"));
+    let entry = |session: &str, uuid: &str, parent: &str, time: &str| {
+        format!(
+            r#"{{"parentUuid":{parent},"sessionId":"{session}","cwd":"/srv/<b>shop</b>","type":"user","message":{{"role":"user","content":"Fix the cart"}},"uuid":"{uuid}","timestamp":"2025-06-{time}:00.000Z"}}"#
+        )
+    };
+    let lines = [
+        entry("s-a", "a-1", "null", "23T08:00"),
+        entry("s-a", "a-2", "\"a-1\"", "23T08:10"),
+        entry("s-b", "b-1", "null", "30T13:00"),
+        entry("s-b", "b-2", "\"b-1\"", "30T15:30"),
+    ];
+    root.write_log("logs/-srv-shop/s.jsonl", &lines);
+
+    let mut report = analyze_conversations_with_ai(
+        &root.join("logs"),
+        &root.join("index"),
+        DateFilter::all().with_tz(ReportTimeZone::Named(chrono_tz::UTC)),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let effort = &mut report.projects[0].work_breakdown.effort_distribution;
+    (
+        effort.development,
+        effort.debugging,
+        effort.configuration,
+        effort.other,
+    ) = (60.0, 30.0, 10.0, 0.0);
+    let mut tracker = TokenTracker::new();
+    tracker.add_usage(
+        "/srv/<b>shop</b>",
+        TokenUsage {
+            prompt_tokens: 2000,
+            completion_tokens: 500,
+            total_tokens: 2500,
+        },
+    );
+    tracker.calculate_cost("gpt-4o-mini");
+    report.token_tracker = Some(tracker);
+    report
+}
+
+#[tokio::test]
+async fn test_html_report_is_self_contained() {
+    let html = render(&report("en").await, "en");
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.ends_with("</html>\n"));
+    assert!(html.contains("<style>"));
+    // Nothing is loaded from the network
+    assert!(!html.contains("src=\"http"));
+    assert!(!html.contains("href=\"http"));
+    assert!(!html.contains("<script"));
+
+    // Project text is escaped
+    assert!(html.contains("/srv/&lt;b&gt;shop&lt;/b&gt;"));
+    assert!(!html.contains("<b>shop</b>"));
+
+    // Collapsible project section
+    assert!(html.contains("<details open>"));
+
+    // Stacked effort bar with one segment per non-zero category
+    assert!(html.contains("<h2>Effort distribution</h2>"));
+    assert!(html.contains("<title>Development: 60%</title>"));
+    assert!(html.contains("<title>Configuration: 10%</title>"));
+    assert!(!html.contains("<title>Other:"));
+
+    // Heatmap from Monday of the first week through the last active day
+    assert!(html.contains("<h2>Daily activity</h2>"));
+    assert!(html.contains("<title>2025-06-23: 25m</title>"));
+    assert!(html.contains("<title>2025-06-25: 0m</title>"));
+    assert!(html.contains("<title>2025-06-30: 30m</title>"));
+    assert_eq!(html.matches("<title>2025-06-").count(), 8);
+
+    // Token cost table with a total row
+    assert!(html.contains("<h2>Token usage</h2>"));
+    assert!(html.contains(
+        "<tr><td>/srv/&lt;b&gt;shop&lt;/b&gt;</td><td class=\"num\">2000</td><td class=\"num\">500</td><td class=\"num\">2500</td>"
+    ));
+    assert!(html.contains("<tr><td><strong>Total</strong></td>"));
+}
+
+#[tokio::test]
+async fn test_html_report_is_localized() {
+    let html = render(&report("ja").await, "ja");
+
+    assert!(html.contains("<html lang=\"ja\">"));
+    assert!(html.contains("<h2>作業配分</h2>"));
+    assert!(html.contains("<th>プロジェクト</th>"));
+}
+
+#[test]
+fn test_html_format_is_accepted() {
+    let matches = cli::build_cli().get_matches_from(["cc2report", "--format", "html"]);
+    let config = Config::from_args(&matches).unwrap();
+    assert!(matches!(config.output.format, OutputFormat::Html));
+}
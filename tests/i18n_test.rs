@@ -0,0 +1,122 @@
+mod common;
+
+use cc2report::conversation_analyzer::ConversationFlow;
+use cc2report::date_filter::{DateFilter, ReportTimeZone};
+use cc2report::i18n::{fallback_chain, missing_keys, Catalog, LANGUAGES};
+use cc2report::parser::Message;
+use cc2report::project_filter::ProjectFilter;
+use cc2report::smart_analyzer::generate_smart_report;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use chrono::NaiveDate;
+use common::TempDir;
+
+#[test]
+fn test_every_language_is_complete() {
+    for lang in LANGUAGES {
+        assert_eq!(missing_keys(lang), Vec::<String>::new(), "{lang}");
+    }
+}
+
+#[test]
+fn test_fallback_chains() {
+    assert_eq!(fallback_chain("pt_BR"), ["pt", "en"]);
+    assert_eq!(fallback_chain("ms"), ["ms", "id", "en"]);
+    assert_eq!(fallback_chain("xx"), ["en"]);
+    assert_eq!(fallback_chain("en"), ["en"]);
+}
+
+#[test]
+fn test_catalog_lookup() {
+    let catalog = Catalog::new("de");
+    assert_eq!(catalog.get("report.title"), "Arbeitsbericht");
+    assert_eq!(catalog.get_in("report", "title"), "Arbeitsbericht");
+    assert_eq!(
+        catalog.format("steps.edit", &[("file", &"main.rs")]),
+        "main.rs bearbeitet"
+    );
+    assert_eq!(catalog.get("report.nonexistent"), "report.nonexistent");
+    assert_eq!(
+        Catalog::new("zh-TW").get("report.title"),
+        Catalog::new("zh").get("report.title")
+    );
+    assert_eq!(Catalog::new("xx").get("report.title"), "Work Report");
+}
+
+#[test]
+fn test_step_descriptions_follow_language() {
+    let edit = Message {
+        role: Some("assistant".to_string()),
+        content: Some(serde_json::json!([{
+            "type": "tool_use",
+            "name": "Edit",
+            "input": {"file_path": "/srv/app/src/main.rs"}
+        }])),
+        model: None,
+        id: None,
+        message_type: None,
+    };
+    let steps = |lang: &str| {
+        let mut flow = ConversationFlow::with_language(lang);
+        flow.analyze_user_message("Fix the parser", "2025-06-29T10:00:00.000Z");
+        flow.analyze_assistant_response(&edit);
+        flow.finalize();
+        flow.topics[0].steps[0].clone()
+    };
+
+    assert!(steps("ja").details.contains(&"main.rs を編集".to_string()));
+    assert!(steps("en").details.contains(&"Edit main.rs".to_string()));
+    assert_eq!(steps("ja").tools[0].name, "Edit");
+}
+
+#[tokio::test]
+async fn test_report_in_german() {
+    let root = TempDir::new("i18n-de");
+    root.write_log(
+        "logs/-srv-shop/s.jsonl",
+        &[r#"{"parentUuid":null,"sessionId":"s-a","cwd":"/srv/shop","type":"user","message":{"role":"user","content":"Fix the cart"},"uuid":"a-1","timestamp":"2025-06-23T08:00:00.000Z"}"#.to_string()],
+    );
+
+    let day = |d| NaiveDate::from_ymd_opt(2025, 6, d).unwrap();
+    let report = analyze_conversations_with_ai(
+        &root.join("logs"),
+        &root.join("index"),
+        DateFilter::range(Some(day(20)), Some(day(25)))
+            .with_tz(ReportTimeZone::Named(chrono_tz::UTC)),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "de",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+    assert!(
+        report.date.starts_with("2025-06-20 bis 2025-06-25"),
+        "{}",
+        report.date
+    );
+    // The rule-based summary and the report totals follow the language too
+    let insights = &report.summary.productivity_insights;
+    assert!(insights.contains("1 Projekte mit"), "{insights}");
+    assert!(
+        insights.ends_with("Sitzungen: 1, Nachrichten: 1"),
+        "{insights}"
+    );
+    assert_eq!(
+        report.projects[0].objective,
+        "Softwareentwicklung und -verbesserung"
+    );
+
+    let output = root.join("report.md");
+    generate_smart_report(&report, Some(&output), "de").unwrap();
+    let markdown = std::fs::read_to_string(&output).unwrap();
+    assert!(
+        markdown.starts_with("# Arbeitsbericht - 2025-06-20 bis 2025-06-25"),
+        "{markdown}"
+    );
+}
@@ -1,5 +1,9 @@
-use cc2report::{conversation_analyzer, parser, smart_analyzer};
+mod common;
+
+use cc2report::templates::Templates;
+use cc2report::{cli, conversation_analyzer, parser, smart_analyzer};
 use chrono::NaiveDate;
+use common::TempDir;
 use std::path::PathBuf;
 
 #[test]
@@ -91,9 +95,8 @@ fn test_conversation_flow_analysis() {
 
     assert!(!flow.topics.is_empty(), "Should have at least one topic");
     assert_eq!(flow.topics[0].steps.len(), 2, "Should have 2 work steps");
-    assert_eq!(
-        flow.topics[0].user_intent.len() > 0,
-        true,
+    assert!(
+        !flow.topics[0].user_intent.is_empty(),
         "Should have user intent"
     );
 }
@@ -133,6 +136,7 @@ fn test_smart_report_structure() {
             productivity_insights: "Good progress on feature implementation".to_string(),
         },
         projects: vec![SmartProjectSummary {
+            project: "~/test-project".to_string(),
             title: "Test Project".to_string(),
             objective: "Implement test features".to_string(),
             work_breakdown: WorkBreakdown {
@@ -154,8 +158,17 @@ fn test_smart_report_structure() {
                 technical_improvements: vec!["Improved performance".to_string()],
                 unresolved_issues: vec!["Need to add more tests".to_string()],
             },
+            sessions: vec![],
+            commits: vec![],
+            diffstat: None,
+            files: vec![],
+            commands: Default::default(),
+            active_time: None,
         }],
         token_tracker: None,
+        api_stats: None,
+        timeline: vec![],
+        active_time: None,
     };
 
     // Test JSON serialization
@@ -165,6 +178,68 @@ fn test_smart_report_structure() {
     assert!(json.contains("85"));
 }
 
+#[test]
+fn test_provider_selection() {
+    use cc2report::config::{Config, ProviderKind};
+
+    let matches = cli::build_cli().get_matches_from([
+        "cc2report",
+        "--provider",
+        "ollama",
+        "--log-dir",
+        "tests/data",
+    ]);
+    let config = Config::from_args(&matches).unwrap();
+
+    assert_eq!(config.api.provider, ProviderKind::Ollama);
+    assert_eq!(config.api.model, "llama3.1");
+    assert!(config.api.api_key().is_none());
+    assert!(
+        config.validate().is_ok(),
+        "Ollama should not need an API key"
+    );
+
+    let matches = cli::build_cli().get_matches_from([
+        "cc2report",
+        "--provider",
+        "anthropic",
+        "--api-key",
+        "test-key",
+        "--model",
+        "claude-3-5-haiku-latest",
+    ]);
+    let config = Config::from_args(&matches).unwrap();
+
+    assert_eq!(config.api.provider, ProviderKind::Anthropic);
+    assert_eq!(config.api.model, "claude-3-5-haiku-latest");
+    assert_eq!(config.api.api_key(), Some("test-key"));
+}
+
+#[tokio::test]
+async fn test_offline_report() {
+    let index = TempDir::new("offline-index");
+    let report = cc2report::work_report_v2::analyze_conversations_with_ai(
+        std::path::Path::new("tests/data"),
+        &index,
+        cc2report::date_filter::DateFilter::all(),
+        &cc2report::project_filter::ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(report.projects.len(), 1, "Expected the test project");
+    assert!(report.token_tracker.is_none(), "No API usage offline");
+    assert_eq!(report.summary.total_work_items, 6);
+}
+
 // Helper function matching the one in work_report_v2.rs
 fn is_in_date_range(
     date: chrono::NaiveDate,
@@ -0,0 +1,209 @@
+mod common;
+
+use cc2report::date_filter::DateFilter;
+use cc2report::log_index::{IndexStats, LogIndex};
+use chrono::NaiveDate;
+use common::TempDir;
+use std::io::Write;
+use std::path::PathBuf;
+
+fn entry(uuid: &str, day: u32) -> String {
+    format!(
+        r#"{{"parentUuid":null,"sessionId":"s-1","type":"user","message":{{"role":"user","content":"Message {uuid}"}},"uuid":"{uuid}","timestamp":"2025-06-{day:02}T10:00:00.000Z"}}"#
+    )
+}
+
+/// A temp dir with a `logs` directory, and the index directory next to it
+fn fixture(name: &str) -> (TempDir, PathBuf, PathBuf) {
+    let root = TempDir::new(&format!("index-{name}"));
+    let logs = root.join("logs");
+    std::fs::create_dir_all(&logs).unwrap();
+    let index = root.join("index");
+    (root, logs, index)
+}
+
+fn uuids(index: &mut LogIndex, files: &[PathBuf], day: Option<u32>) -> Vec<String> {
+    let date = day.map(|day| NaiveDate::from_ymd_opt(2025, 6, day).unwrap());
+    index
+        .scan_files(files, date.map_or(DateFilter::all(), DateFilter::day))
+        .into_iter()
+        .flat_map(|scan| scan.unwrap().entries)
+        .map(|entry| entry.uuid.unwrap())
+        .collect()
+}
+
+#[test]
+fn test_unchanged_files_are_read_from_the_index() {
+    let (_root, logs, index_dir) = fixture("reuse");
+    let files = vec![logs.join("a.jsonl"), logs.join("b.jsonl")];
+    std::fs::write(&files[0], format!("{}\n", entry("u-1", 28))).unwrap();
+    std::fs::write(&files[1], format!("{}\n", entry("u-2", 29))).unwrap();
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    assert_eq!(uuids(&mut index, &files, None), ["u-1", "u-2"]);
+    assert_eq!(index.stats().parsed, 2);
+    index.save().unwrap();
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    assert_eq!(index.len(), 2);
+    assert_eq!(uuids(&mut index, &files, Some(29)), ["u-2"]);
+    assert_eq!(
+        index.stats(),
+        IndexStats {
+            reused: 1,
+            skipped: 1,
+            ..IndexStats::default()
+        }
+    );
+}
+
+#[test]
+fn test_appended_lines_are_parsed_incrementally() {
+    let (_root, logs, index_dir) = fixture("append");
+    let files = vec![logs.join("a.jsonl")];
+    std::fs::write(&files[0], format!("{}\n", entry("u-1", 29))).unwrap();
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    uuids(&mut index, &files, None);
+    index.save().unwrap();
+
+    // A line that is still being written is left for the next run
+    let mut file = std::fs::File::options()
+        .append(true)
+        .open(&files[0])
+        .unwrap();
+    write!(file, "{}\n{{\"parentUuid\":", entry("u-2", 29)).unwrap();
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    assert_eq!(uuids(&mut index, &files, None), ["u-1", "u-2"]);
+    assert_eq!(index.stats().appended, 1);
+    index.save().unwrap();
+
+    writeln!(
+        file,
+        "null,{}",
+        &entry("u-3", 29)[r#"{"parentUuid":null,"#.len()..]
+    )
+    .unwrap();
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    let scans = index.scan_files(&files, DateFilter::all());
+    let scan = scans.into_iter().next().unwrap().unwrap();
+    assert!(scan.errors.is_empty(), "{:?}", scan.errors);
+    let uuids: Vec<_> = scan.entries.iter().filter_map(|e| e.uuid.clone()).collect();
+    assert_eq!(uuids, ["u-1", "u-2", "u-3"]);
+    assert_eq!(index.stats().appended, 1);
+}
+
+#[test]
+fn test_rewritten_files_are_parsed_again() {
+    let (_root, logs, index_dir) = fixture("rewrite");
+    let files = vec![logs.join("a.jsonl")];
+    std::fs::write(&files[0], format!("{}\n", entry("u-1", 29))).unwrap();
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    uuids(&mut index, &files, None);
+    index.save().unwrap();
+
+    std::fs::write(
+        &files[0],
+        format!("{}\n{}\n", entry("u-9", 29), entry("u-2", 29)),
+    )
+    .unwrap();
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    assert_eq!(uuids(&mut index, &files, None), ["u-9", "u-2"]);
+    assert_eq!(index.stats().parsed, 1);
+}
+
+#[test]
+fn test_damaged_index_is_rebuilt() {
+    let (_root, logs, index_dir) = fixture("damaged");
+    let files = vec![logs.join("a.jsonl")];
+    std::fs::write(&files[0], format!("{}\n", entry("u-1", 29))).unwrap();
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    uuids(&mut index, &files, None);
+    index.save().unwrap();
+
+    // Damaged data files are parsed again
+    for file in std::fs::read_dir(&index_dir).unwrap() {
+        let path = file.unwrap().path();
+        if path.file_name().unwrap() != "manifest.json" {
+            std::fs::write(&path, "{\"path\":").unwrap();
+        }
+    }
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    assert_eq!(uuids(&mut index, &files, None), ["u-1"]);
+    assert_eq!(index.stats().parsed, 1);
+    index.save().unwrap();
+
+    // A damaged manifest starts an empty index
+    std::fs::write(index_dir.join("manifest.json"), "not json").unwrap();
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    assert!(index.is_empty());
+    assert_eq!(uuids(&mut index, &files, None), ["u-1"]);
+    index.save().unwrap();
+
+    LogIndex::clear(&index_dir).unwrap();
+    assert!(!index_dir.exists());
+    assert!(LogIndex::open(&index_dir).unwrap().is_empty());
+}
+
+#[test]
+fn test_unreferenced_data_files_are_removed() {
+    let (_root, logs, index_dir) = fixture("orphans");
+    let files = vec![logs.join("a.jsonl"), logs.join("b.jsonl")];
+    std::fs::write(&files[0], format!("{}\n", entry("u-1", 29))).unwrap();
+    std::fs::write(&files[1], format!("{}\n", entry("u-2", 29))).unwrap();
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    uuids(&mut index, &files, None);
+    // A data file of a rewritten log, and an API cache entry sharing the directory
+    std::fs::write(index_dir.join("idx-00000000deadbeef.bin"), "{}").unwrap();
+    std::fs::write(index_dir.join("0123abcd.json"), "{}").unwrap();
+    index.save().unwrap();
+    let data_files = || {
+        std::fs::read_dir(&index_dir)
+            .unwrap()
+            .filter(|file| {
+                let name = file.as_ref().unwrap().file_name();
+                name.to_string_lossy().starts_with("idx-")
+            })
+            .count()
+    };
+    assert_eq!(data_files(), 2);
+
+    std::fs::remove_file(&files[1]).unwrap();
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    assert_eq!(uuids(&mut index, &files[..1], None), ["u-1"]);
+    assert_eq!(index.stats().reused, 1);
+    index.save().unwrap();
+    assert_eq!(index.len(), 1);
+    assert_eq!(data_files(), 1);
+    assert!(index_dir.join("0123abcd.json").exists());
+}
+
+#[test]
+fn test_clear_keeps_files_the_index_did_not_write() {
+    let (_root, logs, index_dir) = fixture("shared");
+    let files = vec![logs.join("a.jsonl")];
+    std::fs::write(&files[0], format!("{}\n", entry("u-1", 29))).unwrap();
+
+    // Without a manifest the directory is not an index
+    LogIndex::clear(&logs).unwrap();
+    assert!(files[0].exists());
+
+    let mut index = LogIndex::open(&index_dir).unwrap();
+    uuids(&mut index, &files, None);
+    index.save().unwrap();
+    std::fs::write(index_dir.join("0123abcd.json"), "{}").unwrap();
+
+    LogIndex::clear(&index_dir).unwrap();
+    let left: Vec<_> = std::fs::read_dir(&index_dir)
+        .unwrap()
+        .map(|file| file.unwrap().file_name())
+        .collect();
+    assert_eq!(left, ["0123abcd.json"]);
+    assert!(LogIndex::open(&index_dir).unwrap().is_empty());
+}
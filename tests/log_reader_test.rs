@@ -0,0 +1,104 @@
+mod common;
+
+use cc2report::date_filter::{DateFilter, ReportTimeZone};
+use cc2report::log_reader::{jsonl_files, scan_file, scan_files};
+use chrono::NaiveDate;
+use common::TempDir;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn entry(uuid: &str, day: u32) -> String {
+    format!(
+        r#"{{"parentUuid":null,"sessionId":"s-1","type":"user","message":{{"role":"user","content":"Message {uuid}"}},"uuid":"{uuid}","timestamp":"2025-06-{day:02}T10:00:00.000Z"}}"#
+    )
+}
+
+fn day(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+}
+
+fn range(from: Option<NaiveDate>, to: Option<NaiveDate>) -> DateFilter {
+    DateFilter::range(from, to).with_tz(ReportTimeZone::Named(chrono_tz::UTC))
+}
+
+#[test]
+fn test_scan_keeps_in_range_entries_and_summaries() {
+    let dir = TempDir::new("reader-mixed");
+    let path = dir.write_log(
+        "mixed.jsonl",
+        &[
+            r#"{"type":"summary","summary":"Earlier work","leafUuid":"old"}"#.to_string(),
+            entry("u-1", 28),
+            String::new(),
+            entry("u-2", 29),
+            "{not json".to_string(),
+            entry("u-3", 30),
+        ],
+    );
+
+    let scan = scan_file(&path, range(Some(day(29)), Some(day(29)))).unwrap();
+
+    assert!(!scan.skipped);
+    let uuids: Vec<_> = scan.entries.iter().map(|e| e.uuid.as_deref()).collect();
+    assert_eq!(uuids, [Some("u-2")]);
+    assert_eq!(scan.summaries.len(), 1);
+    assert_eq!(scan.summaries[0].summary, "Earlier work");
+    assert_eq!(scan.errors.len(), 1);
+    assert!(
+        scan.errors[0].contains("mixed.jsonl:5:"),
+        "{}",
+        scan.errors[0]
+    );
+}
+
+#[test]
+fn test_scan_skips_files_outside_range_by_timestamps() {
+    let dir = TempDir::new("reader-early");
+    let path = dir.write_log("early.jsonl", &[entry("u-1", 20), entry("u-2", 21)]);
+
+    let after = scan_file(&path, range(Some(day(25)), None)).unwrap();
+    assert!(after.skipped);
+    assert!(after.entries.is_empty());
+
+    let before = scan_file(&path, range(None, Some(day(10)))).unwrap();
+    assert!(before.skipped);
+
+    let inside = scan_file(&path, range(Some(day(21)), None)).unwrap();
+    assert!(!inside.skipped);
+    assert_eq!(inside.entries.len(), 1);
+}
+
+#[test]
+fn test_scan_skips_files_modified_before_range() {
+    // The timestamps alone would put the file in range; only the mtime excludes it
+    let dir = TempDir::new("reader-stale");
+    let path = dir.write_log("stale.jsonl", &[entry("u-1", 29)]);
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    let june_1 = UNIX_EPOCH + Duration::from_secs(1_748_736_000);
+    file.set_modified(june_1).unwrap();
+
+    let scan = scan_file(&path, range(Some(day(2)), None)).unwrap();
+    assert!(scan.skipped);
+
+    file.set_modified(SystemTime::now()).unwrap();
+    let scan = scan_file(&path, range(Some(day(2)), None)).unwrap();
+    assert!(!scan.skipped);
+    assert_eq!(scan.entries.len(), 1);
+}
+
+#[test]
+fn test_scan_files_preserves_order() {
+    let dir = TempDir::new("reader-order");
+    for (name, uuid) in [("b", "u-b"), ("a", "u-a"), ("c", "u-c")] {
+        dir.write_log(&format!("{name}.jsonl"), &[entry(uuid, 29)]);
+    }
+    std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+    let files = jsonl_files(&dir).unwrap();
+    let scans = scan_files(&files, DateFilter::all());
+
+    let uuids: Vec<_> = scans
+        .into_iter()
+        .map(|scan| scan.unwrap().entries[0].uuid.clone().unwrap())
+        .collect();
+    assert_eq!(uuids, ["u-a", "u-b", "u-c"]);
+}
@@ -0,0 +1,246 @@
+mod common;
+
+use cc2report::ai_analyzer::{analyze_with_ai, ConversationData};
+use cc2report::config::{Config, ProviderKind};
+use cc2report::provider::{create_provider, ChatMessage, CompletionRequest};
+use cc2report::templates::Templates;
+use common::{analysis_json, isolate_home, openai_reply, StubResponse, StubServer};
+
+fn request(prompt: &str) -> CompletionRequest {
+    CompletionRequest {
+        system: "You are a test".to_string(),
+        messages: vec![ChatMessage::user(prompt)],
+        response_schema: None,
+    }
+}
+
+#[tokio::test]
+async fn test_custom_base_url_and_headers() {
+    let server = StubServer::start(vec![StubResponse::json(200, openai_reply("hello"))]);
+
+    let mut config = Config::default();
+    config.api.openai_api_key = Some("sk-test".to_string());
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+    config
+        .api
+        .extra_headers
+        .insert("OpenAI-Organization".to_string(), "org-123".to_string());
+
+    let provider = create_provider(&config.api).unwrap();
+    let completion = provider.complete(&request("ping")).await.unwrap();
+
+    assert_eq!(completion.text, "hello");
+    assert_eq!(completion.usage.unwrap().total_tokens, 150);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "/v1/chat/completions");
+    assert_eq!(requests[0].header("authorization"), Some("Bearer sk-test"));
+    assert_eq!(requests[0].header("openai-organization"), Some("org-123"));
+    assert_eq!(requests[0].json()["messages"][1]["content"], "ping");
+}
+
+#[test]
+fn test_header_values_may_contain_separators() {
+    let matches = cc2report::cli::build_cli().get_matches_from([
+        "cc2report",
+        "--header",
+        "Authorization: Bearer YWJjOmRlZg==",
+        "--header",
+        "api-key=a2V5Onh5eg==",
+    ]);
+    let config = Config::from_args(&matches).unwrap();
+
+    let headers = &config.api.extra_headers;
+    assert_eq!(headers["Authorization"], "Bearer YWJjOmRlZg==");
+    assert_eq!(headers["api-key"], "a2V5Onh5eg==");
+
+    let matches =
+        cc2report::cli::build_cli().get_matches_from(["cc2report", "--header", "no-separator"]);
+    assert!(Config::from_args(&matches).is_err());
+}
+
+#[tokio::test]
+async fn test_azure_style_endpoint() {
+    let server = StubServer::start(vec![StubResponse::json(200, openai_reply("azure"))]);
+
+    let mut config = Config::default();
+    config.api.openai_api_key = None;
+    config.api.base_url = Some(format!(
+        "{}/openai/deployments/gpt-4o?api-version=2024-06-01",
+        server.base_url()
+    ));
+    config
+        .api
+        .extra_headers
+        .insert("api-key".to_string(), "azure-key".to_string());
+
+    let provider = create_provider(&config.api).unwrap();
+    let completion = provider.complete(&request("ping")).await.unwrap();
+    assert_eq!(completion.text, "azure");
+
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].path,
+        "/openai/deployments/gpt-4o/chat/completions?api-version=2024-06-01"
+    );
+    assert_eq!(requests[0].header("api-key"), Some("azure-key"));
+    assert_eq!(requests[0].header("authorization"), None);
+}
+
+#[tokio::test]
+async fn test_hosted_openai_requires_key() {
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::OpenAi;
+    config.api.openai_api_key = None;
+    config.api.base_url = None;
+
+    assert!(create_provider(&config.api).is_err());
+}
+
+#[tokio::test]
+async fn test_analyze_with_ai_against_stub() {
+    isolate_home();
+    let server = StubServer::start(vec![StubResponse::json(
+        200,
+        openai_reply(&format!(
+            "```json\n{}\n```",
+            analysis_json("Stubbed Project")
+        )),
+    )]);
+
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::Ollama;
+    config.api.model = "test-model".to_string();
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+
+    let project_name = format!("stub-project-{}", std::process::id());
+    let conversations = vec![ConversationData {
+        project_name: project_name.clone(),
+        user_messages: vec!["Please add a stub server".to_string()],
+        assistant_actions: vec!["Created tests/common/mod.rs".to_string()],
+        timestamps: vec!["2025-06-29T10:00:00.000Z".to_string()],
+        summaries: vec![],
+        commits: vec![],
+    }];
+
+    let provider = create_provider(&config.api).unwrap();
+    let (results, tracker, _stats) = analyze_with_ai(
+        provider,
+        &Templates::default(),
+        conversations,
+        "en",
+        true,
+        1,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results[&project_name].project_title, "Stubbed Project");
+    assert_eq!(tracker.total_usage.total_tokens, 150);
+    assert_eq!(server.requests()[0].json()["model"], "test-model");
+}
+
+#[tokio::test]
+async fn test_parallel_work_queue() {
+    isolate_home();
+    let server = StubServer::start(vec![StubResponse::json(
+        200,
+        openai_reply(&analysis_json("Queued Project")),
+    )]);
+
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::Ollama;
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+
+    let conversations: Vec<ConversationData> = (0..5)
+        .map(|i| ConversationData {
+            project_name: format!("queued-{}-{i}", std::process::id()),
+            user_messages: vec![format!("Task {i}")],
+            assistant_actions: vec![],
+            timestamps: vec![],
+            summaries: vec![],
+            commits: vec![],
+        })
+        .collect();
+
+    let provider = create_provider(&config.api).unwrap();
+    let (results, tracker, _stats) = analyze_with_ai(
+        provider,
+        &Templates::default(),
+        conversations,
+        "en",
+        true,
+        3,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results.len(), 5);
+    assert_eq!(server.requests().len(), 5);
+    assert_eq!(tracker.total_usage.total_tokens, 5 * 150);
+}
+
+#[tokio::test]
+async fn test_partial_usage_does_not_fail_the_request() {
+    let server = StubServer::start(vec![
+        StubResponse::json(
+            200,
+            serde_json::json!({
+                "choices": [{ "message": { "role": "assistant", "content": "ollama" } }],
+                "usage": { "prompt_tokens": 120, "completion_tokens": 30 }
+            }),
+        ),
+        StubResponse::json(
+            200,
+            serde_json::json!({
+                "choices": [{ "message": { "role": "assistant", "content": "proxy" } }],
+                "usage": { "total_tokens": 99 }
+            }),
+        ),
+        StubResponse::json(
+            200,
+            serde_json::json!({
+                "content": [{ "type": "text", "text": "anthropic" }],
+                "usage": { "output_tokens": 30 }
+            }),
+        ),
+    ]);
+
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::Ollama;
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+    let provider = create_provider(&config.api).unwrap();
+
+    let completion = provider.complete(&request("ping")).await.unwrap();
+    assert_eq!(completion.text, "ollama");
+    let usage = completion.usage.unwrap();
+    assert_eq!(
+        (
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            usage.total_tokens
+        ),
+        (120, 30, 150)
+    );
+
+    let usage = provider.complete(&request("ping")).await.unwrap().usage;
+    let usage = usage.unwrap();
+    assert_eq!(
+        (
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            usage.total_tokens
+        ),
+        (0, 0, 99)
+    );
+
+    config.api.provider = ProviderKind::Anthropic;
+    config.api.anthropic_api_key = Some("sk-ant-test".to_string());
+    config.api.base_url = Some(server.base_url());
+    let provider = create_provider(&config.api).unwrap();
+    let completion = provider.complete(&request("ping")).await.unwrap();
+    assert_eq!(completion.text, "anthropic");
+    assert_eq!(completion.usage.unwrap().total_tokens, 30);
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use cc2report::cli;
+use cc2report::config::Config;
+use cc2report::date_filter::DateFilter;
+use cc2report::project_filter::ProjectFilter;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use common::TempDir;
+use std::path::Path;
+
+fn filter(include: &[&str], exclude: &[&str]) -> ProjectFilter {
+    let strings = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+    ProjectFilter::new(&strings(include), &strings(exclude)).unwrap()
+}
+
+/// Log directory with one project per (encoded directory, cwd) pair
+fn fixture(name: &str, projects: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new(&format!("projects-{name}"));
+    for (encoded, cwd) in projects {
+        dir.write_log(
+            &format!("logs/{encoded}/s-1.jsonl"),
+            &[format!(
+                r#"{{"parentUuid":null,"sessionId":"{encoded}","cwd":"{cwd}","type":"user","message":{{"role":"user","content":"Work on {encoded}"}},"uuid":"{encoded}-u","timestamp":"2025-06-29T10:00:00.000Z"}}"#
+            )],
+        );
+    }
+    dir
+}
+
+async fn report_titles(dir: &Path, project_filter: &ProjectFilter) -> Vec<String> {
+    let report = analyze_conversations_with_ai(
+        &dir.join("logs"),
+        &dir.join("index"),
+        DateFilter::all(),
+        project_filter,
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+    let mut titles: Vec<_> = report.projects.into_iter().map(|p| p.title).collect();
+    titles.sort();
+    titles
+}
+
+#[test]
+fn test_patterns_match_name_cwd_and_last_component() {
+    let client = filter(&["client-*"], &[]);
+    assert!(client.matches("~/work/client-acme", []));
+    assert!(client.matches("encoded/name", ["/srv/repos/client-acme"]));
+    assert!(!client.matches("~/work/internal", ["/srv/repos/internal"]));
+
+    let no_scratch = filter(&[], &["/tmp/*", "*scratch*"]);
+    assert!(no_scratch.matches("~/work/app", ["/home/me/work/app"]));
+    assert!(!no_scratch.matches("~/scratch/app", []));
+    assert!(!no_scratch.matches("app", ["/tmp/app"]));
+
+    assert!(ProjectFilter::all().matches("anything", []));
+    assert!(ProjectFilter::new(&["[".to_string()], &[]).is_err());
+}
+
+#[tokio::test]
+async fn test_report_only_contains_selected_projects() {
+    let dir = fixture(
+        "select",
+        &[
+            ("-srv-client-a", "/srv/client-a"),
+            ("-srv-client-b", "/srv/client-b"),
+            ("-srv-scratch", "/srv/scratch"),
+        ],
+    );
+
+    assert_eq!(report_titles(&dir, &ProjectFilter::all()).await.len(), 3);
+    assert_eq!(
+        report_titles(&dir, &filter(&["/srv/client-*"], &["*-b"]))
+            .await
+            .len(),
+        1
+    );
+    assert_eq!(
+        report_titles(&dir, &filter(&[], &["scratch"])).await.len(),
+        2
+    );
+}
+
+#[test]
+fn test_settings_file_ignores_projects() {
+    let dir = TempDir::new("settings");
+    let settings = dir.join("config.toml");
+    std::fs::write(&settings, "[projects]\nignore = [\"~/scratch/*\"]\n").unwrap();
+
+    let matches = cli::build_cli().get_matches_from([
+        "cc2report",
+        "--config",
+        settings.to_str().unwrap(),
+        "--project",
+        "client-*",
+        "--exclude-project",
+        "*-old",
+    ]);
+    let config = Config::from_args(&matches).unwrap();
+    assert_eq!(config.processing.include_projects, ["client-*"]);
+    assert_eq!(config.processing.exclude_projects, ["~/scratch/*", "*-old"]);
+
+    std::fs::write(&settings, "[projects]\nignore = \"not a list\"\n").unwrap();
+    assert!(Config::from_args(&matches).is_err());
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use cc2report::date_filter::DateFilter;
+use cc2report::project_filter::ProjectFilter;
+use cc2report::project_path::{decode, encode, repository_root, resolve};
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use common::TempDir;
+use std::path::{Path, PathBuf};
+
+/// A repository at `root/my-app` with a linked worktree at `root/my-app-feature`
+fn repository_with_worktree(root: &Path) -> (PathBuf, PathBuf) {
+    let repo = root.join("my-app");
+    let worktree = root.join("my-app-feature");
+    let git_dir = repo.join(".git").join("worktrees").join("feature");
+    std::fs::create_dir_all(&git_dir).unwrap();
+    std::fs::create_dir_all(repo.join("src")).unwrap();
+    std::fs::write(git_dir.join("commondir"), "../..\n").unwrap();
+    std::fs::create_dir_all(&worktree).unwrap();
+    std::fs::write(
+        worktree.join(".git"),
+        format!("gitdir: {}\n", git_dir.display()),
+    )
+    .unwrap();
+    (repo, worktree)
+}
+
+#[test]
+fn test_recorded_cwd_wins_over_the_encoded_name() {
+    let encoded = "-srv-my-app";
+    assert_eq!(decode(encoded), Path::new("/srv/my/app"));
+    assert_eq!(encode(Path::new("/srv/my-app")), encoded);
+    assert_eq!(encode(Path::new("/srv/my/app")), encoded);
+
+    // The cwd matching the directory name is preferred over later `cd`s
+    let cwds = ["/tmp", "/srv/my-app", "/tmp"];
+    assert_eq!(resolve(encoded, cwds), Path::new("/srv/my-app"));
+    assert_eq!(resolve("-renamed", cwds), Path::new("/tmp"));
+    assert_eq!(resolve(encoded, []), Path::new("/srv/my/app"));
+}
+
+#[test]
+fn test_worktrees_and_subdirectories_resolve_to_the_repository() {
+    let root = TempDir::new("paths-root");
+    let (repo, worktree) = repository_with_worktree(&root);
+
+    assert_eq!(repository_root(&repo.join("src")), repo);
+    assert_eq!(repository_root(&worktree), repo);
+    assert_eq!(repository_root(&root), *root);
+    assert_eq!(
+        repository_root(Path::new("/nonexistent/project")),
+        Path::new("/nonexistent/project")
+    );
+}
+
+#[tokio::test]
+async fn test_report_groups_directories_by_repository() {
+    let root = TempDir::new("paths-report");
+    let (repo, worktree) = repository_with_worktree(&root);
+    let cwds = [repo.clone(), repo.join("src"), worktree];
+    for (index, cwd) in cwds.iter().enumerate() {
+        root.write_log(
+            &format!("logs/{}/s.jsonl", encode(cwd)),
+            &[format!(
+                r#"{{"parentUuid":null,"sessionId":"s-{index}","cwd":"{}","type":"user","message":{{"role":"user","content":"Task {index}"}},"uuid":"u-{index}","timestamp":"2025-06-29T1{index}:00:00.000Z"}}"#,
+                cwd.display()
+            )],
+        );
+    }
+
+    let report = analyze_conversations_with_ai(
+        &root.join("logs"),
+        &root.join("index"),
+        DateFilter::all(),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(report.projects.len(), 1);
+    assert_eq!(report.projects[0].sessions.len(), 3);
+}
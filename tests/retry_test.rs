@@ -0,0 +1,190 @@
+mod common;
+
+use cc2report::ai_analyzer::{analyze_with_ai, ConversationData};
+use cc2report::config::Config;
+use cc2report::error::ApiError;
+use cc2report::provider::{
+    create_provider, parse_reset_duration, retry_after_from_headers, ChatMessage, CompletionRequest,
+};
+use cc2report::templates::Templates;
+use common::{isolate_home, openai_reply, StubResponse, StubServer};
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::time::Duration;
+
+fn stub_config(server: &StubServer) -> Config {
+    let mut config = Config::default();
+    config.api.openai_api_key = Some("sk-test".to_string());
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+    config.api.max_retries = 2;
+    config.api.retry_delay_ms = 1;
+    config
+}
+
+fn request() -> CompletionRequest {
+    CompletionRequest {
+        system: "You are a test".to_string(),
+        messages: vec![ChatMessage::user("ping")],
+        response_schema: None,
+    }
+}
+
+#[tokio::test]
+async fn test_rate_limit_is_retried() {
+    let server = StubServer::start(vec![
+        StubResponse::json(
+            429,
+            serde_json::json!({"error": {"message": "Rate limit reached"}}),
+        )
+        .with_header("retry-after", "0"),
+        StubResponse::json(200, openai_reply("recovered")),
+    ]);
+
+    let provider = create_provider(&stub_config(&server).api).unwrap();
+    let completion = provider.complete(&request()).await.unwrap();
+
+    assert_eq!(completion.text, "recovered");
+    assert_eq!(completion.retries, 1);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_server_errors_exhaust_retries() {
+    let server = StubServer::start(vec![StubResponse::json(
+        503,
+        serde_json::json!({"error": {"message": "overloaded"}}),
+    )]);
+
+    let provider = create_provider(&stub_config(&server).api).unwrap();
+    let err = provider.complete(&request()).await.unwrap_err();
+
+    assert!(matches!(err, ApiError::RetriesExhausted { retries: 2, .. }));
+    assert_eq!(err.retries(), 2);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_client_errors_are_not_retried() {
+    let server = StubServer::start(vec![StubResponse::json(
+        400,
+        serde_json::json!({"error": {"message": "bad request"}}),
+    )]);
+
+    let provider = create_provider(&stub_config(&server).api).unwrap();
+    let err = provider.complete(&request()).await.unwrap_err();
+
+    assert!(matches!(err, ApiError::RequestFailed { .. }));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_projects_are_reported() {
+    let _home = isolate_home();
+    let server = StubServer::start(vec![StubResponse::json(
+        500,
+        serde_json::json!({"error": {"message": "boom"}}),
+    )]);
+
+    let provider = create_provider(&stub_config(&server).api).unwrap();
+    let conversations = vec![ConversationData {
+        project_name: "retry-project".to_string(),
+        user_messages: vec!["Fix the flaky test".to_string()],
+        assistant_actions: vec![],
+        timestamps: vec![],
+        summaries: vec![],
+        commits: vec![],
+    }];
+
+    let (results, _tracker, stats) = analyze_with_ai(
+        provider,
+        &Templates::default(),
+        conversations,
+        "en",
+        true,
+        1,
+    )
+    .await
+    .unwrap();
+
+    assert!(results.is_empty());
+    assert_eq!(stats.requests, 1);
+    assert_eq!(stats.retries, 2);
+    assert_eq!(stats.failures.len(), 1);
+    assert_eq!(stats.failures[0].project, "retry-project");
+    assert!(stats
+        .get_summary_string("en")
+        .contains("Failed projects: 1"));
+}
+
+#[test]
+fn test_parse_reset_duration() {
+    assert_eq!(parse_reset_duration("6m0s"), Some(Duration::from_secs(360)));
+    assert_eq!(
+        parse_reset_duration("20ms"),
+        Some(Duration::from_millis(20))
+    );
+    assert_eq!(
+        parse_reset_duration("1h2m3.5s"),
+        Some(Duration::from_millis(3_723_500))
+    );
+    assert_eq!(parse_reset_duration("2"), Some(Duration::from_secs(2)));
+    assert_eq!(parse_reset_duration("soon"), None);
+}
+
+#[test]
+fn test_out_of_range_waits_do_not_panic() {
+    let huge = format!("{}s", "9".repeat(400));
+    assert_eq!(parse_reset_duration(&huge), None);
+
+    for value in ["1e300", "inf", "NaN"] {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static(value));
+        assert_eq!(retry_after_from_headers(&headers), None, "{value}");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after-ms", HeaderValue::from_static(value));
+        assert_eq!(retry_after_from_headers(&headers), None, "{value}");
+    }
+
+    // A valid but very long wait is capped at the maximum backoff
+    let mut headers = HeaderMap::new();
+    headers.insert("retry-after", HeaderValue::from_static("86400"));
+    assert_eq!(
+        retry_after_from_headers(&headers),
+        Some(Duration::from_secs(120))
+    );
+}
+
+#[test]
+fn test_retry_after_headers() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(retry_after_from_headers(&headers), None);
+
+    headers.insert("retry-after", HeaderValue::from_static("7"));
+    assert_eq!(
+        retry_after_from_headers(&headers),
+        Some(Duration::from_secs(7))
+    );
+
+    headers.insert("retry-after-ms", HeaderValue::from_static("250"));
+    assert_eq!(
+        retry_after_from_headers(&headers),
+        Some(Duration::from_millis(250))
+    );
+
+    // OpenAI rate-limit headers: only the exhausted limit matters
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "x-ratelimit-remaining-requests",
+        HeaderValue::from_static("0"),
+    );
+    headers.insert("x-ratelimit-reset-requests", HeaderValue::from_static("1s"));
+    headers.insert(
+        "x-ratelimit-remaining-tokens",
+        HeaderValue::from_static("5000"),
+    );
+    headers.insert("x-ratelimit-reset-tokens", HeaderValue::from_static("6m0s"));
+    assert_eq!(
+        retry_after_from_headers(&headers),
+        Some(Duration::from_secs(1))
+    );
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use cc2report::config::{Config, ProviderKind};
+use cc2report::date_filter::{DateFilter, ReportTimeZone};
+use cc2report::project_filter::ProjectFilter;
+use cc2report::provider::create_provider;
+use cc2report::session::{summarize_sessions, SessionTracker};
+use cc2report::smart_analyzer::generate_smart_report;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use chrono::{TimeZone, Utc};
+use common::{analysis_json, isolate_home, openai_reply, StubResponse, StubServer, TempDir};
+use std::path::Path;
+
+/// All dates, with session times shown in UTC like the fixture timestamps
+fn utc() -> DateFilter {
+    DateFilter::all().with_tz(ReportTimeZone::Named(chrono_tz::UTC))
+}
+
+#[test]
+fn test_session_tracker() {
+    let mut tracker = SessionTracker::new();
+    let at = |h, m| Utc.with_ymd_and_hms(2025, 6, 29, h, m, 0).unwrap();
+
+    tracker.record("b", at(14, 0), true);
+    tracker.record("a", at(10, 30), true);
+    tracker.record("a", at(9, 0), false);
+    tracker.record("a", at(11, 45), true);
+
+    let sessions = tracker.into_sessions();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0].session_id, "a");
+    assert_eq!(sessions[0].start, at(9, 0));
+    assert_eq!(sessions[0].end, at(11, 45));
+    assert_eq!(sessions[0].message_count, 2);
+
+    let summaries = summarize_sessions(sessions, &[]);
+    assert_eq!(summaries[0].duration_minutes, 165);
+    assert_eq!(summaries[0].time_range(), "2025-06-29 09:00–11:45");
+    assert_eq!(summaries[1].duration_minutes, 0);
+}
+
+#[tokio::test]
+async fn test_offline_report_lists_sessions() {
+    let dir = TempDir::new("sessions");
+    let report = analyze_conversations_with_ai(
+        Path::new("tests/data"),
+        &dir.join("index"),
+        utc(),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let sessions = &report.projects[0].sessions;
+    assert_eq!(sessions.len(), 2);
+
+    assert_eq!(sessions[0].session_id, "test-session-1");
+    assert_eq!(sessions[0].message_count, 4);
+    assert_eq!(sessions[0].duration_minutes, 1);
+    assert_eq!(sessions[0].summary, "Test message 1");
+
+    assert_eq!(sessions[1].session_id, "test-session-2");
+    assert_eq!(sessions[1].message_count, 2);
+    assert_eq!(sessions[1].summary, "Different session");
+
+    let output = dir.join("report.md");
+    generate_smart_report(&report, Some(&output), "en").unwrap();
+    let markdown = std::fs::read_to_string(&output).unwrap();
+
+    assert!(markdown.contains("**Sessions**:"));
+    assert!(markdown.contains("- 2025-06-29 10:00–10:01 (1 min, 4 messages): Test message 1"));
+}
+
+#[tokio::test]
+async fn test_per_session_ai_summaries() {
+    isolate_home();
+    let server = StubServer::start(vec![StubResponse::json(
+        200,
+        openai_reply(&analysis_json("Session Project")),
+    )]);
+
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::Ollama;
+    config.api.model = format!("session-model-{}", std::process::id());
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+    let provider = create_provider(&config.api).unwrap();
+
+    let index = TempDir::new("sessions-ai-index");
+    let report = analyze_conversations_with_ai(
+        Path::new("tests/data"),
+        &index,
+        utc(),
+        &ProjectFilter::all(),
+        Some(provider),
+        &Templates::default(),
+        "en",
+        true,
+        2,
+        true,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    // One request for the project and one per session
+    assert_eq!(server.requests().len(), 3);
+
+    let project = &report.projects[0];
+    assert_eq!(project.title, "Session Project");
+    assert_eq!(project.sessions.len(), 2);
+    for session in &project.sessions {
+        assert_eq!(session.summary, "Stub server answered");
+    }
+}
+
+#[tokio::test]
+async fn test_failed_session_is_not_a_failed_project() {
+    isolate_home();
+    let reply = StubResponse::json(200, openai_reply(&analysis_json("Session Project")));
+    let server = StubServer::start(vec![
+        reply.clone(),
+        reply,
+        StubResponse::json(500, serde_json::json!({"error": {"message": "boom"}})),
+    ]);
+
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::Ollama;
+    config.api.model = format!("failed-session-model-{}", std::process::id());
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+    config.api.max_retries = 0;
+    let provider = create_provider(&config.api).unwrap();
+
+    let index = TempDir::new("sessions-failed-index");
+    let report = analyze_conversations_with_ai(
+        Path::new("tests/data"),
+        &index,
+        utc(),
+        &ProjectFilter::all(),
+        Some(provider),
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        true,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(report.projects[0].title, "Session Project");
+    assert_eq!(report.api_stats.as_ref().unwrap().failures.len(), 1);
+    let insights = &report.summary.productivity_insights;
+    assert!(insights.contains("Note: 1 session(s) failed to analyze."));
+    assert!(!insights.contains("project(s) failed"), "{insights}");
+}
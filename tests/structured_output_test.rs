@@ -0,0 +1,207 @@
+mod common;
+
+use cc2report::ai_analyzer::{
+    analysis_response_schema, analyze_with_ai, parse_analysis_response, ConversationData,
+};
+use cc2report::config::{Config, ProviderKind};
+use cc2report::provider::{create_provider, ChatMessage, CompletionRequest};
+use cc2report::templates::Templates;
+use common::{analysis_json, isolate_home, openai_reply, StubResponse, StubServer};
+
+fn stub_config(server: &StubServer, provider: ProviderKind) -> Config {
+    let mut config = Config::default();
+    config.api.provider = provider;
+    config.api.model = "test-model".to_string();
+    config.api.base_url = Some(match provider {
+        ProviderKind::Anthropic => server.base_url(),
+        _ => format!("{}/v1", server.base_url()),
+    });
+    config
+}
+
+fn conversation(name: &str) -> Vec<ConversationData> {
+    vec![ConversationData {
+        project_name: format!("{name}-{}", std::process::id()),
+        user_messages: vec!["Return structured output".to_string()],
+        assistant_actions: vec![],
+        timestamps: vec![],
+        summaries: vec![],
+        commits: vec![],
+    }]
+}
+
+#[test]
+fn test_schema_matches_response_struct() {
+    let schema = analysis_response_schema().schema;
+    let sample: serde_json::Value = serde_json::from_str(&analysis_json("Sample")).unwrap();
+
+    let mut required: Vec<&str> = schema["required"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    let mut fields: Vec<&str> = sample
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(|k| k.as_str())
+        .collect();
+    required.sort_unstable();
+    fields.sort_unstable();
+    assert_eq!(required, fields);
+
+    let activity = &schema["properties"]["main_activities"]["items"];
+    assert_eq!(activity["required"].as_array().unwrap().len(), 4);
+    assert_eq!(schema["additionalProperties"], false);
+}
+
+#[test]
+fn test_parse_analysis_response_tolerates_fences() {
+    let fenced = format!("```json\n{}\n```", analysis_json("Fenced"));
+    assert_eq!(
+        parse_analysis_response(&fenced).unwrap().project_title,
+        "Fenced"
+    );
+    assert!(parse_analysis_response("{\"project_title\": ").is_err());
+}
+
+#[tokio::test]
+async fn test_openai_request_uses_json_schema() {
+    isolate_home();
+    let server = StubServer::start(vec![StubResponse::json(
+        200,
+        openai_reply(&analysis_json("Schema Project")),
+    )]);
+
+    let provider = create_provider(&stub_config(&server, ProviderKind::Ollama).api).unwrap();
+    let (results, _tracker, _stats) = analyze_with_ai(
+        provider,
+        &Templates::default(),
+        conversation("schema"),
+        "en",
+        true,
+        1,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results.len(), 1);
+    let body = server.requests()[0].json();
+    assert_eq!(body["response_format"]["type"], "json_schema");
+    assert_eq!(
+        body["response_format"]["json_schema"]["name"],
+        "work_analysis"
+    );
+    assert_eq!(body["response_format"]["json_schema"]["strict"], true);
+}
+
+#[tokio::test]
+async fn test_anthropic_forces_tool_call() {
+    isolate_home();
+    let input: serde_json::Value = serde_json::from_str(&analysis_json("Tool Project")).unwrap();
+    let server = StubServer::start(vec![StubResponse::json(
+        200,
+        serde_json::json!({
+            "content": [{
+                "type": "tool_use",
+                "id": "toolu_01",
+                "name": "work_analysis",
+                "input": input
+            }],
+            "usage": { "input_tokens": 100, "output_tokens": 40 }
+        }),
+    )]);
+
+    let provider = create_provider(&stub_config(&server, ProviderKind::Anthropic).api).unwrap();
+    let (results, tracker, _stats) = analyze_with_ai(
+        provider,
+        &Templates::default(),
+        conversation("tool"),
+        "en",
+        true,
+        1,
+    )
+    .await
+    .unwrap();
+
+    let analysis = results.values().next().unwrap();
+    assert_eq!(analysis.project_title, "Tool Project");
+    assert_eq!(tracker.total_usage.total_tokens, 140);
+
+    let request = &server.requests()[0];
+    assert_eq!(request.path, "/v1/messages");
+    let body = request.json();
+    assert_eq!(body["tools"][0]["name"], "work_analysis");
+    assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+    assert_eq!(body["tool_choice"]["type"], "tool");
+    assert_eq!(body["tool_choice"]["name"], "work_analysis");
+}
+
+#[tokio::test]
+async fn test_invalid_json_is_repaired() {
+    isolate_home();
+    let server = StubServer::start(vec![
+        StubResponse::json(200, openai_reply("{\"project_title\": \"Broken\",")),
+        StubResponse::json(200, openai_reply(&analysis_json("Repaired Project"))),
+    ]);
+
+    let provider = create_provider(&stub_config(&server, ProviderKind::Ollama).api).unwrap();
+    let (results, tracker, stats) = analyze_with_ai(
+        provider,
+        &Templates::default(),
+        conversation("repair"),
+        "en",
+        true,
+        1,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        results.values().next().unwrap().project_title,
+        "Repaired Project"
+    );
+    assert_eq!(stats.requests, 2);
+    assert!(stats.failures.is_empty());
+    assert_eq!(tracker.total_usage.total_tokens, 300);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    let messages = requests[1].json()["messages"].clone();
+    let messages = messages.as_array().unwrap();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[2]["role"], "assistant");
+    assert_eq!(messages[2]["content"], "{\"project_title\": \"Broken\",");
+    assert!(messages[3]["content"]
+        .as_str()
+        .unwrap()
+        .contains("could not be parsed"));
+}
+
+#[tokio::test]
+async fn test_unsupported_response_format_falls_back() {
+    let server = StubServer::start(vec![
+        StubResponse::json(
+            400,
+            serde_json::json!({
+                "error": { "message": "Invalid parameter: 'response_format' of type 'json_schema' is not supported with this model." }
+            }),
+        ),
+        StubResponse::json(200, openai_reply("plain")),
+    ]);
+
+    let provider = create_provider(&stub_config(&server, ProviderKind::Ollama).api).unwrap();
+    let request = CompletionRequest {
+        system: "You are a test".to_string(),
+        messages: vec![ChatMessage::user("ping")],
+        response_schema: Some(analysis_response_schema()),
+    };
+    let completion = provider.complete(&request).await.unwrap();
+
+    assert_eq!(completion.text, "plain");
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].json().get("response_format").is_some());
+    assert!(requests[1].json().get("response_format").is_none());
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use cc2report::config::{Config, ProviderKind};
+use cc2report::date_filter::DateFilter;
+use cc2report::parser::{self, LogRecord};
+use cc2report::project_filter::ProjectFilter;
+use cc2report::provider::create_provider;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use common::{analysis_json, isolate_home, openai_reply, StubResponse, StubServer, TempDir};
+
+/// A `logs` directory with one project whose summary record is stored in a separate file
+fn log_dir_with_summaries(name: &str) -> TempDir {
+    let dir = TempDir::new(name);
+
+    let entries = [
+        r#"{"parentUuid":null,"sessionId":"s-1","type":"user","message":{"role":"user","content":"Please implement the parser"},"uuid":"u-1","timestamp":"2025-06-29T10:00:00.000Z"}"#,
+        r#"{"parentUuid":"u-1","sessionId":"s-1","type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Implemented the parser module"}]},"uuid":"u-2","timestamp":"2025-06-29T10:05:00.000Z"}"#,
+    ];
+    dir.write_log(
+        "logs/-home-user-summaries/s-1.jsonl",
+        &entries.map(str::to_string),
+    );
+
+    let summaries = [
+        r#"{"type":"summary","summary":"JSONL parser implementation","leafUuid":"u-2"}"#,
+        r#"{"type":"summary","summary":"Conversation outside the range","leafUuid":"unknown"}"#,
+    ];
+    dir.write_log(
+        "logs/-home-user-summaries/s-2.jsonl",
+        &summaries.map(str::to_string),
+    );
+
+    dir
+}
+
+#[test]
+fn test_summary_record_parses_as_summary() {
+    let line = r#"{"type":"summary","summary":"Test session summary","leafUuid":"leaf-1"}"#;
+    match serde_json::from_str::<LogRecord>(line).unwrap() {
+        LogRecord::Summary(summary) => {
+            assert_eq!(summary.summary, "Test session summary");
+            assert_eq!(summary.leaf_uuid, "leaf-1");
+        }
+        LogRecord::Entry(_) => panic!("summary parsed as a log entry"),
+    }
+}
+
+#[tokio::test]
+async fn test_offline_report_shows_linked_summaries() {
+    let dir = log_dir_with_summaries("offline-summaries");
+    let report = analyze_conversations_with_ai(
+        &dir.join("logs"),
+        &dir.join("index"),
+        DateFilter::all(),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let session = &report.projects[0].sessions[0];
+    assert_eq!(
+        session.claude_summaries,
+        vec!["JSONL parser implementation".to_string()]
+    );
+    assert_eq!(session.summary, "JSONL parser implementation");
+}
+
+#[tokio::test]
+async fn test_summaries_are_sent_to_the_model() {
+    isolate_home();
+    let dir = log_dir_with_summaries("ai-summaries");
+    let server = StubServer::start(vec![StubResponse::json(
+        200,
+        openai_reply(&analysis_json("Parser Project")),
+    )]);
+
+    let mut config = Config::default();
+    config.api.provider = ProviderKind::Ollama;
+    config.api.model = format!("summary-model-{}", std::process::id());
+    config.api.base_url = Some(format!("{}/v1", server.base_url()));
+    let provider = create_provider(&config.api).unwrap();
+
+    analyze_conversations_with_ai(
+        &dir.join("logs"),
+        &dir.join("index"),
+        DateFilter::all(),
+        &ProjectFilter::all(),
+        Some(provider),
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let prompt = server.requests()[0].json()["messages"][1]["content"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(prompt.contains("Session Summaries"));
+    assert!(prompt.contains("- JSONL parser implementation"));
+    assert!(!prompt.contains("Conversation outside the range"));
+}
+
+#[test]
+fn test_daily_report_collects_summaries() {
+    let dir = log_dir_with_summaries("daily-summaries");
+    let report = parser::analyze_logs(&dir.join("logs"), DateFilter::all(), "en").unwrap();
+
+    assert_eq!(report.projects.len(), 1);
+    assert_eq!(
+        report.projects[0].summaries,
+        vec!["JSONL parser implementation".to_string()]
+    );
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use cc2report::cli;
+use cc2report::config::Config;
+use cc2report::session::SessionSummary;
+use cc2report::smart_analyzer::*;
+use cc2report::templates::{create_default_template_file, Templates};
+use cc2report::token_tracker::TokenTracker;
+use common::TempDir;
+use std::path::PathBuf;
+
+fn report() -> SmartReport {
+    SmartReport {
+        date: "2025-06-29".to_string(),
+        summary: ExecutiveSummary {
+            total_work_items: 3,
+            completion_rate: 66.7,
+            key_focus_areas: vec!["Checkout".to_string(), "Shipping".to_string()],
+            productivity_insights: String::new(),
+        },
+        projects: vec![SmartProjectSummary {
+            project: "/srv/shop".to_string(),
+            title: "Shop".to_string(),
+            objective: "Faster checkout".to_string(),
+            work_breakdown: WorkBreakdown {
+                primary_activities: vec![SmartActivity {
+                    category: "Development".to_string(),
+                    summary: "Express shipping".to_string(),
+                    item_count: 2,
+                    complexity: ComplexityLevel::Moderate,
+                }],
+                effort_distribution: EffortMetrics {
+                    development: 100.0,
+                    debugging: 0.0,
+                    configuration: 0.0,
+                    other: 0.0,
+                },
+            },
+            outcomes: ProjectOutcomes {
+                delivered_value: vec!["Express shipping at checkout".to_string()],
+                technical_improvements: vec![],
+                unresolved_issues: vec!["Rounding <1 cent".to_string()],
+            },
+            sessions: vec![SessionSummary {
+                session_id: "s-b".to_string(),
+                start: "2025-06-29T15:00:00+02:00".to_string(),
+                end: "2025-06-29T16:30:00+02:00".to_string(),
+                duration_minutes: 90,
+                message_count: 4,
+                summary: "Add express shipping".to_string(),
+                claude_summaries: vec![],
+            }],
+            commits: vec![],
+            diffstat: None,
+            files: vec![],
+            commands: Default::default(),
+            active_time: None,
+        }],
+        token_tracker: None,
+        api_stats: None,
+        timeline: vec![],
+        active_time: None,
+    }
+}
+
+#[test]
+fn test_default_templates_render_the_built_in_report() {
+    let markdown = Templates::default()
+        .report
+        .render(None, &report(), "en")
+        .unwrap();
+
+    assert!(markdown.starts_with("# Work Report - 2025-06-29\n\n## Summary\n"));
+    assert!(markdown.contains("- **Completion rate**: 67%\n"));
+    assert!(markdown.contains("- **Focus areas**: Checkout, Shipping\n"));
+    assert!(markdown.contains("## Shop\n\n**Objective**: Faster checkout\n\n**Activities**:\n"));
+    assert!(markdown.contains("- Development - Express shipping\n"));
+    assert!(markdown.contains("- Express shipping at checkout\n"));
+    assert!(markdown.contains("**Unresolved Issues**:\n- Rounding <1 cent\n"));
+    assert!(
+        markdown.contains("- 2025-06-29 15:00–16:30 (90 min, 4 messages): Add express shipping\n")
+    );
+    assert!(markdown.ends_with(": Add express shipping\n\n"));
+
+    let mut with_tokens = report();
+    with_tokens.token_tracker = Some(TokenTracker::new());
+    let markdown = Templates::default()
+        .report
+        .render(None, &with_tokens, "en")
+        .unwrap();
+    assert!(markdown.contains("\n## Token Usage Summary\n"));
+
+    let german = Templates::default()
+        .report
+        .render(None, &report(), "de")
+        .unwrap();
+    assert!(german.contains("## Zusammenfassung\n\n- **Arbeitspunkte**: 3\n"));
+}
+
+#[test]
+fn test_custom_layout_with_loops_conditionals_and_partials() {
+    let mut templates = Templates::default().report;
+    templates.activity_item = "* {{ activity.summary }} ({{ activity.item_count }})\n".to_string();
+    let standup = "Standup {{ date }} ({{ lang }})\n\
+        {% for project in projects %}## {{ project.project }}\n\
+        {% for session in project.sessions %}{{ session.start | time }}-{{ session.end | time }} {{ session.summary }}\n{% endfor %}\
+        {% for activity in project.work_breakdown.primary_activities %}{% include \"activity_item\" %}{% endfor %}\
+        {% if project.outcomes.unresolved_issues %}Blocked: {{ project.outcomes.unresolved_issues | join(\"; \") }}\n{% endif %}\
+        {% if project.active_time %}never{% endif %}\
+        {% endfor %}";
+
+    let text = templates.render(Some(standup), &report(), "de").unwrap();
+    assert_eq!(
+        text,
+        "Standup 2025-06-29 (de)\n## /srv/shop\n15:00-16:30 Add express shipping\n* Express shipping (2)\nBlocked: Rounding <1 cent\n"
+    );
+
+    // The configured document is the fallback layout
+    templates.document = Some("{{ projects | length }} project".to_string());
+    assert_eq!(
+        templates.render(None, &report(), "en").unwrap(),
+        "1 project"
+    );
+
+    // Broken templates are errors, not silently empty reports
+    assert!(templates
+        .render(Some("{% for x in %}"), &report(), "en")
+        .is_err());
+    assert!(templates
+        .render(Some("{{ date | time }}"), &report(), "en")
+        .is_err());
+}
+
+#[test]
+fn test_customizing_a_partial_changes_the_built_in_layout() {
+    let mut templates = Templates::default().report;
+    templates.activity_item = "* {{ activity.summary }}\n".to_string();
+    let markdown = templates.render(None, &report(), "en").unwrap();
+    assert!(markdown.starts_with("# Work Report - 2025-06-29\n"));
+    assert!(markdown.contains("* Express shipping\n"));
+    assert!(!markdown.contains("- Development - Express shipping"));
+
+    // A generated template file holds the built-in layout
+    let root = TempDir::new("templates-generated");
+    let path = root.join("templates.toml");
+    create_default_template_file(&path).unwrap();
+    let generated = Templates::merge_with_defaults(Some(Templates::load_from_file(&path).unwrap()));
+    assert_eq!(generated.report, Templates::default().report);
+}
+
+#[test]
+fn test_old_placeholders_fall_back_to_the_default_partials() {
+    let defaults = Templates::default().report;
+    let mut templates = defaults.clone();
+    templates.header = "# {title} - {date}\n\n".to_string();
+    templates.project_section =
+        "### {index}. {title}\n\n**{purpose_label}**: {purpose}\n\n".to_string();
+    templates.activity_item = "* {{ activity.summary }} ({{activity.item_count}})\n".to_string();
+
+    templates.replace_legacy_partials();
+    assert_eq!(templates.header, defaults.header);
+    assert_eq!(templates.project_section, defaults.project_section);
+    assert_eq!(
+        templates.activity_item,
+        "* {{ activity.summary }} ({{activity.item_count}})\n"
+    );
+    assert!(!templates
+        .render(None, &report(), "en")
+        .unwrap()
+        .contains("{title}"));
+}
+
+#[test]
+fn test_template_option() {
+    let matches = cli::build_cli().get_matches_from(["cc2report", "--template", "standup.md.j2"]);
+    let config = Config::from_args(&matches).unwrap();
+    assert_eq!(config.output.template, Some(PathBuf::from("standup.md.j2")));
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use cc2report::cli;
+use cc2report::config::Config;
+use cc2report::date_filter::{DateFilter, ReportTimeZone};
+use cc2report::project_filter::ProjectFilter;
+use cc2report::smart_analyzer::generate_smart_report;
+use cc2report::templates::Templates;
+use cc2report::timeline::{GroupBy, TimelineBucket};
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use chrono::NaiveDate;
+use common::TempDir;
+
+/// A one-prompt session with a reply a few minutes later
+fn session(id: &str, start: &str, prompt: &str) -> String {
+    format!(
+        concat!(
+            r#"{{"parentUuid":null,"sessionId":"{id}","cwd":"/srv/my-app","type":"user","message":{{"role":"user","content":"{prompt}"}},"uuid":"{id}-u","timestamp":"{start}:00.000Z"}}"#,
+            "\n",
+            r#"{{"parentUuid":"{id}-u","sessionId":"{id}","type":"assistant","message":{{"role":"assistant","content":[{{"type":"text","text":"Done"}}]}},"uuid":"{id}-a","timestamp":"{start}:30.000Z"}}"#,
+            "\n"
+        ),
+        id = id,
+        start = start,
+        prompt = prompt
+    )
+}
+
+/// Monday and Tuesday of one week, then the Monday after
+fn fixture(name: &str) -> TempDir {
+    let dir = TempDir::new(&format!("timeline-{name}"));
+    for (id, start, prompt) in [
+        ("s-1", "2025-06-23T09:00", "Add the parser"),
+        ("s-2", "2025-06-24T14:15", "Fix the login bug"),
+        ("s-3", "2025-06-30T08:45", "Write the release notes"),
+    ] {
+        dir.write_log(
+            &format!("logs/-srv-my-app/{id}.jsonl"),
+            &[session(id, start, prompt)],
+        );
+    }
+    dir
+}
+
+fn utc() -> DateFilter {
+    DateFilter::all().with_tz(ReportTimeZone::Named(chrono_tz::UTC))
+}
+
+fn date(month: u32, day: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(2025, month, day)
+}
+
+#[tokio::test]
+async fn test_timeline_groups_topics_by_day_and_week() {
+    let dir = fixture("buckets");
+    let mut report = analyze_conversations_with_ai(
+        &dir.join("logs"),
+        &dir.join("index"),
+        utc(),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let days: Vec<_> = report.timeline.iter().map(|b| b.label()).collect();
+    assert_eq!(days, ["2025-06-23", "2025-06-24", "2025-06-30"]);
+    let entry = &report.timeline[1].entries[0];
+    assert_eq!(entry.project, "/srv/my-app");
+    assert_eq!(entry.time_range(false), "14:15");
+
+    report.group_timeline(GroupBy::Week);
+    let weeks: Vec<_> = report.timeline.iter().map(|b| b.label()).collect();
+    assert_eq!(
+        weeks,
+        ["2025-06-23 – 2025-06-29", "2025-06-30 – 2025-07-06"]
+    );
+    assert_eq!(report.timeline[0].entries.len(), 2);
+    assert_eq!(
+        report.timeline[0].entries[1].time_range(true),
+        "06-24 14:15"
+    );
+
+    let output = dir.join("report.md");
+    generate_smart_report(&report, Some(&output), "en").unwrap();
+    let markdown = std::fs::read_to_string(&output).unwrap();
+    assert!(markdown.contains("## Timeline"));
+    assert!(markdown.contains("### 2025-06-23 – 2025-06-29"));
+    assert!(markdown.contains("- 06-23 09:00"));
+
+    report.group_timeline(GroupBy::Project);
+    assert!(report.timeline.is_empty());
+}
+
+#[tokio::test]
+async fn test_timeline_days_follow_the_time_zone() {
+    let dir = fixture("tz");
+    // 08:45 UTC on June 30 is still June 29 in Honolulu (UTC-10)
+    let honolulu: ReportTimeZone = "Pacific/Honolulu".parse().unwrap();
+    let report = analyze_conversations_with_ai(
+        &dir.join("logs"),
+        &dir.join("index"),
+        DateFilter::all().with_tz(honolulu),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let last = report.timeline.last().unwrap();
+    assert_eq!(last.label(), "2025-06-29");
+    assert_eq!(last.entries[0].time_range(false), "22:45");
+}
+
+#[test]
+fn test_default_grouping_follows_the_period() {
+    assert_eq!(
+        GroupBy::for_period(&DateFilter::day(date(6, 29).unwrap()), &[]),
+        GroupBy::Project
+    );
+    assert_eq!(
+        GroupBy::for_period(&DateFilter::range(date(6, 23), date(6, 29)), &[]),
+        GroupBy::Day
+    );
+    assert_eq!(
+        GroupBy::for_period(&DateFilter::range(date(6, 1), date(6, 30)), &[]),
+        GroupBy::Week
+    );
+    assert_eq!(
+        GroupBy::for_period(&DateFilter::all(), &[]),
+        GroupBy::Project
+    );
+}
+
+fn day_bucket(day: &str) -> TimelineBucket {
+    TimelineBucket {
+        start: day.to_string(),
+        end: day.to_string(),
+        entries: Vec::new(),
+    }
+}
+
+#[test]
+fn test_open_ended_periods_use_the_days_with_activity() {
+    let week = [day_bucket("2025-06-23"), day_bucket("2025-06-24")];
+    let month = [day_bucket("2025-06-02"), day_bucket("2025-06-30")];
+
+    assert_eq!(GroupBy::for_period(&DateFilter::all(), &week), GroupBy::Day);
+    assert_eq!(
+        GroupBy::for_period(&DateFilter::all(), &month),
+        GroupBy::Week
+    );
+    assert_eq!(
+        GroupBy::for_period(&DateFilter::range(date(6, 24), None), &week),
+        GroupBy::Project
+    );
+    assert_eq!(
+        GroupBy::for_period(&DateFilter::range(None, date(6, 30)), &week),
+        GroupBy::Week
+    );
+}
+
+#[test]
+fn test_group_by_option() {
+    let matches = cli::build_cli().get_matches_from(["cc2report", "--group-by", "week"]);
+    let config = Config::from_args(&matches).unwrap();
+    assert_eq!(config.output.group_by, Some(GroupBy::Week));
+
+    assert!(cli::build_cli()
+        .try_get_matches_from(["cc2report", "--group-by", "month"])
+        .is_err());
+}
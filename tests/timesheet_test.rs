@@ -0,0 +1,117 @@
+mod common;
+
+use cc2report::cli;
+use cc2report::config::{Config, OutputFormat};
+use cc2report::date_filter::{DateFilter, ReportTimeZone};
+use cc2report::project_filter::ProjectFilter;
+use cc2report::smart_analyzer::SmartReport;
+use cc2report::templates::Templates;
+use cc2report::timesheet::{to_csv, to_ics};
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use common::TempDir;
+
+/// Three sessions of `/srv/shop`, the second one summarized by Claude Code and
+/// interrupted by a long pause, the third with a summary that looks like a formula
+async fn report(name: &str) -> SmartReport {
+    let root = TempDir::new(&format!("timesheet-{name}"));
+    let entry = |session: &str, uuid: &str, parent: &str, time: &str, content: &str| {
+        format!(
+            r#"{{"parentUuid":{parent},"sessionId":"{session}","cwd":"/srv/shop","type":"user","message":{{"role":"user","content":"{content}"}},"uuid":"{uuid}","timestamp":"2025-06-29T{time}:00.000Z"}}"#
+        )
+    };
+    let summary = r#"{"type":"summary","summary":"Checkout, \"express\" shipping and a very long tail of follow-up work on the order confirmation page","leafUuid":"b-3"}"#;
+    let formula = r#"{"type":"summary","summary":"=SUM(A1:A9)","leafUuid":"c-1"}"#;
+    let lines = [
+        entry("s-a", "a-1", "null", "08:00", "Fix the cart total"),
+        entry("s-a", "a-2", "\"a-1\"", "08:10", "Round to cents"),
+        entry("s-b", "b-1", "null", "13:00", "Add express shipping"),
+        entry("s-b", "b-2", "\"b-1\"", "13:05", "Use the new rates"),
+        entry(
+            "s-b",
+            "b-3",
+            "\"b-2\"",
+            "14:30",
+            "Show it on the confirmation",
+        ),
+        entry("s-c", "c-1", "null", "17:00", "Export the totals"),
+        summary.to_string(),
+        formula.to_string(),
+    ];
+    root.write_log("logs/-srv-shop/s.jsonl", &lines);
+
+    analyze_conversations_with_ai(
+        &root.join("logs"),
+        &root.join("index"),
+        DateFilter::all().with_tz(ReportTimeZone::Named(chrono_tz::Europe::Berlin)),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_csv_has_one_row_per_block_of_work() {
+    let csv = to_csv(&report("csv").await);
+    let rows: Vec<&str> = csv.split("\r\n").collect();
+
+    assert_eq!(
+        rows[0],
+        "project,session_id,start,end,active_minutes,summary"
+    );
+    assert!(rows[1].starts_with("/srv/shop,s-a,2025-06-29 10:00,2025-06-29 10:25,25,"));
+    // The pause in s-b is not billed; every block ends one idle gap after its last message
+    let checkout = "\"Checkout, \"\"express\"\" shipping and a very long tail of follow-up work on the order confirmation page\"";
+    assert_eq!(
+        rows[2],
+        format!("/srv/shop,s-b,2025-06-29 15:00,2025-06-29 15:20,20,{checkout}")
+    );
+    assert_eq!(
+        rows[3],
+        format!("/srv/shop,s-b,2025-06-29 16:30,2025-06-29 16:45,15,{checkout}")
+    );
+    assert_eq!(
+        rows[4],
+        "/srv/shop,s-c,2025-06-29 19:00,2025-06-29 19:15,15,'=SUM(A1:A9)"
+    );
+    assert_eq!(rows[5], "");
+}
+
+#[tokio::test]
+async fn test_ics_has_one_event_per_block_of_work() {
+    let ics = to_ics(&report("ics").await, "en");
+
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 4);
+    assert!(ics.contains("UID:s-b-20250629T130000Z@cc2report\r\n"));
+    assert!(ics.contains("DTSTART:20250629T130000Z\r\nDTEND:20250629T132000Z\r\n"));
+    assert!(ics.contains("DTSTART:20250629T143000Z\r\nDTEND:20250629T144500Z\r\n"));
+    assert!(ics.lines().all(|line| line.len() <= 75));
+
+    // Long values are folded onto continuation lines and TEXT is escaped
+    let unfolded = ics.replace("\r\n ", "");
+    assert!(unfolded.contains(
+        "SUMMARY:/srv/shop: Checkout\\, \"express\" shipping and a very long tail of follow-up work on the order confirmation page\r\n"
+    ));
+    assert!(unfolded.contains("\\n20 min\\, 2 messages\r\n"));
+}
+
+#[test]
+fn test_timesheet_formats_are_accepted() {
+    for (format, expected) in [("csv", OutputFormat::Csv), ("ics", OutputFormat::Ics)] {
+        let matches = cli::build_cli().get_matches_from(["cc2report", "--format", format]);
+        let config = Config::from_args(&matches).unwrap();
+        assert_eq!(
+            std::mem::discriminant(&config.output.format),
+            std::mem::discriminant(&expected)
+        );
+    }
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use cc2report::date_filter::{DateFilter, ReportTimeZone};
+use cc2report::project_filter::ProjectFilter;
+use cc2report::smart_analyzer::generate_smart_report;
+use cc2report::templates::Templates;
+use cc2report::work_report_v2::analyze_conversations_with_ai;
+use cc2report::work_time::{format_minutes, ActiveTime};
+use chrono::{DateTime, Duration, Utc};
+use common::TempDir;
+
+const UTC: ReportTimeZone = ReportTimeZone::Named(chrono_tz::UTC);
+
+fn at(time: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(&format!("2025-06-{time}:00Z"))
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn timestamps() -> Vec<DateTime<Utc>> {
+    // Out of order on purpose
+    [
+        "28T11:05", "28T10:00", "28T10:10", "28T10:20", "28T11:00", "29T09:00", "29T09:30",
+        "29T09:15",
+    ]
+    .into_iter()
+    .map(at)
+    .collect()
+}
+
+#[test]
+fn test_pauses_longer_than_the_idle_gap_split_blocks() {
+    let active = ActiveTime::from_timestamps(timestamps(), Duration::minutes(15), UTC).unwrap();
+
+    // Every block ends one idle gap after its last message
+    assert_eq!(active.minutes, 100);
+    assert_eq!(active.blocks, 3);
+    assert_eq!(active.first_activity, "2025-06-28T10:00:00+00:00");
+    assert_eq!(active.last_activity, "2025-06-29T09:30:00+00:00");
+    assert_eq!(active.longest_block.minutes, 45);
+    assert_eq!(active.longest_block.start, "2025-06-29T09:00:00+00:00");
+    assert_eq!(active.span(), "06-28 10:00–06-29 09:30");
+
+    let days: Vec<(&str, i64, usize)> = active
+        .days
+        .iter()
+        .map(|day| (day.date.as_str(), day.minutes, day.blocks))
+        .collect();
+    assert_eq!(days, [("2025-06-28", 55, 2), ("2025-06-29", 45, 1)]);
+    assert_eq!(active.days[0].longest_block_minutes, 35);
+
+    // A longer gap joins the first day into one block
+    let relaxed = ActiveTime::from_timestamps(timestamps(), Duration::minutes(60), UTC).unwrap();
+    assert_eq!(relaxed.days[0].minutes, 125);
+    assert_eq!(relaxed.longest_block.minutes, 125);
+
+    assert!(ActiveTime::from_timestamps([], Duration::minutes(15), UTC).is_none());
+    assert_eq!(format_minutes(45), "45m");
+    assert_eq!(format_minutes(185), "3h 05m");
+}
+
+#[test]
+fn test_blocks_continue_past_midnight() {
+    let late = ["28T23:50", "29T00:00", "29T00:10"].map(at);
+    let active = ActiveTime::from_timestamps(late, Duration::minutes(15), UTC).unwrap();
+
+    assert_eq!(active.minutes, 35);
+    assert_eq!(active.blocks, 1);
+    assert_eq!(active.longest_block.minutes, 35);
+    let days: Vec<(&str, i64, usize)> = active
+        .days
+        .iter()
+        .map(|day| (day.date.as_str(), day.minutes, day.blocks))
+        .collect();
+    assert_eq!(days, [("2025-06-28", 10, 1), ("2025-06-29", 25, 1)]);
+    assert_eq!(active.days[1].first_activity, "2025-06-29T00:00:00+00:00");
+
+    // Midnight is taken in the report time zone: 23:50-00:10 UTC is 01:50-02:10 in Berlin
+    let berlin = ReportTimeZone::Named(chrono_tz::Europe::Berlin);
+    let active = ActiveTime::from_timestamps(late, Duration::minutes(15), berlin).unwrap();
+    assert_eq!(active.days.len(), 1);
+    assert_eq!(active.days[0].minutes, 35);
+}
+
+#[test]
+fn test_a_lone_message_counts_as_one_idle_gap_like_every_block() {
+    let active = ActiveTime::from_timestamps(
+        ["29T09:00", "29T10:00", "29T10:05"].map(at),
+        Duration::minutes(15),
+        UTC,
+    )
+    .unwrap();
+    assert_eq!(active.minutes, 35);
+    assert_eq!(active.blocks, 2);
+    assert_eq!(active.work_blocks[0].start, "2025-06-29T09:00:00+00:00");
+    assert_eq!(active.work_blocks[0].end, "2025-06-29T09:15:00+00:00");
+    // Two messages five minutes apart count five minutes more than one message
+    assert_eq!(active.longest_block.start, "2025-06-29T10:00:00+00:00");
+    assert_eq!(active.longest_block.end, "2025-06-29T10:20:00+00:00");
+    assert_eq!(active.last_activity, "2025-06-29T10:05:00+00:00");
+
+    // The idle time after a message just before midnight belongs to the next day
+    let active = ActiveTime::from_timestamps([at("28T23:55")], Duration::minutes(15), UTC).unwrap();
+    let days: Vec<(&str, i64)> = active
+        .days
+        .iter()
+        .map(|day| (day.date.as_str(), day.minutes))
+        .collect();
+    assert_eq!(days, [("2025-06-28", 5), ("2025-06-29", 10)]);
+    assert_eq!(active.days[1].first_activity, "2025-06-29T00:00:00+00:00");
+    assert_eq!(active.days[1].last_activity, "2025-06-29T00:10:00+00:00");
+}
+
+#[tokio::test]
+async fn test_parallel_projects_count_once_in_the_total() {
+    let root = TempDir::new("work-time");
+    for (project, times) in [
+        ("-srv-api", ["10:00", "10:30"]),
+        ("-srv-web", ["10:15", "10:40"]),
+    ] {
+        let cwd = project.replace('-', "/");
+        let lines: Vec<String> = times
+            .iter()
+            .enumerate()
+            .map(|(index, time)| {
+                let parent = if index == 0 {
+                    "null".to_string()
+                } else {
+                    format!("\"{project}-{}\"", index - 1)
+                };
+                format!(
+                    r#"{{"parentUuid":{parent},"sessionId":"{project}","cwd":"{cwd}","type":"user","message":{{"role":"user","content":"Step {index}"}},"uuid":"{project}-{index}","timestamp":"2025-06-29T{time}:00.000Z"}}"#
+                )
+            })
+            .collect();
+        root.write_log(&format!("logs/{project}/s.jsonl"), &lines);
+    }
+
+    let report = analyze_conversations_with_ai(
+        &root.join("logs"),
+        &root.join("index"),
+        DateFilter::all().with_tz(UTC),
+        &ProjectFilter::all(),
+        None,
+        &Templates::default(),
+        "en",
+        true,
+        1,
+        false,
+        15,
+        false,
+    )
+    .await
+    .unwrap();
+
+    // Each project alone pauses for 25-30 minutes, so every message is a block of one idle
+    // gap; together the messages are at most 15 minutes apart and form one block
+    for project in &report.projects {
+        assert_eq!(project.active_time.as_ref().unwrap().minutes, 30);
+    }
+    let total = report.active_time.as_ref().unwrap();
+    assert_eq!(total.minutes, 55);
+    assert_eq!(total.blocks, 1);
+
+    let output = root.join("report.md");
+    generate_smart_report(&report, Some(&output), "en").unwrap();
+    let markdown = std::fs::read_to_string(&output).unwrap();
+
+    assert!(markdown.contains("**Active time**: 30m (10:00–10:30, longest block 15m)"));
+    assert!(markdown.contains("## Working time"));
+    assert!(markdown.contains("| 2025-06-29 | 55m | 10:00 | 10:40 | 55m |"));
+}